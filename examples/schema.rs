@@ -1,5 +1,5 @@
 use cosmwasm_schema::write_api;
-use grant_dao::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use grant_dao::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SudoMsg};
 
 //run cargo schema to generate
 fn main() {
@@ -7,5 +7,7 @@ fn main() {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
+        sudo: SudoMsg,
     }
 }
@@ -0,0 +1,189 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::ContractError;
+
+/// Characters a `BoundedString` may hold
+pub trait Charset {
+    const DESCRIPTION: &'static str;
+
+    fn allows(c: char) -> bool;
+}
+
+/// Free text, newlines and tabs allowed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Text;
+
+/// A single line of text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Line;
+
+/// No whitespace at all, for URIs and hashes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token;
+
+impl Charset for Text {
+    const DESCRIPTION: &'static str = "control characters other than newlines and tabs";
+
+    fn allows(c: char) -> bool {
+        !c.is_control() || c == '\n' || c == '\t'
+    }
+}
+
+impl Charset for Line {
+    const DESCRIPTION: &'static str = "control characters";
+
+    fn allows(c: char) -> bool {
+        !c.is_control()
+    }
+}
+
+impl Charset for Token {
+    const DESCRIPTION: &'static str = "whitespace or control characters";
+
+    fn allows(c: char) -> bool {
+        !c.is_control() && !c.is_whitespace()
+    }
+}
+
+/// String of `MIN..=MAX` bytes drawn from charset `C`, checked whenever one is built or
+/// deserialized so handlers and stored state never see anything else
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoundedString<C, const MIN: usize, const MAX: usize>(String, PhantomData<C>);
+
+pub type Title = BoundedString<Line, 1, 140>;
+pub type Description = BoundedString<Text, 0, 4096>;
+pub type Uri = BoundedString<Token, 1, 256>;
+pub type DisplayName = BoundedString<Line, 1, 64>;
+pub type ContentHash = BoundedString<Token, 1, 128>;
+pub type Reason = BoundedString<Text, 1, 280>;
+pub type FrontendId = BoundedString<Token, 1, 64>;
+pub type GroupId = BoundedString<Token, 1, 64>;
+
+impl<C: Charset, const MIN: usize, const MAX: usize> BoundedString<C, MIN, MAX> {
+    pub fn new(value: impl Into<String>) -> Result<Self, ContractError> {
+        let value = value.into();
+        if value.len() < MIN || value.len() > MAX {
+            return Err(ContractError::InvalidInput(format!(
+                "expected {} to {} bytes, got {}",
+                MIN,
+                MAX,
+                value.len()
+            )));
+        }
+        if !value.chars().all(C::allows) {
+            return Err(ContractError::InvalidInput(format!(
+                "{} are not allowed",
+                C::DESCRIPTION
+            )));
+        }
+        Ok(BoundedString(value, PhantomData))
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> BoundedString<C, MIN, MAX> {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl<C: Charset, const MIN: usize, const MAX: usize> FromStr for BoundedString<C, MIN, MAX> {
+    type Err = ContractError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> AsRef<str> for BoundedString<C, MIN, MAX> {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> fmt::Display for BoundedString<C, MIN, MAX> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> PartialEq<&str> for BoundedString<C, MIN, MAX> {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> PartialEq<BoundedString<C, MIN, MAX>> for &str {
+    fn eq(&self, other: &BoundedString<C, MIN, MAX>) -> bool {
+        *self == other.0
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> Serialize for BoundedString<C, MIN, MAX> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de, C: Charset, const MIN: usize, const MAX: usize> Deserialize<'de>
+    for BoundedString<C, MIN, MAX>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Self::new(value).map_err(|err| match err {
+            ContractError::InvalidInput(reason) => de::Error::custom(reason),
+            err => de::Error::custom(err),
+        })
+    }
+}
+
+impl<C, const MIN: usize, const MAX: usize> JsonSchema for BoundedString<C, MIN, MAX> {
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{from_json, to_json_binary};
+
+    #[test]
+    fn bounds_and_charset() {
+        assert!(Title::new("Round 1").is_ok());
+        assert!(Title::new("").is_err());
+        assert!(Title::new("a".repeat(141)).is_err());
+        assert!(Title::new("two\nlines").is_err());
+
+        assert!(Description::new("").is_ok());
+        assert!(Description::new("two\nlines\tand a tab").is_ok());
+        assert!(Description::new("bell\u{7}").is_err());
+
+        assert!(Uri::new("ipfs://avatar").is_ok());
+        assert!(Uri::new("ipfs://my avatar").is_err());
+    }
+
+    #[test]
+    fn checked_on_deserialize() {
+        let title: Title = from_json(br#""Round 1""#).unwrap();
+        assert_eq!("Round 1", title);
+        assert_eq!(to_json_binary(&title).unwrap().as_slice(), br#""Round 1""#);
+
+        let oversized = to_json_binary(&"a".repeat(141)).unwrap();
+        from_json::<Title>(&oversized).unwrap_err();
+    }
+}
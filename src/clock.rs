@@ -0,0 +1,183 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{BlockInfo, StdError, StdResult, Timestamp};
+use cw_utils::Scheduled;
+
+/// Unit delays and deadlines are measured in
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum ClockMode {
+    /// Seconds of block time
+    #[default]
+    Time,
+    /// Blocks, for chains whose block times are too irregular to schedule by
+    Height,
+}
+
+/// `env.block` read in the configured mode, every delay the contract schedules goes through here
+pub struct Clock<'a> {
+    mode: ClockMode,
+    block: &'a BlockInfo,
+}
+
+impl<'a> Clock<'a> {
+    pub fn new(mode: ClockMode, block: &'a BlockInfo) -> Self {
+        Clock { mode, block }
+    }
+
+    pub fn now(&self) -> Scheduled {
+        self.after(0)
+    }
+
+    /// `delay` seconds or blocks from now
+    pub fn after(&self, delay: u64) -> Scheduled {
+        self.after_block(self.block.height, self.block.time, delay)
+    }
+
+    /// `delay` seconds or blocks from an earlier block, such as the one a proposal executed in
+    pub fn after_block(&self, height: u64, time: Timestamp, delay: u64) -> Scheduled {
+        match self.mode {
+            ClockMode::Time => Scheduled::AtTime(time.plus_seconds(delay)),
+            ClockMode::Height => Scheduled::AtHeight(height + delay),
+        }
+    }
+}
+
+/// `delay` seconds or blocks after `at`, in whichever unit `at` was scheduled in
+pub fn delayed(at: Scheduled, delay: u64) -> Scheduled {
+    match at {
+        Scheduled::AtTime(time) => Scheduled::AtTime(time.plus_seconds(delay)),
+        Scheduled::AtHeight(height) => Scheduled::AtHeight(height + delay),
+    }
+}
+
+/// Seconds or blocks from the current block until `at`, in whichever unit it was scheduled in,
+/// 0 once it has passed
+pub fn remaining(block: &BlockInfo, at: Scheduled) -> u64 {
+    match at {
+        Scheduled::AtTime(time) => time.seconds().saturating_sub(block.time.seconds()),
+        Scheduled::AtHeight(height) => height.saturating_sub(block.height),
+    }
+}
+
+/// How far the current block is into `[start, end]`, as `(elapsed, total)` in the span's own unit
+pub fn progress(block: &BlockInfo, start: &Scheduled, end: &Scheduled) -> StdResult<(u64, u64)> {
+    let (now, start, end) = match (start, end) {
+        (Scheduled::AtTime(start), Scheduled::AtTime(end)) => {
+            (block.time.seconds(), start.seconds(), end.seconds())
+        }
+        (Scheduled::AtHeight(start), Scheduled::AtHeight(end)) => (block.height, *start, *end),
+        _ => return Err(StdError::generic_err("span mixes heights and times")),
+    };
+    Ok((now.clamp(start, end) - start, end - start))
+}
+
+/// A deadline in both units, whichever it was not scheduled in estimated from the expected
+/// block time
+#[cw_serde]
+pub struct Deadline {
+    pub at: Scheduled,
+    pub height: Option<u64>,
+    pub time: Option<Timestamp>,
+}
+
+/// Without an expected block time only the unit `at` was scheduled in is known
+pub fn deadline(block: &BlockInfo, at: Scheduled, expected_block_time: Option<u64>) -> Deadline {
+    let block_time = expected_block_time.filter(|seconds| *seconds > 0);
+    let (height, time) = match at {
+        Scheduled::AtHeight(height) => {
+            let time = block_time.map(|seconds| {
+                if height >= block.height {
+                    block.time.plus_seconds((height - block.height) * seconds)
+                } else {
+                    block.time.minus_seconds((block.height - height) * seconds)
+                }
+            });
+            (Some(height), time)
+        }
+        Scheduled::AtTime(time) => {
+            let now = block.time.seconds();
+            let height = block_time.map(|seconds| {
+                if time.seconds() >= now {
+                    block.height + (time.seconds() - now).div_ceil(seconds)
+                } else {
+                    block
+                        .height
+                        .saturating_sub((now - time.seconds()) / seconds)
+                }
+            });
+            (height, Some(time))
+        }
+    };
+    Deadline { at, height, time }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::Timestamp;
+
+    #[test]
+    fn schedules_in_either_mode() {
+        let block = mock_env().block;
+
+        let time = Clock::new(ClockMode::Time, &block);
+        assert_eq!(
+            Scheduled::AtTime(block.time.plus_seconds(60)),
+            time.after(60)
+        );
+
+        let height = Clock::new(ClockMode::Height, &block);
+        assert_eq!(Scheduled::AtHeight(block.height + 10), height.after(10));
+        assert!(height.now().is_triggered(&block));
+        assert!(!height.after(1).is_triggered(&block));
+
+        // a delay keeps the unit it is added to
+        assert_eq!(time.after(90), delayed(time.after(60), 30));
+        assert_eq!(height.after(15), delayed(height.after(10), 5));
+
+        assert_eq!(60, remaining(&block, time.after(60)));
+        assert_eq!(0, remaining(&block, Scheduled::AtHeight(block.height - 1)));
+    }
+
+    #[test]
+    fn progress_clamps_to_span() {
+        let block = mock_env().block;
+
+        let start = Scheduled::AtHeight(block.height - 5);
+        let end = Scheduled::AtHeight(block.height + 15);
+        assert_eq!((5, 20), progress(&block, &start, &end).unwrap());
+
+        let start = Scheduled::AtTime(block.time.plus_seconds(10));
+        let end = Scheduled::AtTime(block.time.plus_seconds(20));
+        assert_eq!((0, 10), progress(&block, &start, &end).unwrap());
+
+        let end = Scheduled::AtTime(Timestamp::from_seconds(0));
+        progress(&block, &Scheduled::AtHeight(0), &end).unwrap_err();
+    }
+
+    #[test]
+    fn deadline_in_both_units() {
+        let block = mock_env().block;
+
+        let at = Scheduled::AtHeight(block.height + 10);
+        assert_eq!(
+            Deadline {
+                at,
+                height: Some(block.height + 10),
+                time: Some(block.time.plus_seconds(60)),
+            },
+            deadline(&block, at, Some(6))
+        );
+
+        // partial blocks round up, the deadline is not reached before then
+        let at = Scheduled::AtTime(block.time.plus_seconds(61));
+        assert_eq!(
+            Some(block.height + 11),
+            deadline(&block, at, Some(6)).height
+        );
+
+        assert_eq!(None, deadline(&block, at, None).height);
+        assert_eq!(None, deadline(&block, at, Some(0)).height);
+    }
+}
@@ -1,42 +1,202 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::convert::TryFrom;
 
 use cosmwasm_std::{
-    entry_point, to_json_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo,
-    Order, Response, StdResult, Uint128,
+    entry_point, from_json, to_json_binary, to_json_vec, Addr, Api, BankMsg, Binary, BlockInfo,
+    Coin, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, Event, HexBinary, MessageInfo, Order,
+    Reply, Response, StdError, StdResult, Storage, SubMsg, Timestamp, Uint128, Uint64, WasmMsg,
 };
 use cw2::set_contract_version;
-use cw_storage_plus::Bound;
+use cw_storage_plus::{Bound, Map, Prefix, PrimaryKey};
+use cw_utils::{must_pay, Expiration, Scheduled};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
+use crate::bounded::{
+    ContentHash, Description, DisplayName, FrontendId, GroupId, Reason, Title, Uri,
+};
+use crate::clock::{deadline, delayed, progress, remaining, Clock, ClockMode, Deadline};
+use crate::cw20::{cw20_balance, cw20_outgoing, Cw20ReceiveMsg};
+use crate::cw3::{self, Cw3ExecuteMsg};
+use crate::dex::swap_msg;
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{Member, Proposal, MEMBERS, PROPOSALS};
+use crate::membership::{MemberChangedHookMsg, MemberDiff, MembershipSource};
+use crate::msg::{
+    AnalyticsResponse, CanProposeResponse, CanVoteResponse, Cw20Balance, ExecuteMsg,
+    ExportBallotsResponse, ExportedBallot, FrontendResponse, GrantStatusResponse, ImportedProposal,
+    InstantiateMsg, KeyRotationResponse, ListAnnouncementsResponse, ListFrontendsResponse,
+    ListGroupsResponse, ListMembersResponse, ListProposalsResponse, ListSecurityPayoutsResponse,
+    ListVotesResponse, MemberResponse, MigrateMsg, NotifierMsg, OutboxResponse, Participation,
+    ParticipationSnapshotResponse, PendingConfigResponse, ProposalEvent, ProposalRecord,
+    ProposalResponse, ProposalSelection, QueryMsg, RawEntry, SecurityPayoutResponse,
+    ShadowLedgerResponse, ShadowPayout, StorageStatsResponse, SudoMsg, SuspensionResponse,
+    TreasuryBalancesResponse, TreasuryStatsResponse, VestingResponse, VoteProofResponse,
+};
+use crate::state::{
+    ballot_key, member_key, total_weight_key, Announcement, ArchivedProposal, Ballot, Bounty,
+    Config, ConfigUpdate, Deposit, Execution, Frontend, GrantPayout, Group, GroupMember,
+    GroupTally, KeyRotation, Member, OutboxEntry, PendingConfig, PeriodStats, Profile, Proposal,
+    ProposalStatus, ProposalType, QuorumDecay, RewardDistribution, SecurityLane, SecurityPayout,
+    StorageUsage, Suspension, Tally, TieBreak, Vesting, ANALYTICS, ANNOUNCEMENTS, ARCHIVE, BALLOTS,
+    CARRIED_VOTES, CLAIM_WINDOWS, COMMITTED, CONFIG, CW20_TREASURY, DEFAULT_DENOM, DELEGATIONS,
+    DELEGATORS, DISCLOSED_BALLOTS, EXECUTED_AT, FRONTENDS, GRANTED, GROUPS, HELD_DEPOSITS,
+    KEY_ROTATIONS, MEMBERS, MEMBERS_BY_WEIGHT, MEMBER_GROUPS, MIRRORED_PROPOSALS, NEXT_OUTBOX_ID,
+    NEXT_PROPOSAL_ID, NEXT_SECURITY_PAYOUT_ID, OUTBOX, PAYOUTS_BY_MEMBER, PENDING_CONFIG, PROFILES,
+    PROPOSALS, REFERRAL_FEES, REWARD_DISTRIBUTIONS, REWARD_PAYOUTS, ROTATED_FROM, SECURITY_PAYOUTS,
+    SECURITY_SPENT, SHADOW_LEDGER, SHADOW_SPENT, STORAGE_USAGE, SUSPENSIONS, TALLIES, TOTAL_WEIGHT,
+    VESTINGS, VOTE_OPERATORS, VOTING_POWER,
+};
+use crate::tally::{VotingStrategy, VotingWindow};
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-const DENOM: &str = "udevcore";
-
 // pagination info for queries
 const MAX_PAGE_LIMIT: u32 = 250;
 
+// how long after the voting deadline finalizing a proposal earns its bounty, in seconds
+const BOUNTY_WINDOW: u64 = 3600;
+
+// length of the periods analytics are rolled up over, in seconds of block time
+const ANALYTICS_PERIOD: u64 = 30 * 86_400;
+
+// proposals stored per ImportProposals call
+const MAX_IMPORT_BATCH: usize = 50;
+
+// payouts in one GrantBatch proposal
+const MAX_GRANT_PAYOUTS: usize = 50;
+
+// proposals executed per ExecuteBatch call
+const MAX_EXECUTE_BATCH: usize = 20;
+
+// delegators one member can vote for, each costs their delegate a read per vote
+const MAX_DELEGATORS: usize = 30;
+
+// upper bound on proposals aggregated by a single participation snapshot
+const MAX_SNAPSHOT_PROPOSALS: usize = 100;
+
+// STORAGE_USAGE keys
+const USAGE_PROPOSALS: &str = "proposals";
+const USAGE_BALLOTS: &str = "ballots";
+const USAGE_MEMBERS: &str = "members";
+const USAGE_ARCHIVE: &str = "archive";
+
+// proposals archived per ArchiveProposals call
+const DEFAULT_ARCHIVE_LIMIT: u32 = 30;
+const MAX_ARCHIVE_LIMIT: u32 = 100;
+
+// reward payouts sent per PayRewards call
+const DEFAULT_PAYOUT_LIMIT: u32 = 30;
+const MAX_PAYOUT_LIMIT: u32 = 100;
+
+// largest share of a grant a frontend can be paid for referring it
+const MAX_REFERRAL_FEE: Decimal = Decimal::percent(10);
+
+// proposals backfilled per migrate call
+const DEFAULT_MIGRATE_LIMIT: u32 = 50;
+const MAX_MIGRATE_LIMIT: u32 = 200;
+
+// most recent proposals whose tallies AssertInvariants recounts
+const INVARIANT_SAMPLE: usize = 20;
+
+// reply ids
+const NOTIFY_REPLY_ID: u64 = 1;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    // the same rules a membership batch is held to, and a DAO needs someone to govern it
+    if msg.members.is_empty() {
+        return Err(ContractError::InvalidInput(
+            "at least one member is required".to_string(),
+        ));
+    }
+    validate_member_changes(deps.api, &msg.members, &[], &[])?;
+
+    let notifier = msg
+        .notifier
+        .map(|notifier| deps.api.addr_validate(&notifier))
+        .transpose()?;
+    let admin = msg
+        .admin
+        .map(|admin| deps.api.addr_validate(&admin))
+        .transpose()?;
+    let parent_dao = msg
+        .parent_dao
+        .map(|parent| deps.api.addr_validate(&parent))
+        .transpose()?;
+    let guardian = msg
+        .guardian
+        .map(|guardian| deps.api.addr_validate(&guardian))
+        .transpose()?;
+    let config = Config {
+        denom: msg.denom.unwrap_or_else(|| DEFAULT_DENOM.to_string()),
+        notifier,
+        receipt_salt: msg.receipt_salt,
+        admin,
+        key_rotation_delay: msg.key_rotation_delay,
+        clock: msg.clock,
+        expected_block_time: msg.expected_block_time,
+        archive_after: msg.archive_after,
+        referral_fee: msg.referral_fee,
+        parent_dao,
+        voting_period: msg.voting_period,
+        quorum: msg.quorum,
+        threshold: msg.threshold,
+        expense_threshold: msg.expense_threshold,
+        proposal_deposit: msg.proposal_deposit,
+        burn_forfeited_deposits: msg.burn_forfeited_deposits.unwrap_or_default(),
+        tie_break: msg.tie_break.unwrap_or_default(),
+        group_quorums: msg.group_quorums.unwrap_or_default(),
+        shadow_funds: msg.shadow_funds,
+        execution_delay: msg.execution_delay,
+        execution_expiration: msg.execution_expiration,
+        private_ballots: msg.private_ballots.unwrap_or_default(),
+        guardian,
+        quorum_decay: msg.quorum_decay.unwrap_or_default(),
+        max_proposal_amount: msg.max_proposal_amount,
+        lock_self_voted_vesting: msg.lock_self_voted_vesting.unwrap_or_default(),
+        membership: msg.membership.unwrap_or_default(),
+        voting_strategy: msg.voting_strategy.unwrap_or_default(),
+        removal_grace_period: msg.removal_grace_period,
+        private_drafts: msg.private_drafts.unwrap_or_default(),
+        security_lane: msg.security_lane,
+        dex_router: msg.dex_router,
+        cw20_tokens: msg.cw20_tokens.unwrap_or_default(),
+    };
+    validate_config(deps.as_ref(), &config)?;
+    CONFIG.save(deps.storage, &config)?;
 
+    let mut total_weight = Uint128::zero();
     for member in msg.members {
-        MEMBERS.save(
+        let address = deps.api.addr_validate(member.address.as_str())?;
+        tracked_save(
             deps.storage,
-            deps.api.addr_validate(member.address.as_str())?,
+            &MEMBERS,
+            USAGE_MEMBERS,
+            &address,
             &Member {
                 address: member.address.clone(),
                 weight: member.weight,
             },
         )?;
+        VOTING_POWER.save(deps.storage, &address, &member.weight, env.block.height)?;
+        index_weight(deps.storage, &address, None, Some(member.weight))?;
+        total_weight += member.weight;
+    }
+    TOTAL_WEIGHT.save(deps.storage, &total_weight)?;
+
+    for GroupMember { address, group } in msg.groups.unwrap_or_default() {
+        let member = MEMBERS
+            .may_load(deps.storage, &address)?
+            .ok_or_else(|| StdError::generic_err(format!("{} is not a member", address)))?;
+        set_group(deps.storage, &member, Some(group))?;
     }
 
     Ok(Response::new().add_attribute("action", "instantiate"))
@@ -49,339 +209,11285 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    apply_pending_config(deps.storage, &env)?;
+
     match msg {
         ExecuteMsg::Propose {
             title,
             description,
             recipient,
             amount,
-        } => execute_propose(deps, info, title, description, recipient, amount),
+            proposal_type,
+            frontend_id,
+        } => execute_propose(
+            deps,
+            env,
+            info,
+            title,
+            description,
+            recipient,
+            amount,
+            proposal_type,
+            frontend_id,
+        ),
         ExecuteMsg::Vote {
             proposal_id,
             approve,
-        } => execute_vote(deps, info, proposal_id, approve),
-        ExecuteMsg::Execute { proposal_id } => execute_execute(deps, env, proposal_id), // Add env here
+        } => execute_vote(deps, env, info.sender, None, proposal_id, approve),
+        ExecuteMsg::VoteFor {
+            member,
+            proposal_id,
+            approve,
+        } => {
+            if VOTE_OPERATORS.may_load(deps.storage, &member)?.as_ref() != Some(&info.sender) {
+                return Err(ContractError::Unauthorized {});
+            }
+            execute_vote(deps, env, member, Some(info.sender), proposal_id, approve)
+        }
+        ExecuteMsg::SetVoteOperator { operator } => execute_set_vote_operator(deps, info, operator),
+        ExecuteMsg::Delegate { delegate } => execute_delegate(deps, info, delegate),
+        ExecuteMsg::Undelegate {} => execute_undelegate(deps, info),
+        ExecuteMsg::Execute { proposal_id } => execute_execute(deps, env, info, proposal_id),
+        ExecuteMsg::ExecuteBatch { proposal_ids } => {
+            execute_execute_batch(deps, env, info, proposal_ids)
+        }
+        ExecuteMsg::UpdateProfile {
+            display_name,
+            avatar_uri,
+            bio_hash,
+        } => execute_update_profile(deps, info, display_name, avatar_uri, bio_hash),
+        ExecuteMsg::SuspendMember {
+            member,
+            reason,
+            duration,
+        } => execute_suspend_member(deps, env, info, member, reason, duration),
+        ExecuteMsg::RotateKey { new_address } => execute_rotate_key(deps, env, info, new_address),
+        ExecuteMsg::ConfirmKeyRotation {} => execute_confirm_key_rotation(deps, env, info),
+        ExecuteMsg::PayRewards { proposal_id, limit } => {
+            execute_pay_rewards(deps, env, proposal_id, limit)
+        }
+        ExecuteMsg::ClaimRewards {} => execute_claim_rewards(deps, env, info),
+        ExecuteMsg::PruneRemovedMember { address } => {
+            execute_prune_removed_member(deps, env, address)
+        }
+        ExecuteMsg::ClaimVested { proposal_id } => {
+            execute_claim_vested(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::MirrorProposal {
+            parent_proposal_id,
+            title,
+            description,
+            recipient,
+            amount,
+        } => execute_mirror_proposal(
+            deps,
+            env,
+            info,
+            parent_proposal_id,
+            title,
+            description,
+            recipient,
+            amount,
+        ),
+        ExecuteMsg::BreakTie {
+            proposal_id,
+            approve,
+        } => execute_break_tie(deps, env, info, proposal_id, approve),
+        ExecuteMsg::AttachBounty { proposal_id } => {
+            execute_attach_bounty(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::WithdrawDeposit { proposal_id } => {
+            execute_withdraw_deposit(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::Close { proposal_id } => execute_close(deps, env, info, proposal_id),
+        ExecuteMsg::Cancel { proposal_id } => execute_cancel(deps, env, info, proposal_id),
+        ExecuteMsg::VetoProposal { proposal_id } => {
+            execute_veto_proposal(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::ArchiveProposals { start_after, limit } => {
+            execute_archive_proposals(deps, env, start_after, limit)
+        }
+        ExecuteMsg::MemberChangedHook(MemberChangedHookMsg { diffs }) => {
+            execute_member_changed_hook(deps, env, info, diffs)
+        }
+        ExecuteMsg::Receive(receive) => execute_receive(deps, info, receive),
+        ExecuteMsg::AuthorizeSecurityPayout {
+            recipient,
+            amount,
+            report_hash,
+        } => execute_authorize_security_payout(deps, env, info, recipient, amount, report_hash),
+        ExecuteMsg::ApproveSecurityPayout { payout_id } => {
+            execute_approve_security_payout(deps, env, info, payout_id)
+        }
+        ExecuteMsg::ReadProposal { proposal_id } => {
+            execute_read_proposal(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::Cw3(Cw3ExecuteMsg::Propose {
+            title,
+            description,
+            msgs,
+            latest,
+        }) => {
+            if latest.is_some() {
+                return Err(ContractError::InvalidInput(
+                    "proposals close after the voting period, `latest` must be unset".to_string(),
+                ));
+            }
+            // a proposal without messages is a text proposal, published once passed
+            let proposal_type = if msgs.is_empty() {
+                ProposalType::Announce {
+                    title: title.clone(),
+                    body: description.clone(),
+                }
+            } else {
+                ProposalType::Custom { msgs }
+            };
+            execute_propose(
+                deps,
+                env,
+                info,
+                title,
+                description,
+                None,
+                None,
+                Some(proposal_type),
+                None,
+            )
+        }
+        ExecuteMsg::Cw3(Cw3ExecuteMsg::Vote { proposal_id, vote }) => {
+            let approve = match vote {
+                cw3::Vote::Yes => true,
+                cw3::Vote::No | cw3::Vote::Veto => false,
+                cw3::Vote::Abstain => {
+                    return Err(ContractError::InvalidInput(
+                        "abstaining is not supported".to_string(),
+                    ))
+                }
+            };
+            execute_vote(deps, env, info.sender, None, proposal_id, approve)
+        }
+        ExecuteMsg::ImportProposals { proposals } => {
+            execute_import_proposals(deps, env, info, proposals)
+        }
+        ExecuteMsg::OpenDraft { proposal_id } => execute_open_draft(deps, env, info, proposal_id),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn execute_propose(
-    deps: DepsMut,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    title: String,
-    description: String,
-    recipient: Option<Addr>,
+    title: Title,
+    description: Description,
+    recipient: Option<String>,
     amount: Option<Uint128>,
+    proposal_type: Option<ProposalType>,
+    frontend_id: Option<FrontendId>,
 ) -> Result<Response, ContractError> {
-    let member_opt = MEMBERS.load(deps.storage, info.sender.clone());
+    ensure_can_propose(deps.storage, &env, &info.sender)?;
+    if description.as_str().trim().is_empty() {
+        return Err(ContractError::EmptyDescription {});
+    }
+    let recipient = recipient
+        .map(|recipient| deps.api.addr_validate(&recipient))
+        .transpose()?;
 
-    if member_opt.is_err() {
-        return Err(ContractError::Unauthorized {});
+    let proposal_type = proposal_type.unwrap_or_default();
+    // a batch's amount follows from its payouts
+    let amount = match (&proposal_type, amount) {
+        (ProposalType::GrantBatch { payouts }, None) => payouts_total(payouts)?,
+        (_, amount) => amount.unwrap_or_default(),
+    };
+    validate_proposal_type(
+        deps.as_ref(),
+        &env,
+        &proposal_type,
+        amount,
+        recipient.as_ref(),
+    )?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let deposit = match config.proposal_deposit.filter(|amount| !amount.is_zero()) {
+        Some(required) => {
+            let paid = must_pay(&info, &config.denom)?;
+            if paid != required {
+                return Err(ContractError::InvalidInput(format!(
+                    "a proposal deposit of {}{} is required",
+                    required, config.denom
+                )));
+            }
+            let held = HELD_DEPOSITS.may_load(deps.storage)?.unwrap_or_default();
+            HELD_DEPOSITS.save(deps.storage, &(held + paid))?;
+            Some(Deposit {
+                depositor: info.sender.clone(),
+                amount: paid,
+                settled: false,
+            })
+        }
+        None => None,
+    };
+
+    if let Some(frontend_id) = &frontend_id {
+        if !FRONTENDS.has(deps.storage, frontend_id.as_str()) {
+            return Err(ContractError::InvalidInput(format!(
+                "frontend {} is not registered",
+                frontend_id
+            )));
+        }
+    }
+
+    let proposal = create_proposal(
+        deps.branch(),
+        &env,
+        title,
+        description,
+        recipient.unwrap_or(info.sender.clone()),
+        amount,
+        proposal_type,
+        frontend_id,
+        None,
+        Some(info.sender),
+        deposit,
+    )?;
+
+    let mut response = Response::default();
+    if let Some(frontend_id) = &proposal.frontend_id {
+        response = response.add_attribute("frontend_id", frontend_id.as_str());
+    }
+    Ok(response.add_submessages(notify(deps.as_ref(), proposal.id, ProposalEvent::Opened)?))
+}
+
+// checks a proposal's payload before it is stored, whether proposed or imported
+fn validate_proposal_type(
+    deps: Deps,
+    env: &Env,
+    proposal_type: &ProposalType,
+    amount: Uint128,
+    recipient: Option<&Addr>,
+) -> Result<(), ContractError> {
+    if let Some(cap) = CONFIG.load(deps.storage)?.max_proposal_amount {
+        if amount > cap {
+            return Err(ContractError::AmountAboveCap { amount, cap });
+        }
+    }
+    match proposal_type {
+        ProposalType::VestedGrant { upfront, duration } => {
+            if *upfront > amount {
+                return Err(ContractError::InvalidInput(
+                    "upfront exceeds the grant amount".to_string(),
+                ));
+            }
+            if *duration == 0 {
+                return Err(ContractError::InvalidInput(
+                    "vesting duration must be positive".to_string(),
+                ));
+            }
+        }
+        ProposalType::Grant | ProposalType::Announce { .. } => {}
+        ProposalType::GrantBatch { payouts } => {
+            if payouts.is_empty() || payouts.len() > MAX_GRANT_PAYOUTS {
+                return Err(ContractError::InvalidInput(format!(
+                    "expected 1 to {} payouts",
+                    MAX_GRANT_PAYOUTS
+                )));
+            }
+            for payout in payouts {
+                deps.api.addr_validate(payout.recipient.as_str())?;
+                if payout.amount.is_zero() {
+                    return Err(ContractError::InvalidInput(format!(
+                        "the payout to {} is empty",
+                        payout.recipient
+                    )));
+                }
+            }
+            if payouts_total(payouts)? != amount {
+                return Err(ContractError::InvalidInput(
+                    "a batch's amount has to be the total of its payouts".to_string(),
+                ));
+            }
+        }
+        ProposalType::Signal {} => {
+            if !amount.is_zero() {
+                return Err(ContractError::InvalidInput(
+                    "a signaling proposal moves no funds".to_string(),
+                ));
+            }
+        }
+        ProposalType::Expense { .. } => {
+            if recipient.is_none() {
+                return Err(ContractError::InvalidInput(
+                    "an expense has to name the vendor as recipient".to_string(),
+                ));
+            }
+        }
+        ProposalType::DiscloseBallots { proposal_id } => {
+            if !TALLIES.has(deps.storage, *proposal_id) {
+                return Err(ContractError::ProposalDoesNotExist {});
+            }
+        }
+        ProposalType::UpdateMembersBatch {
+            add,
+            remove,
+            update,
+        } => validate_member_changes(deps.api, add, remove, update)?,
+        ProposalType::ConfirmSuspension { member } => {
+            deps.api.addr_validate(member.as_str())?;
+        }
+        ProposalType::DistributeRewards {
+            start,
+            end,
+            min_participation,
+            ..
+        } => {
+            if start >= end {
+                return Err(ContractError::InvalidInput(
+                    "reward period must end after it starts".to_string(),
+                ));
+            }
+            if *min_participation > Decimal::one() {
+                return Err(ContractError::InvalidInput(
+                    "min_participation cannot exceed 100%".to_string(),
+                ));
+            }
+            // nothing more can be executed in a period that is over, so the size checked here
+            // is the one the distribution is computed over
+            if *end > env.block.time {
+                return Err(ContractError::InvalidInput(
+                    "reward period must be over before it is proposed".to_string(),
+                ));
+            }
+            if executed_between(deps.storage, *start, *end)?.len() > MAX_SNAPSHOT_PROPOSALS {
+                return Err(ContractError::InvalidInput(format!(
+                    "reward period covers more than {} proposals",
+                    MAX_SNAPSHOT_PROPOSALS
+                )));
+            }
+        }
+        ProposalType::UpdateConfig { config, .. } => {
+            validate_config(deps, config)?;
+            // outstanding vestings and rewards were funded in the current denom
+            if config.denom != CONFIG.load(deps.storage)?.denom {
+                return Err(ContractError::InvalidInput(
+                    "the denom cannot be changed after instantiation".to_string(),
+                ));
+            }
+        }
+        ProposalType::ParentVote { .. } => {
+            parent_dao(deps.storage)?;
+        }
+        ProposalType::UpdateFrontends { register, .. } => {
+            for frontend in register {
+                deps.api.addr_validate(frontend.operator.as_str())?;
+            }
+        }
+        ProposalType::UpdateGroups { assign, unassign } => {
+            let mut seen = HashSet::new();
+            for address in assign.iter().map(|m| &m.address).chain(unassign) {
+                deps.api
+                    .addr_validate(address.as_str())
+                    .map_err(|_| invalid_member_entry(address, "invalid address"))?;
+                if !seen.insert(address) {
+                    return Err(invalid_member_entry(address, "listed more than once"));
+                }
+            }
+        }
+        ProposalType::Custom { msgs } => {
+            if msgs.is_empty() {
+                return Err(ContractError::InvalidInput(
+                    "a custom proposal needs at least one message".to_string(),
+                ));
+            }
+        }
+        ProposalType::Swap {
+            offer,
+            ask_denom,
+            min_receive,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            if config.dex_router.is_none() {
+                return Err(ContractError::InvalidInput(
+                    "no DEX router is configured on this chain".to_string(),
+                ));
+            }
+            if offer.is_zero() || !amount.is_zero() {
+                return Err(ContractError::InvalidInput(
+                    "a swap offers a positive amount and grants nothing".to_string(),
+                ));
+            }
+            if ask_denom.is_empty() || *ask_denom == config.denom {
+                return Err(ContractError::InvalidInput(
+                    "a swap has to ask for another denom".to_string(),
+                ));
+            }
+            if min_receive.is_zero() {
+                return Err(ContractError::InvalidInput(
+                    "a swap needs a minimum to receive".to_string(),
+                ));
+            }
+        }
+        ProposalType::GoLive {} => {
+            if CONFIG.load(deps.storage)?.shadow_funds.is_none() {
+                return Err(ContractError::InvalidInput(
+                    "the DAO is already live".to_string(),
+                ));
+            }
+        }
     }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_proposal(
+    deps: DepsMut,
+    env: &Env,
+    title: Title,
+    description: Description,
+    recipient: Addr,
+    amount: Uint128,
+    proposal_type: ProposalType,
+    frontend_id: Option<FrontendId>,
+    parent_proposal_id: Option<u64>,
+    proposer: Option<Addr>,
+    deposit: Option<Deposit>,
+) -> StdResult<Proposal> {
+    let id = NEXT_PROPOSAL_ID.may_load(deps.storage)?.unwrap_or_default();
+    NEXT_PROPOSAL_ID.save(deps.storage, &(id + 1))?;
 
     let proposal = Proposal {
-        id: 0,
+        id,
         title,
         description,
-        votes_for: Uint128::zero(),
-        votes_against: Uint128::zero(),
-        voters: HashSet::new(),
         executed: false,
-        amount: amount.unwrap_or_default(),
-        recipient: recipient.unwrap_or(info.sender),
+        execution: None,
+        amount,
+        recipient,
+        proposal_type,
+        frontend_id,
+        parent_proposal_id,
+        status: ProposalStatus::Open,
+        deposit,
+        proposer,
+        submitted_at: None,
+        bounty: None,
+        created_at: Some(env.block.time),
+        created_height: Some(env.block.height),
     };
 
-    PROPOSALS.save(deps.storage, proposal.id, &proposal)?;
+    save_proposal(deps.storage, &proposal)?;
+    open_voting(deps, env, &proposal)?;
+    Ok(proposal)
+}
 
-    Ok(Response::default())
+// the tally is what takes votes, the proposal is open once it has one
+fn open_voting(deps: DepsMut, env: &Env, proposal: &Proposal) -> StdResult<()> {
+    let config = CONFIG.load(deps.storage)?;
+    let total_weight = config.membership.backend().total_weight(deps.as_ref())?;
+    let storage = deps.storage;
+    let voting_end = match config.voting_period {
+        Some(period) => Some(clock(storage, env)?.after(period)),
+        None => None,
+    };
+    let threshold = match proposal.proposal_type {
+        ProposalType::Expense { .. } => config.expense_threshold.or(config.threshold),
+        _ => config.threshold,
+    };
+    let category = proposal.proposal_type.category();
+    let groups = config
+        .group_quorums
+        .iter()
+        .filter(|rule| rule.categories.is_empty() || rule.categories.contains(&category))
+        .map(|rule| {
+            let weight = GROUPS
+                .may_load(storage, rule.group.as_str())?
+                .map_or(Uint128::zero(), |group| group.weight);
+            Ok(GroupTally {
+                group: rule.group.clone(),
+                required: weight.mul_ceil(rule.quorum),
+                votes_for: Uint128::zero(),
+                votes_against: Uint128::zero(),
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    let quorum_decay = config
+        .quorum_decay
+        .iter()
+        .find(|rule| rule.categories.is_empty() || rule.categories.contains(&category))
+        .map(|rule| QuorumDecay {
+            categories: Vec::new(),
+            ..rule.clone()
+        });
+    let tally = Tally {
+        voting_end,
+        threshold: Some(threshold.unwrap_or(Decimal::percent(50))),
+        quorum: Some(config.quorum.unwrap_or_default()),
+        voting_period: config.voting_period,
+        groups,
+        quorum_decay,
+        commitment: commitment(storage, &config.denom, proposal)?,
+        strategy: config.voting_strategy.clone(),
+        snapshot_height: Some(env.block.height),
+        membership: config.membership.clone(),
+        total_weight: Some(total_weight),
+        ..Tally::default()
+    };
+    TALLIES.save(storage, proposal.id, &tally)?;
+    record_analytics(storage, env, |stats| stats.created += 1)
 }
 
-fn execute_vote(
-    deps: DepsMut,
+#[allow(clippy::too_many_arguments)]
+fn execute_mirror_proposal(
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    proposal_id: u64,
-    approve: bool,
+    parent_proposal_id: u64,
+    title: Title,
+    description: Description,
+    recipient: Addr,
+    amount: Uint128,
 ) -> Result<Response, ContractError> {
-    let member = MEMBERS
-        .load(deps.storage, info.sender.clone())
-        .map_err(|_| ContractError::Unauthorized {})?;
-
-    let mut proposal = PROPOSALS
-        .load(deps.storage, proposal_id)
-        .map_err(|_| ContractError::ProposalDoesNotExist {})?;
-
-    if proposal.voters.contains(&info.sender) {
-        return Err(ContractError::MemberAlreadyVoted {});
+    if CONFIG.load(deps.storage)?.parent_dao.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
     }
-
-    if approve {
-        proposal.votes_for += member.weight;
-    } else {
-        proposal.votes_against += member.weight;
+    if let Some(id) = MIRRORED_PROPOSALS.may_load(deps.storage, parent_proposal_id)? {
+        return Err(ContractError::InvalidInput(format!(
+            "parent proposal {} is already mirrored as {}",
+            parent_proposal_id, id
+        )));
     }
 
-    proposal.voters.insert(info.sender);
+    let recipient = deps.api.addr_validate(recipient.as_str())?;
+    let proposal = create_proposal(
+        deps.branch(),
+        &env,
+        title,
+        description,
+        recipient,
+        amount,
+        ProposalType::Grant,
+        None,
+        Some(parent_proposal_id),
+        None,
+        None,
+    )?;
+    MIRRORED_PROPOSALS.save(deps.storage, parent_proposal_id, &proposal.id)?;
 
-    PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
+    Ok(Response::new()
+        .add_attribute("method", "execute_mirror_proposal")
+        .add_attribute("proposal_id", proposal.id.to_string())
+        .add_attribute("parent_proposal_id", parent_proposal_id.to_string())
+        .add_submessages(notify(deps.as_ref(), proposal.id, ProposalEvent::Opened)?))
+}
 
-    Ok(Response::default())
+fn ensure_not_suspended(
+    storage: &dyn Storage,
+    env: &Env,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    match SUSPENSIONS.may_load(storage, address)? {
+        Some(suspension) if !suspension.expires_at.is_triggered(&env.block) => {
+            Err(ContractError::MemberSuspended {})
+        }
+        _ => Ok(()),
+    }
 }
 
-fn execute_execute(deps: DepsMut, env: Env, proposal_id: u64) -> Result<Response, ContractError> {
-    let mut proposal = PROPOSALS.load(deps.storage, proposal_id)?;
+/// Checks a proposer must pass, shared with the `CanPropose` query
+fn ensure_can_propose(
+    storage: &dyn Storage,
+    env: &Env,
+    proposer: &Addr,
+) -> Result<(), ContractError> {
+    if !MEMBERS.has(storage, proposer) {
+        return Err(ContractError::Unauthorized {});
+    }
+    ensure_not_suspended(storage, env, proposer)
+}
 
-    if proposal.executed {
-        return Err(ContractError::AlreadyExecuted {});
+/// Guards every path that would change a proposal's record once it has been decided
+fn ensure_unlocked(tally: &Tally) -> Result<(), ContractError> {
+    if tally.locked {
+        return Err(ContractError::ProposalLocked {});
     }
+    Ok(())
+}
 
-    let mut response = Response::new();
+/// Every check a vote must pass, shared with the `CanVote` query so the two never disagree
+fn ensure_can_vote(
+    deps: Deps,
+    env: &Env,
+    proposal_id: u64,
+    voter: &Addr,
+) -> Result<(Member, Tally), ContractError> {
+    let storage = deps.storage;
+    // the tally doubles as the existence check, the proposal itself is never loaded
+    let tally = TALLIES
+        .may_load(storage, proposal_id)?
+        .ok_or(ContractError::ProposalDoesNotExist {})?;
+    let member = Member {
+        address: voter.clone(),
+        weight: voting_weight(deps, &tally, voter)?.ok_or(ContractError::Unauthorized {})?,
+    };
 
-    if proposal.votes_for > proposal.votes_against {
-        if deps
-            .querier
-            .query_balance(env.contract.address, DENOM)?
-            .amount
-            < proposal.amount
+    ensure_unlocked(&tally)?;
+    if tally
+        .voting_end
+        .is_some_and(|end| end.is_triggered(&env.block))
+    {
+        // only past the deadline is it worth loading what a tie extension depends on
+        let config = CONFIG.load(storage)?;
+        let total_weight = electorate(storage, &tally)?;
+        if effective_voting_end(&config, &tally, total_weight)
+            .is_some_and(|end| end.is_triggered(&env.block))
         {
-            return Err(ContractError::InsufficientFunds {});
+            return Err(ContractError::VotingClosed {});
         }
+    }
+    ensure_not_suspended(storage, env, voter)?;
 
-        proposal.executed = true;
-        PROPOSALS.save(deps.storage, proposal_id, &proposal)?;
-
-        if !proposal.amount.is_zero() {
-            let transfer = BankMsg::Send {
-                to_address: proposal.recipient.to_string(),
-                amount: vec![Coin {
-                    denom: DENOM.to_string(),
-                    amount: proposal.amount,
-                }],
-            };
+    Ok((member, tally))
+}
 
-            response = response.add_message(transfer);
-        }
+fn payouts_total(payouts: &[GrantPayout]) -> StdResult<Uint128> {
+    payouts.iter().try_fold(Uint128::zero(), |total, payout| {
+        Ok(total.checked_add(payout.amount)?)
+    })
+}
 
-        return Ok(response
-            .add_attribute("method", "execute_execute")
-            .add_attribute("recipient", proposal.recipient)
-            .add_attribute("amount", proposal.amount));
+/// `address`'s say in a proposal, `None` for non-members
+fn voting_weight(deps: Deps, tally: &Tally, address: &Addr) -> StdResult<Option<Uint128>> {
+    match tally.snapshot_height {
+        // members who joined after the proposal opened have no say in it
+        Some(height) => tally.membership.backend().weight_at(deps, address, height),
+        None => Ok(MEMBERS
+            .may_load(deps.storage, address)?
+            .map(|member| member.weight)),
     }
+}
 
-    Ok(response)
+fn delegators(storage: &dyn Storage, delegate: &Addr) -> StdResult<Vec<Addr>> {
+    DELEGATORS
+        .prefix(delegate)
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetProposal { proposal_id } => {
-            to_json_binary(&query_get_proposal(deps, proposal_id)?)
+/// Member weight behind a tally's ballots, whatever the strategy counted them for
+fn cast_weight(tally: &Tally) -> Uint128 {
+    tally
+        .weight_cast
+        .unwrap_or(tally.votes_for + tally.votes_against)
+}
+
+/// Total weight a proposal's quorum and thresholds are measured against
+fn electorate(storage: &dyn Storage, tally: &Tally) -> StdResult<Uint128> {
+    match tally.total_weight {
+        Some(total_weight) => Ok(total_weight),
+        None => TOTAL_WEIGHT.load(storage),
+    }
+}
+
+// the ballot is `voter`'s either way, `operator` only records who sent it
+fn execute_vote(
+    deps: DepsMut,
+    env: Env,
+    voter: Addr,
+    operator: Option<Addr>,
+    proposal_id: u64,
+    approve: bool,
+) -> Result<Response, ContractError> {
+    let (member, mut tally) = ensure_can_vote(deps.as_ref(), &env, proposal_id, &voter)?;
+
+    // a delegator's weight is theirs to cast until their delegate's ballot has carried it
+    if CARRIED_VOTES.has(deps.storage, (proposal_id, &voter)) {
+        return Err(ContractError::MemberAlreadyVoted {});
+    }
+    let mut weight = member.weight;
+    let mut carried = vec![];
+    let mut lent_weights = vec![];
+    for delegator in delegators(deps.storage, &voter)? {
+        if BALLOTS.has(deps.storage, (proposal_id, &delegator)) {
+            continue;
         }
-        QueryMsg::ListProposals { start_after, limit } => {
-            to_json_binary(&query_list_proposals(deps, start_after, limit))
+        // carried by an earlier delegate, only the voter's own previous ballot gives it back
+        if CARRIED_VOTES
+            .may_load(deps.storage, (proposal_id, &delegator))?
+            .is_some_and(|carrier| carrier != voter)
+        {
+            continue;
         }
-        QueryMsg::GetMember { address } => to_json_binary(&query_get_member(deps, address)?),
-        QueryMsg::ListMembers { start_after, limit } => {
-            to_json_binary(&query_list_members(deps, start_after, limit))
+        if let Some(lent) = voting_weight(deps.as_ref(), &tally, &delegator)? {
+            weight += lent;
+            carried.push(delegator);
+            lent_weights.push(lent);
         }
     }
-}
 
-fn query_get_proposal(deps: Deps, proposal_id: u64) -> StdResult<Proposal> {
-    let proposal = PROPOSALS.load(deps.storage, proposal_id)?;
-    Ok(proposal)
-}
+    let config = CONFIG.load(deps.storage)?;
+    // whether this vote makes the proposal pass decides its funds and the notification
+    let total_weight = electorate(deps.storage, &tally)?;
+    let was_passing = passes(&config, &tally, total_weight);
 
-fn query_get_member(deps: Deps, address: Addr) -> StdResult<Member> {
-    let member = MEMBERS.load(deps.storage, address)?;
-    Ok(member)
-}
+    // the voter's group is only looked up for proposals a group quorum applies to
+    let group = if tally.groups.is_empty() {
+        None
+    } else {
+        MEMBER_GROUPS.may_load(deps.storage, &voter)?
+    };
+    let group = group.and_then(|group| tally.groups.iter().position(|g| g.group == group));
 
-fn query_list_proposals(deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> Vec<Proposal> {
-    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
-    let start = start_after.map(Bound::exclusive);
+    let window = VotingWindow {
+        remaining: tally
+            .voting_end
+            .map(|end| remaining(&env.block, end))
+            .unwrap_or_default(),
+        period: voting_period_of(&config, &tally).unwrap_or_default(),
+    };
+    let counted = tally.strategy.tally().counted(weight, &window);
+    let mut weight_cast = cast_weight(&tally);
 
-    let proposals = PROPOSALS
-        .range(deps.storage, start, None, Order::Ascending)
-        .take(limit as usize)
-        .filter_map(Result::ok)
-        .map(|(_, proposal)| proposal)
-        .collect();
+    // a ballot may be changed until voting ends, the previous one is taken back out first
+    if let Some(previous) = BALLOTS.may_load(deps.storage, (proposal_id, &voter))? {
+        if previous.approve == approve {
+            return Err(ContractError::MemberAlreadyVoted {});
+        }
+        if previous.approve {
+            tally.votes_for -= previous.counted_weight();
+        } else {
+            tally.votes_against -= previous.counted_weight();
+        }
+        weight_cast -= previous.weight;
+        for delegator in &previous.delegators {
+            let key = (proposal_id, delegator);
+            if CARRIED_VOTES.may_load(deps.storage, key)?.as_ref() == Some(&voter) {
+                CARRIED_VOTES.remove(deps.storage, key);
+            }
+        }
+        if let Some(index) = group {
+            // saturating, the voter may have joined the group after casting it
+            let group = &mut tally.groups[index];
+            if previous.approve {
+                group.votes_for = group.votes_for.saturating_sub(previous.weight);
+            } else {
+                group.votes_against = group.votes_against.saturating_sub(previous.weight);
+            }
+        }
+    }
+    if approve {
+        tally.votes_for += counted;
+    } else {
+        tally.votes_against += counted;
+    }
+    // quorum goes by member weight, tracked apart once any ballot counts for less; group
+    // tallies below are quorums too and always take member weight
+    if counted != weight || tally.weight_cast.is_some() {
+        tally.weight_cast = Some(weight_cast + weight);
+    }
+    if let Some(index) = group {
+        let group = &mut tally.groups[index];
+        if approve {
+            group.votes_for += weight;
+        } else {
+            group.votes_against += weight;
+        }
+    }
+    let now_passing = passes(&config, &tally, total_weight);
+    let mut response = Response::default();
+    // funds are reserved from the vote that makes a proposal pass, as long as there are any
+    // left, so two passing proposals cannot count on the same balance
+    if !now_passing {
+        release_commitment(deps.storage, &mut tally)?;
+    } else if !was_passing
+        && !tally.commitment.is_zero()
+        && available_funds(deps.as_ref(), &env, &config.denom)? >= tally.commitment
+    {
+        let committed = COMMITTED.may_load(deps.storage)?.unwrap_or_default();
+        COMMITTED.save(deps.storage, &(committed + tally.commitment))?;
+        tally.committed = true;
+        response = response.add_attribute("funds_committed", tally.commitment);
+    }
+    TALLIES.save(deps.storage, proposal_id, &tally)?;
+    for delegator in &carried {
+        CARRIED_VOTES.save(deps.storage, (proposal_id, delegator), &voter)?;
+    }
 
-    proposals
-}
+    let salt = config.receipt_salt.as_deref().unwrap_or_default();
+    let receipt = ballot_receipt(salt, &voter, proposal_id, approve, env.block.height);
 
-fn query_list_members(deps: Deps, start_after: Option<Addr>, limit: Option<u32>) -> Vec<Member> {
-    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
-    let start = start_after.map(Bound::exclusive);
+    let ballot = Ballot {
+        voter,
+        approve,
+        weight,
+        counted: (counted != weight).then_some(counted),
+        height: env.block.height,
+        time: env.block.time,
+        receipt,
+        operator,
+        delegators: carried,
+        lent: lent_weights,
+    };
+    tracked_save(
+        deps.storage,
+        &BALLOTS,
+        USAGE_BALLOTS,
+        (proposal_id, &ballot.voter),
+        &ballot,
+    )?;
 
-    let members = MEMBERS
-        .range(deps.storage, start, None, Order::Ascending)
-        .take(limit as usize)
-        .filter_map(Result::ok)
-        .map(|(_, member)| member)
-        .collect();
+    response = response.add_attribute("receipt", ballot.receipt.to_hex());
+    if config.notifier.is_some() && !was_passing && now_passing {
+        response =
+            response.add_submessages(notify(deps.as_ref(), proposal_id, ProposalEvent::Passed)?);
+    }
 
-    members
+    Ok(response)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::state::Member;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coin, coins, Addr, Empty, Uint128};
-    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
-
-    fn dao_contract() -> Box<dyn Contract<Empty>> {
-        let contract = ContractWrapper::new(execute, instantiate, query);
-        Box::new(contract)
+/// Receipt a voter can hand to a third party to prove how they voted. Fields are hashed
+/// length-prefixed so that no two distinct inputs share an encoding.
+pub fn ballot_receipt(
+    salt: &str,
+    voter: &Addr,
+    proposal_id: u64,
+    approve: bool,
+    height: u64,
+) -> HexBinary {
+    let mut hasher = Sha256::new();
+    for field in [salt.as_bytes(), voter.as_bytes()] {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field);
     }
+    hasher.update(proposal_id.to_be_bytes());
+    hasher.update([approve as u8]);
+    hasher.update(height.to_be_bytes());
 
-    #[test]
-    fn proper_instantiation() {
-        let mut deps = mock_dependencies();
+    HexBinary::from(hasher.finalize().to_vec())
+}
 
-        let members = vec![
-            Member {
-                address: Addr::unchecked("addr1"),
-                weight: Uint128::from(10_u128),
-            },
-            Member {
-                address: Addr::unchecked("addr2"),
-                weight: Uint128::from(20_u128),
-            },
-        ];
-        let msg = InstantiateMsg { members };
-        let info = mock_info("creator", &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+fn execute_execute_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    mut proposal_ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    proposal_ids.sort_unstable();
+    proposal_ids.dedup();
+    if proposal_ids.is_empty() || proposal_ids.len() > MAX_EXECUTE_BATCH {
+        return Err(ContractError::InvalidInput(format!(
+            "expected 1 to {} proposals",
+            MAX_EXECUTE_BATCH
+        )));
     }
 
-    #[test]
-    fn proposal_creation() {
-        let mut deps = mock_dependencies();
+    // the balance only drops once the whole batch has run, until then what it has sent counts
+    // as committed so each proposal is checked against what the ones before it left
+    let denom = CONFIG.load(deps.storage)?.denom;
+    let mut spent = Uint128::zero();
+    let mut response = Response::new().add_attribute("method", "execute_execute_batch");
+    for proposal_id in proposal_ids {
+        let executed = execute_execute(deps.branch(), env.clone(), info.clone(), proposal_id)?;
+        let msgs: Vec<CosmosMsg> = executed
+            .messages
+            .iter()
+            .map(|sub| sub.msg.clone())
+            .collect();
+        let sent = outgoing(&msgs, &denom);
+        let committed = COMMITTED.may_load(deps.storage)?.unwrap_or_default();
+        COMMITTED.save(deps.storage, &(committed + sent))?;
+        spent += sent;
+        response = response
+            .add_submessages(executed.messages)
+            .add_events(executed.events)
+            .add_event(Event::new("batch_execution").add_attributes(executed.attributes));
+    }
+    COMMITTED.update(deps.storage, |committed| -> StdResult<_> {
+        Ok(committed.saturating_sub(spent))
+    })?;
 
-        let members = vec![Member {
-            address: Addr::unchecked("addr1"),
-            weight: Uint128::from(10_u128),
-        }];
-        let msg = InstantiateMsg { members };
-        let info = mock_info("creator", &[]);
-        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    Ok(response)
+}
 
-        // Propose
-        let info = mock_info("addr1", &[]);
-        let msg = ExecuteMsg::Propose {
-            title: "Test Proposal".to_string(),
-            description: "Description for test".to_string(),
-            amount: Some(Uint128::from(100_u128)),
-            recipient: Some(Addr::unchecked("recipient_address")),
-        };
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+fn execute_execute(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    if let Some(archived) = ARCHIVE.may_load(deps.storage, proposal_id)? {
+        return Err(ContractError::AlreadyExecuted {
+            height: archived.execution.height,
+            tx_index: archived.execution.tx_index,
+        });
     }
 
-    #[test]
-    fn vote_for_proposal() {
-        let mut deps = mock_dependencies();
+    let mut proposal = PROPOSALS.load(deps.storage, proposal_id)?;
+
+    if let Some(execution) = &proposal.execution {
+        return Err(ContractError::AlreadyExecuted {
+            height: execution.height,
+            tx_index: execution.tx_index,
+        });
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let denom = config.denom.clone();
+    let shadow = config.shadow_funds.is_some();
+    let mut tally = TALLIES.load(deps.storage, proposal_id)?;
+    let total_weight = electorate(deps.storage, &tally)?;
+    ensure_unlocked(&tally)?;
+    if let Some(end) = effective_voting_end(&config, &tally, total_weight) {
+        if !end.is_triggered(&env.block) && !decided_early(&config, &tally, total_weight) {
+            return Err(ContractError::VotingOpen {
+                ends_at: end.to_string(),
+            });
+        }
+        // the delay runs from the deadline even when the vote was decided early
+        if let Some(delay) = config.execution_delay {
+            let executable_at = delayed(end, delay);
+            if !executable_at.is_triggered(&env.block) {
+                return Err(ContractError::Timelocked {
+                    executable_at: executable_at.to_string(),
+                });
+            }
+        }
+    }
+    let mut response = Response::new();
+
+    let status = proposal_status(&config, &proposal, &tally, total_weight, &env.block);
+    if status == ProposalStatus::Passed {
+        // the proposal's own reservation is what it spends
+        release_commitment(deps.storage, &mut tally)?;
+        if tied(&config, &tally, total_weight) {
+            response = response.add_attribute("tie_break", config.tie_break.as_str());
+        }
+        // recipients are not barred from voting on what pays them, only flagged for monitoring
+        if !proposal.amount.is_zero()
+            && BALLOTS.has(deps.storage, (proposal_id, &proposal.recipient))
+        {
+            response = response.add_attribute("recipient_voted", proposal.recipient.as_str());
+        }
+        match &proposal.proposal_type {
+            ProposalType::Grant => {
+                let referral = referral(deps.storage, &proposal)?;
+                if available_funds(deps.as_ref(), &env, &denom)?
+                    < proposal.amount + referral_amount(&referral)
+                {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+
+                if !proposal.amount.is_zero() {
+                    response = payout(
+                        deps.storage,
+                        shadow,
+                        &proposal.recipient,
+                        proposal.amount,
+                        &denom,
+                        response,
+                    )?;
+                }
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("recipient", proposal.recipient.clone())
+                    .add_attribute("amount", proposal.amount);
+                response = pay_referral(deps.storage, shadow, referral, &denom, response)?;
+            }
+            ProposalType::GrantBatch { payouts } => {
+                // all or nothing, every payout is covered before any is sent
+                if available_funds(deps.as_ref(), &env, &denom)? < proposal.amount {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+
+                for grant in payouts {
+                    response = payout(
+                        deps.storage,
+                        shadow,
+                        &grant.recipient,
+                        grant.amount,
+                        &denom,
+                        response,
+                    )?;
+                }
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("payouts", payouts.len().to_string())
+                    .add_attribute("amount", proposal.amount);
+            }
+            ProposalType::Expense { invoice_hash } => {
+                if available_funds(deps.as_ref(), &env, &denom)? < proposal.amount {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+
+                if !proposal.amount.is_zero() {
+                    response = payout(
+                        deps.storage,
+                        shadow,
+                        &proposal.recipient,
+                        proposal.amount,
+                        &denom,
+                        response,
+                    )?;
+                }
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("vendor", proposal.recipient.clone())
+                    .add_attribute("amount", proposal.amount)
+                    .add_attribute("invoice_hash", invoice_hash.as_str());
+            }
+            ProposalType::VestedGrant { upfront, duration } => {
+                // the whole grant has to be covered now, not just the upfront part
+                let referral = referral(deps.storage, &proposal)?;
+                if available_funds(deps.as_ref(), &env, &denom)?
+                    < proposal.amount + referral_amount(&referral)
+                {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+
+                if !upfront.is_zero() {
+                    response = payout(
+                        deps.storage,
+                        shadow,
+                        &proposal.recipient,
+                        *upfront,
+                        &denom,
+                        response,
+                    )?;
+                }
+
+                let clock = clock(deps.storage, &env)?;
+                let vesting = Vesting {
+                    proposal_id,
+                    recipient: proposal.recipient.clone(),
+                    upfront: *upfront,
+                    vesting_amount: proposal.amount - *upfront,
+                    start: clock.now(),
+                    end: clock.after(*duration),
+                    claimed: Uint128::zero(),
+                    shadow,
+                    reserved: true,
+                };
+                VESTINGS.save(deps.storage, proposal_id, &vesting)?;
+                reserve_owed(deps.storage, vesting.vesting_amount)?;
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("recipient", proposal.recipient.clone())
+                    .add_attribute("amount", proposal.amount)
+                    .add_attribute("upfront", *upfront);
+                response = pay_referral(deps.storage, shadow, referral, &denom, response)?;
+            }
+            ProposalType::Announce { title, body } => {
+                let announcement = Announcement {
+                    proposal_id,
+                    title: title.clone(),
+                    body: body.clone(),
+                    published_at: env.block.time,
+                };
+                ANNOUNCEMENTS.save(deps.storage, proposal_id, &announcement)?;
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("announcement", proposal_id.to_string());
+            }
+            ProposalType::Signal {} => {
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_event(
+                        Event::new("signal")
+                            .add_attribute("proposal_id", proposal_id.to_string())
+                            .add_attribute("votes_for", tally.votes_for)
+                            .add_attribute("votes_against", tally.votes_against),
+                    );
+            }
+            ProposalType::UpdateMembersBatch {
+                add,
+                remove,
+                update,
+            } => {
+                let claim_until = claim_window(deps.storage, &env)?;
+                let total_weight = apply_member_changes(
+                    deps.storage,
+                    env.block.height,
+                    claim_until,
+                    add,
+                    remove,
+                    update,
+                )?;
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("added", add.len().to_string())
+                    .add_attribute("removed", remove.len().to_string())
+                    .add_attribute("updated", update.len().to_string())
+                    .add_attribute("total_weight", total_weight);
+            }
+            ProposalType::ConfirmSuspension { member } => {
+                // the member may have left some other way while the vote was open
+                if MEMBERS.has(deps.storage, member) {
+                    let claim_until = claim_window(deps.storage, &env)?;
+                    apply_member_changes(
+                        deps.storage,
+                        env.block.height,
+                        claim_until,
+                        &[],
+                        std::slice::from_ref(member),
+                        &[],
+                    )?;
+                }
+                SUSPENSIONS.remove(deps.storage, member);
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("removed", member.clone());
+            }
+            ProposalType::DistributeRewards {
+                pool,
+                start,
+                end,
+                min_participation,
+            } => {
+                if available_funds(deps.as_ref(), &env, &denom)? < *pool {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+
+                let distribution = schedule_rewards(
+                    deps.branch(),
+                    proposal_id,
+                    *pool,
+                    *start,
+                    *end,
+                    *min_participation,
+                )?;
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("eligible", distribution.eligible.to_string());
+            }
+            ProposalType::UpdateConfig {
+                config,
+                effective_at,
+            } => {
+                response = response.add_attribute("method", "execute_execute");
+
+                match effective_at {
+                    Some(effective_at) if !effective_at.is_triggered(&env.block) => {
+                        let pending = PendingConfig {
+                            changes: ConfigUpdate::between(&CONFIG.load(deps.storage)?, config),
+                            effective_at: *effective_at,
+                        };
+                        PENDING_CONFIG.save(deps.storage, &pending)?;
+
+                        response =
+                            response.add_attribute("config_effective_at", effective_at.to_string());
+                    }
+                    _ => {
+                        // an immediate change supersedes anything still scheduled
+                        CONFIG.save(deps.storage, config)?;
+                        PENDING_CONFIG.remove(deps.storage);
+
+                        response = response.add_attribute("config", "updated");
+                    }
+                }
+            }
+            ProposalType::ParentVote {
+                proposal_id: parent_proposal_id,
+                approve,
+            } => {
+                let parent_dao = parent_dao(deps.storage)?;
+                let vote = ExecuteMsg::Vote {
+                    proposal_id: *parent_proposal_id,
+                    approve: *approve,
+                };
+                response = response
+                    .add_message(WasmMsg::Execute {
+                        contract_addr: parent_dao.to_string(),
+                        msg: to_json_binary(&vote)?,
+                        funds: vec![],
+                    })
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("parent_dao", parent_dao)
+                    .add_attribute("parent_proposal_id", parent_proposal_id.to_string());
+            }
+            ProposalType::DiscloseBallots {
+                proposal_id: disclosed,
+            } => {
+                DISCLOSED_BALLOTS.save(deps.storage, *disclosed, &Empty {})?;
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("disclosed_ballots", disclosed.to_string());
+            }
+            ProposalType::UpdateFrontends { register, remove } => {
+                for id in remove {
+                    FRONTENDS.remove(deps.storage, id.as_str());
+                }
+                for frontend in register {
+                    FRONTENDS.save(deps.storage, frontend.id.as_str(), frontend)?;
+                }
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("frontends_registered", register.len().to_string())
+                    .add_attribute("frontends_removed", remove.len().to_string());
+            }
+            ProposalType::UpdateGroups { assign, unassign } => {
+                let assigned = assign.iter().map(|m| (&m.address, Some(m.group.clone())));
+                for (address, group) in assigned.chain(unassign.iter().map(|a| (a, None))) {
+                    let member = MEMBERS
+                        .may_load(deps.storage, address)?
+                        .ok_or_else(|| invalid_member_entry(address, "not a member"))?;
+                    set_group(deps.storage, &member, group)?;
+                }
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("assigned", assign.len().to_string())
+                    .add_attribute("unassigned", unassign.len().to_string());
+            }
+            ProposalType::Custom { msgs } => {
+                if available_funds(deps.as_ref(), &env, &denom)? < outgoing(msgs, &denom) {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+
+                // nothing can be simulated about arbitrary messages, shadow mode only records them
+                if shadow {
+                    response = response.add_attribute("shadow_messages", msgs.len().to_string());
+                } else {
+                    debit_cw20(deps.storage, msgs)?;
+                    response = response.add_messages(msgs.clone());
+                }
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("messages", msgs.len().to_string());
+            }
+            ProposalType::Swap {
+                offer,
+                ask_denom,
+                min_receive,
+            } => {
+                if available_funds(deps.as_ref(), &env, &denom)? < *offer {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+                // the router may have been unset since the proposal passed
+                let router = config.dex_router.as_ref().ok_or_else(|| {
+                    ContractError::InvalidInput("no DEX router is configured".to_string())
+                })?;
+
+                if shadow {
+                    response = response.add_attribute("shadow_messages", "1");
+                } else {
+                    response = response.add_message(swap_msg(
+                        router,
+                        *offer,
+                        &denom,
+                        ask_denom,
+                        *min_receive,
+                    )?);
+                }
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("offer", *offer)
+                    .add_attribute("ask_denom", ask_denom)
+                    .add_attribute("min_receive", *min_receive);
+            }
+            ProposalType::GoLive {} => {
+                let mut live = CONFIG.load(deps.storage)?;
+                live.shadow_funds = None;
+                CONFIG.save(deps.storage, &live)?;
+
+                response = response
+                    .add_attribute("method", "execute_execute")
+                    .add_attribute("mode", "live");
+            }
+        }
+
+        let execution = Execution {
+            height: env.block.height,
+            time: env.block.time,
+            tx_index: env.transaction.as_ref().map(|tx| tx.index),
+        };
+        response = response.add_attribute("execution_height", execution.height.to_string());
+        if let Some(frontend_id) = &proposal.frontend_id {
+            response = response.add_attribute("frontend_id", frontend_id.as_str());
+        }
+
+        if let Some((recipient, bounty)) = settle_bounty(
+            deps.storage,
+            &config,
+            &tally,
+            &mut proposal,
+            &info.sender,
+            &env,
+        )? {
+            response = response
+                .add_message(grant_transfer(&recipient, bounty, &denom))
+                .add_attribute("bounty_paid_to", recipient);
+        }
+
+        proposal.executed = true;
+        EXECUTED_AT.save(
+            deps.storage,
+            (execution.time.nanos(), proposal_id),
+            &Empty {},
+        )?;
+        proposal.execution = Some(execution);
+        proposal.status = ProposalStatus::Executed;
+        tally.locked = true;
+        TALLIES.save(deps.storage, proposal_id, &tally)?;
+        save_proposal(deps.storage, &proposal)?;
+
+        record_outbox(deps.storage, &env, proposal_id, &response)?;
+        let turnout = participation(&tally, total_weight);
+        let execution_time = proposal
+            .created_at
+            .map(|created_at| env.block.time.seconds() - created_at.seconds());
+        let granted = GRANTED.may_load(deps.storage)?.unwrap_or_default();
+        GRANTED.save(deps.storage, &(granted + proposal.amount))?;
+        record_analytics(deps.storage, &env, |stats| {
+            stats.passed += 1;
+            stats.granted += proposal.amount;
+            stats.participation += turnout;
+            stats.execution_times.extend(execution_time);
+        })?;
+        response =
+            response.add_submessages(notify(deps.as_ref(), proposal_id, ProposalEvent::Executed)?);
+    }
+
+    Ok(response)
+}
+
+fn execute_import_proposals(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposals: Vec<ImportedProposal>,
+) -> Result<Response, ContractError> {
+    ensure_admin_or_self(deps.storage, &env, &info.sender)?;
+    if proposals.len() > MAX_IMPORT_BATCH {
+        return Err(ContractError::InvalidInput(format!(
+            "at most {} proposals can be imported at once",
+            MAX_IMPORT_BATCH
+        )));
+    }
+
+    let first_id = NEXT_PROPOSAL_ID.may_load(deps.storage)?.unwrap_or_default();
+    for (id, imported) in (first_id..).zip(&proposals) {
+        let proposal_type = imported.proposal_type.clone().unwrap_or_default();
+        deps.api.addr_validate(imported.recipient.as_str())?;
+        validate_proposal_type(
+            deps.as_ref(),
+            &env,
+            &proposal_type,
+            imported.amount,
+            Some(&imported.recipient),
+        )?;
+
+        // drafts get no tally, so nothing can be voted or executed until they are opened
+        let proposal = Proposal {
+            id,
+            title: imported.title.clone(),
+            description: imported.description.clone(),
+            recipient: imported.recipient.clone(),
+            amount: imported.amount,
+            executed: false,
+            proposal_type,
+            execution: None,
+            frontend_id: None,
+            parent_proposal_id: None,
+            status: ProposalStatus::Draft,
+            deposit: None,
+            proposer: None,
+            submitted_at: Some(imported.submitted_at),
+            bounty: None,
+            created_at: Some(env.block.time),
+            created_height: Some(env.block.height),
+        };
+        save_proposal(deps.storage, &proposal)?;
+    }
+    let next_id = first_id + proposals.len() as u64;
+    NEXT_PROPOSAL_ID.save(deps.storage, &next_id)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_import_proposals")
+        .add_attribute("imported", proposals.len().to_string())
+        .add_attribute("first_proposal_id", first_id.to_string()))
+}
+
+fn execute_open_draft(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    ensure_admin_or_self(deps.storage, &env, &info.sender)?;
+    let mut proposal = PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalDoesNotExist {})?;
+    if proposal.status != ProposalStatus::Draft {
+        return Err(ContractError::InvalidInput(
+            "only drafts can be opened".to_string(),
+        ));
+    }
+
+    // voting runs from now, the original submission time is only kept as a record
+    proposal.status = ProposalStatus::Open;
+    save_proposal(deps.storage, &proposal)?;
+    open_voting(deps.branch(), &env, &proposal)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_open_draft")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_submessages(notify(deps.as_ref(), proposal_id, ProposalEvent::Opened)?))
+}
+
+// the diffs are checked against the member list rather than trusted for the old weight, so a
+// list that drifted from the group is brought back in line. A group member down to weight 0 has
+// no say, the same as the cw4 backend treats them, so they leave the list
+fn execute_member_changed_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    diffs: Vec<MemberDiff>,
+) -> Result<Response, ContractError> {
+    match CONFIG.load(deps.storage)?.membership {
+        MembershipSource::Cw4 { group } if group == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    let mut add = vec![];
+    let mut remove = vec![];
+    let mut update = vec![];
+    for diff in diffs {
+        let address = deps.api.addr_validate(&diff.key)?;
+        let member = MEMBERS.has(deps.storage, &address);
+        match diff.new.filter(|weight| *weight > 0) {
+            Some(weight) => {
+                let entry = Member {
+                    address,
+                    weight: weight.into(),
+                };
+                if member {
+                    update.push(entry);
+                } else {
+                    add.push(entry);
+                }
+            }
+            None if member => remove.push(address),
+            None => {}
+        }
+    }
+    validate_member_changes(deps.api, &add, &remove, &update)?;
+    let claim_until = claim_window(deps.storage, &env)?;
+    let total_weight = apply_member_changes(
+        deps.storage,
+        env.block.height,
+        claim_until,
+        &add,
+        &remove,
+        &update,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_member_changed_hook")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string())
+        .add_attribute("updated", update.len().to_string())
+        .add_attribute("total_weight", total_weight))
+}
+
+fn security_committee_lane(
+    storage: &dyn Storage,
+    sender: &Addr,
+) -> Result<SecurityLane, ContractError> {
+    match CONFIG.load(storage)?.security_lane {
+        Some(lane) if lane.committee.contains(sender) => Ok(lane),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+fn execute_authorize_security_payout(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+    report_hash: ContentHash,
+) -> Result<Response, ContractError> {
+    let lane = security_committee_lane(deps.storage, &info.sender)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+    if amount > lane.payout_cap {
+        return Err(ContractError::AmountAboveCap {
+            amount,
+            cap: lane.payout_cap,
+        });
+    }
+    if amount.is_zero() {
+        return Err(ContractError::InvalidInput(
+            "a payout has to send something".to_string(),
+        ));
+    }
+
+    let id = NEXT_SECURITY_PAYOUT_ID
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    NEXT_SECURITY_PAYOUT_ID.save(deps.storage, &(id + 1))?;
+    let payout = SecurityPayout {
+        recipient,
+        amount,
+        report_hash,
+        approvals: vec![info.sender],
+        paid_at: None,
+        disclose_at: None,
+    };
+    let response = Response::new()
+        .add_attribute("method", "execute_authorize_security_payout")
+        .add_attribute("payout_id", id.to_string());
+    settle_security_payout(deps, &env, &lane, id, payout, response)
+}
+
+fn execute_approve_security_payout(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    payout_id: u64,
+) -> Result<Response, ContractError> {
+    let lane = security_committee_lane(deps.storage, &info.sender)?;
+    let mut payout = SECURITY_PAYOUTS
+        .may_load(deps.storage, payout_id)?
+        .ok_or_else(|| ContractError::InvalidInput("no such payout".to_string()))?;
+    if payout.paid_at.is_some() {
+        return Err(ContractError::InvalidInput(
+            "the payout was already sent".to_string(),
+        ));
+    }
+    if payout.approvals.contains(&info.sender) {
+        return Err(ContractError::InvalidInput(
+            "already approved by the sender".to_string(),
+        ));
+    }
+    payout.approvals.push(info.sender);
+    let response = Response::new()
+        .add_attribute("method", "execute_approve_security_payout")
+        .add_attribute("payout_id", payout_id.to_string());
+    settle_security_payout(deps, &env, &lane, payout_id, payout, response)
+}
+
+// sends the payout once it has enough approvals, only what was sent counts against the budget
+fn settle_security_payout(
+    deps: DepsMut,
+    env: &Env,
+    lane: &SecurityLane,
+    id: u64,
+    mut pending: SecurityPayout,
+    response: Response,
+) -> Result<Response, ContractError> {
+    let approvals = pending.approvals.len() as u32;
+    let response = response.add_attribute("approvals", approvals.to_string());
+    if approvals < lane.required_approvals {
+        SECURITY_PAYOUTS.save(deps.storage, id, &pending)?;
+        return Ok(response);
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let spent = SECURITY_SPENT.may_load(deps.storage)?.unwrap_or_default();
+    if spent + pending.amount > lane.budget
+        || available_funds(deps.as_ref(), env, &config.denom)? < pending.amount
+    {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    SECURITY_SPENT.save(deps.storage, &(spent + pending.amount))?;
+    pending.paid_at = Some(env.block.time);
+    pending.disclose_at = Some(clock(deps.storage, env)?.after(lane.disclosure_delay));
+    SECURITY_PAYOUTS.save(deps.storage, id, &pending)?;
+
+    // the transfer itself is public on chain, only the details stay out of queries
+    let response = payout(
+        deps.storage,
+        config.shadow_funds.is_some(),
+        &pending.recipient,
+        pending.amount,
+        &config.denom,
+        response,
+    )?;
+    Ok(response.add_attribute("paid", "true"))
+}
+
+// queries carry no sender, so this is the one way to show a private draft only to members
+fn execute_read_proposal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !MEMBERS.has(deps.storage, &info.sender) && config.admin.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let proposal = PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalDoesNotExist {})?;
+    let proposal = proposal_response(deps.as_ref(), &env, proposal)?;
+
+    Ok(Response::new()
+        .set_data(to_json_binary(&proposal)?)
+        .add_attribute("method", "execute_read_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+// the admin, or governance itself through a passed `Custom` proposal calling back in
+fn ensure_admin_or_self(
+    storage: &dyn Storage,
+    env: &Env,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    if *sender == env.contract.address || CONFIG.load(storage)?.admin.as_ref() == Some(sender) {
+        return Ok(());
+    }
+    Err(ContractError::Unauthorized {})
+}
+
+fn execute_suspend_member(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    member: String,
+    reason: Reason,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.admin.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let member = deps.api.addr_validate(&member)?;
+    if !MEMBERS.has(deps.storage, &member) {
+        return Err(invalid_member_entry(&member, "not a member"));
+    }
+    let proposal = create_proposal(
+        deps.branch(),
+        &env,
+        Title::new(format!("Confirm suspension of {}", member))?,
+        Description::new(reason.as_str())?,
+        member.clone(),
+        Uint128::zero(),
+        ProposalType::ConfirmSuspension {
+            member: member.clone(),
+        },
+        None,
+        None,
+        None,
+        None,
+    )?;
+
+    let suspension = Suspension {
+        reason,
+        suspended_at: env.block.time,
+        expires_at: clock(deps.storage, &env)?.after(duration),
+        confirmation_proposal_id: proposal.id,
+    };
+    SUSPENSIONS.save(deps.storage, &member, &suspension)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_suspend_member")
+        .add_attribute("member", member)
+        .add_attribute("proposal_id", proposal.id.to_string())
+        .add_attribute("expires_at", suspension.expires_at.to_string())
+        .add_submessages(notify(deps.as_ref(), proposal.id, ProposalEvent::Opened)?))
+}
+
+fn execute_rotate_key(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_address: String,
+) -> Result<Response, ContractError> {
+    if !MEMBERS.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    ensure_not_suspended(deps.storage, &env, &info.sender)?;
+
+    let new_address = deps.api.addr_validate(&new_address)?;
+    if MEMBERS.has(deps.storage, &new_address) {
+        return Err(invalid_member_entry(&new_address, "already a member"));
+    }
+
+    let response = Response::new()
+        .add_attribute("method", "execute_rotate_key")
+        .add_attribute("old_address", info.sender.clone())
+        .add_attribute("new_address", new_address.clone());
+
+    match CONFIG.load(deps.storage)?.key_rotation_delay {
+        Some(delay) if delay > 0 => {
+            let rotation = KeyRotation {
+                old_address: info.sender,
+                executable_at: clock(deps.storage, &env)?.after(delay),
+            };
+            KEY_ROTATIONS.save(deps.storage, &new_address, &rotation)?;
+
+            Ok(response.add_attribute("executable_at", rotation.executable_at.to_string()))
+        }
+        _ => {
+            let moved_ballots =
+                move_membership(deps.storage, env.block.height, &info.sender, &new_address)?;
+
+            Ok(response.add_attribute("moved_ballots", moved_ballots.to_string()))
+        }
+    }
+}
+
+fn execute_confirm_key_rotation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let rotation = KEY_ROTATIONS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::Unauthorized {})?;
+
+    if !rotation.executable_at.is_triggered(&env.block) {
+        return Err(ContractError::KeyRotationPending {
+            executable_at: rotation.executable_at.to_string(),
+        });
+    }
+
+    // membership may have changed while the rotation was waiting
+    if !MEMBERS.has(deps.storage, &rotation.old_address) {
+        return Err(invalid_member_entry(&rotation.old_address, "not a member"));
+    }
+    if MEMBERS.has(deps.storage, &info.sender) {
+        return Err(invalid_member_entry(&info.sender, "already a member"));
+    }
+    ensure_not_suspended(deps.storage, &env, &rotation.old_address)?;
+
+    KEY_ROTATIONS.remove(deps.storage, &info.sender);
+    let moved_ballots = move_membership(
+        deps.storage,
+        env.block.height,
+        &rotation.old_address,
+        &info.sender,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_confirm_key_rotation")
+        .add_attribute("old_address", rotation.old_address)
+        .add_attribute("new_address", info.sender)
+        .add_attribute("moved_ballots", moved_ballots.to_string()))
+}
+
+// ballots on executed proposals stay with the old address as part of the historical record
+fn move_membership(
+    storage: &mut dyn Storage,
+    height: u64,
+    old: &Addr,
+    new: &Addr,
+) -> StdResult<u32> {
+    let mut member = MEMBERS.load(storage, old)?;
+    member.address = new.clone();
+    tracked_remove(storage, &MEMBERS, USAGE_MEMBERS, old)?;
+    tracked_save(storage, &MEMBERS, USAGE_MEMBERS, new, &member)?;
+    VOTING_POWER.remove(storage, old, height)?;
+    VOTING_POWER.save(storage, new, &member.weight, height)?;
+    index_weight(storage, old, Some(member.weight), None)?;
+    index_weight(storage, new, None, Some(member.weight))?;
+    ROTATED_FROM.save(storage, new, old)?;
+    if let Some(group) = MEMBER_GROUPS.may_load(storage, old)? {
+        MEMBER_GROUPS.remove(storage, old);
+        MEMBER_GROUPS.save(storage, new, &group)?;
+    }
+
+    if let Some(profile) = PROFILES.may_load(storage, old)? {
+        PROFILES.remove(storage, old);
+        PROFILES.save(storage, new, &profile)?;
+    }
+    if let Some(operator) = VOTE_OPERATORS.may_load(storage, old)? {
+        VOTE_OPERATORS.remove(storage, old);
+        VOTE_OPERATORS.save(storage, new, &operator)?;
+    }
+    if let Some(delegate) = DELEGATIONS.may_load(storage, old)? {
+        DELEGATIONS.remove(storage, old);
+        DELEGATORS.remove(storage, (&delegate, old));
+        DELEGATIONS.save(storage, new, &delegate)?;
+        DELEGATORS.save(storage, (&delegate, new), &Empty {})?;
+    }
+    for delegator in delegators(storage, old)? {
+        DELEGATORS.remove(storage, (old, &delegator));
+        DELEGATORS.save(storage, (new, &delegator), &Empty {})?;
+        DELEGATIONS.save(storage, &delegator, new)?;
+    }
+
+    // only ballots that can still count move, decided proposals keep theirs as cast. Statuses
+    // are stored lazily, so proposals that ended since their last write are still under open
+    let mut live = vec![];
+    for status in [
+        ProposalStatus::Open,
+        ProposalStatus::Tied,
+        ProposalStatus::Passed,
+    ] {
+        for id in
+            PROPOSALS
+                .idx
+                .status
+                .prefix(status.as_str())
+                .keys(storage, None, None, Order::Ascending)
+        {
+            live.push(id?);
+        }
+    }
+
+    let mut moved = 0;
+    for id in live {
+        if let Some(mut ballot) = BALLOTS.may_load(storage, (id, old))? {
+            ballot.voter = new.clone();
+            tracked_remove(storage, &BALLOTS, USAGE_BALLOTS, (id, old))?;
+            tracked_save(storage, &BALLOTS, USAGE_BALLOTS, (id, new), &ballot)?;
+            // what the ballot carries stays recorded as carried by it
+            for delegator in &ballot.delegators {
+                if CARRIED_VOTES.may_load(storage, (id, delegator))?.as_ref() == Some(old) {
+                    CARRIED_VOTES.save(storage, (id, delegator), new)?;
+                }
+            }
+            moved += 1;
+        }
+        if let Some(carrier) = CARRIED_VOTES.may_load(storage, (id, old))? {
+            CARRIED_VOTES.remove(storage, (id, old));
+            CARRIED_VOTES.save(storage, (id, new), &carrier)?;
+        }
+    }
+
+    Ok(moved)
+}
+
+// rounding dust from the pro-rata split stays in the treasury
+fn schedule_rewards(
+    deps: DepsMut,
+    proposal_id: u64,
+    pool: Uint128,
+    start: Timestamp,
+    end: Timestamp,
+    min_participation: Decimal,
+) -> StdResult<RewardDistribution> {
+    let snapshot = query_participation_snapshot(
+        deps.as_ref(),
+        ProposalSelection::ExecutedBetween { start, end },
+    )?;
+    let proposal_count = snapshot.proposal_ids.len() as u64;
+
+    let eligible: Vec<Participation> = snapshot
+        .participants
+        .into_iter()
+        .filter(|p| {
+            proposal_count > 0
+                && Decimal::from_ratio(p.proposals_voted, proposal_count) >= min_participation
+        })
+        .collect();
+    let total_weight: Uint128 = eligible.iter().map(|p| p.weight).sum();
+
+    let mut pending = 0;
+    let mut owed = Uint128::zero();
+    for participant in &eligible {
+        let amount = pool.multiply_ratio(participant.weight, total_weight);
+        if !amount.is_zero() {
+            save_payout(deps.storage, proposal_id, &participant.address, amount)?;
+            pending += 1;
+            owed += amount;
+        }
+    }
+    reserve_owed(deps.storage, owed)?;
+
+    let distribution = RewardDistribution {
+        pool,
+        eligible: eligible.len() as u32,
+        pending,
+        paid: Uint128::zero(),
+        shadow: CONFIG.load(deps.storage)?.shadow_funds.is_some(),
+        reserved: true,
+    };
+    REWARD_DISTRIBUTIONS.save(deps.storage, proposal_id, &distribution)?;
+
+    Ok(distribution)
+}
+
+fn execute_pay_rewards(
+    deps: DepsMut,
+    env: Env,
+    proposal_id: u64,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let mut distribution = REWARD_DISTRIBUTIONS
+        .load(deps.storage, proposal_id)
+        .map_err(|_| ContractError::ProposalDoesNotExist {})?;
+
+    let limit = limit.unwrap_or(DEFAULT_PAYOUT_LIMIT).min(MAX_PAYOUT_LIMIT) as usize;
+    let payouts = REWARD_PAYOUTS
+        .prefix(proposal_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    if payouts.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let denom = CONFIG.load(deps.storage)?.denom;
+    let mut response = Response::new();
+    for (recipient, amount) in &payouts {
+        remove_payout(deps.storage, proposal_id, recipient);
+        distribution.paid += *amount;
+        response = payout(
+            deps.storage,
+            distribution.shadow,
+            recipient,
+            *amount,
+            &denom,
+            response,
+        )?;
+    }
+    distribution.pending -= payouts.len() as u32;
+    if distribution.reserved {
+        release_owed(
+            deps.storage,
+            payouts.iter().map(|(_, amount)| *amount).sum(),
+        )?;
+    }
+    REWARD_DISTRIBUTIONS.save(deps.storage, proposal_id, &distribution)?;
+    record_outbox(deps.storage, &env, proposal_id, &response)?;
+
+    Ok(response
+        .add_attribute("method", "execute_pay_rewards")
+        .add_attribute("paid", payouts.len().to_string())
+        .add_attribute("pending", distribution.pending.to_string()))
+}
+
+// distributions with a payout still pending to `address`, and the amount owed by each
+// payouts are indexed by member too, so finding one member's reads only their own
+fn save_payout(
+    storage: &mut dyn Storage,
+    proposal_id: u64,
+    address: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    PAYOUTS_BY_MEMBER.save(storage, (address, proposal_id), &Empty {})?;
+    REWARD_PAYOUTS.save(storage, (proposal_id, address), &amount)
+}
+
+fn remove_payout(storage: &mut dyn Storage, proposal_id: u64, address: &Addr) {
+    PAYOUTS_BY_MEMBER.remove(storage, (address, proposal_id));
+    REWARD_PAYOUTS.remove(storage, (proposal_id, address));
+}
+
+fn pending_payouts(storage: &dyn Storage, address: &Addr) -> StdResult<Vec<(u64, Uint128)>> {
+    PAYOUTS_BY_MEMBER
+        .prefix(address)
+        .keys(storage, None, None, Order::Ascending)
+        .map(|proposal_id| {
+            let proposal_id = proposal_id?;
+            Ok((
+                proposal_id,
+                REWARD_PAYOUTS.load(storage, (proposal_id, address))?,
+            ))
+        })
+        .collect()
+}
+
+fn execute_claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if let Some(closes_at) = CLAIM_WINDOWS.may_load(deps.storage, &info.sender)? {
+        if closes_at.is_triggered(&env.block) {
+            return Err(ContractError::ClaimWindowClosed {
+                closed_at: closes_at.to_string(),
+            });
+        }
+    }
+    let pending = pending_payouts(deps.storage, &info.sender)?;
+    if pending.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let denom = CONFIG.load(deps.storage)?.denom;
+    let mut response = Response::new();
+    let mut claimed = Uint128::zero();
+    for (proposal_id, amount) in &pending {
+        let mut distribution = REWARD_DISTRIBUTIONS.load(deps.storage, *proposal_id)?;
+        remove_payout(deps.storage, *proposal_id, &info.sender);
+        distribution.paid += *amount;
+        distribution.pending -= 1;
+        if distribution.reserved {
+            release_owed(deps.storage, *amount)?;
+        }
+        REWARD_DISTRIBUTIONS.save(deps.storage, *proposal_id, &distribution)?;
+
+        // each distribution's payout goes in its own outbox entry, as `PayRewards` would log it
+        let paid = payout(
+            deps.storage,
+            distribution.shadow,
+            &info.sender,
+            *amount,
+            &denom,
+            Response::new(),
+        )?;
+        record_outbox(deps.storage, &env, *proposal_id, &paid)?;
+        response = response
+            .add_submessages(paid.messages)
+            .add_attributes(paid.attributes);
+        claimed += *amount;
+    }
+
+    Ok(response
+        .add_attribute("method", "execute_claim_rewards")
+        .add_attribute("distributions", pending.len().to_string())
+        .add_attribute("claimed", claimed))
+}
+
+fn execute_prune_removed_member(
+    deps: DepsMut,
+    env: Env,
+    address: String,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    let closes_at = CLAIM_WINDOWS
+        .may_load(deps.storage, &address)?
+        .ok_or_else(|| invalid_member_entry(&address, "no claim window"))?;
+    if !closes_at.is_triggered(&env.block) {
+        return Err(ContractError::ClaimWindowOpen {
+            closes_at: closes_at.to_string(),
+        });
+    }
+
+    let pending = pending_payouts(deps.storage, &address)?;
+    let mut forfeited = Uint128::zero();
+    for (proposal_id, amount) in &pending {
+        remove_payout(deps.storage, *proposal_id, &address);
+        let distribution =
+            REWARD_DISTRIBUTIONS.update(deps.storage, *proposal_id, |distribution| {
+                let mut distribution =
+                    distribution.ok_or_else(|| StdError::not_found("distribution"))?;
+                distribution.pending -= 1;
+                StdResult::Ok(distribution)
+            })?;
+        // a forfeited payout goes back to the treasury
+        if distribution.reserved {
+            release_owed(deps.storage, *amount)?;
+        }
+        forfeited += *amount;
+    }
+    CLAIM_WINDOWS.remove(deps.storage, &address);
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_prune_removed_member")
+        .add_attribute("address", address)
+        .add_attribute("pruned", pending.len().to_string())
+        .add_attribute("forfeited", forfeited))
+}
+
+fn execute_archive_proposals(
+    deps: DepsMut,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let retention = match config.archive_after {
+        Some(retention) => retention,
+        None => {
+            return Err(ContractError::InvalidInput(
+                "archiving is not enabled".to_string(),
+            ))
+        }
+    };
+    let clock = Clock::new(config.clock.unwrap_or_default(), &env.block);
+    let limit = limit
+        .unwrap_or(DEFAULT_ARCHIVE_LIMIT)
+        .min(MAX_ARCHIVE_LIMIT) as usize;
+
+    let start = start_after.map(Bound::exclusive);
+
+    // the scan is bounded by `limit` whether or not what it finds is due
+    let executed = PROPOSALS
+        .idx
+        .status
+        .prefix(ProposalStatus::Executed.as_str())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut due = vec![];
+    for (id, proposal) in &executed {
+        match proposal.execution.clone() {
+            Some(execution)
+                if clock
+                    .after_block(execution.height, execution.time, retention)
+                    .is_triggered(&env.block) =>
+            {
+                due.push((*id, proposal.clone(), execution))
+            }
+            _ => {}
+        }
+    }
+
+    let mut response = Response::new();
+    for (id, proposal, execution) in &mut due {
+        // the proposer can no longer withdraw once the record is gone, so refund them here
+        let depositor = proposal.deposit.as_ref().map(|d| d.depositor.clone());
+        let refund = settle_deposit(deps.storage, proposal)?;
+        if let Some(depositor) = depositor.filter(|_| !refund.is_zero()) {
+            response = response.add_message(grant_transfer(&depositor, refund, &config.denom));
+        }
+        let tally = TALLIES.may_load(deps.storage, *id)?.unwrap_or_default();
+        let archived = ArchivedProposal {
+            id: *id,
+            title_hash: HexBinary::from(Sha256::digest(proposal.title.as_str()).to_vec()),
+            recipient: proposal.recipient.clone(),
+            amount: proposal.amount,
+            votes_for: tally.votes_for,
+            votes_against: tally.votes_against,
+            execution: execution.clone(),
+        };
+        tracked_save(deps.storage, &ARCHIVE, USAGE_ARCHIVE, *id, &archived)?;
+        remove_proposal(deps.storage, proposal)?;
+        TALLIES.remove(deps.storage, *id);
+    }
+
+    response = response
+        .add_attribute("method", "execute_archive_proposals")
+        .add_attribute("archived", due.len().to_string());
+    // a full chunk means there may be more to go
+    if executed.len() == limit {
+        if let Some((id, _)) = executed.last() {
+            response = response.add_attribute("last_proposal_id", id.to_string());
+        }
+    }
+    Ok(response)
+}
+
+fn execute_claim_vested(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let mut vesting = VESTINGS
+        .load(deps.storage, proposal_id)
+        .map_err(|_| ContractError::ProposalDoesNotExist {})?;
+
+    if info.sender != vesting.recipient {
+        return Err(ContractError::Unauthorized {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    if config.lock_self_voted_vesting
+        && BALLOTS.has(deps.storage, (proposal_id, &vesting.recipient))
+    {
+        return Err(ContractError::InvalidInput(
+            "vested claims are locked for recipients who voted on their own grant".to_string(),
+        ));
+    }
+
+    let claimable = vested_amount(&vesting, &env)? - vesting.claimed;
+    if claimable.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    vesting.claimed += claimable;
+    VESTINGS.save(deps.storage, proposal_id, &vesting)?;
+    if vesting.reserved {
+        release_owed(deps.storage, claimable)?;
+    }
+
+    let response = payout(
+        deps.storage,
+        vesting.shadow,
+        &vesting.recipient,
+        claimable,
+        &config.denom,
+        Response::new(),
+    )?;
+    record_outbox(deps.storage, &env, proposal_id, &response)?;
+    Ok(response
+        .add_attribute("method", "execute_claim_vested")
+        .add_attribute("recipient", vesting.recipient)
+        .add_attribute("amount", claimable))
+}
+
+fn vested_amount(vesting: &Vesting, env: &Env) -> StdResult<Uint128> {
+    let (elapsed, duration) = progress(&env.block, &vesting.start, &vesting.end)?;
+
+    Ok(vesting.vesting_amount.multiply_ratio(elapsed, duration))
+}
+
+fn execute_break_tie(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    approve: bool,
+) -> Result<Response, ContractError> {
+    let proposal = PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalDoesNotExist {})?;
+    if proposal.proposer.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut tally = TALLIES.load(deps.storage, proposal_id)?;
+    let total_weight = electorate(deps.storage, &tally)?;
+    if proposal_status(&config, &proposal, &tally, total_weight, &env.block) != ProposalStatus::Tied
+    {
+        return Err(ContractError::InvalidInput(
+            "the proposal is not waiting on a tie break".to_string(),
+        ));
+    }
+    tally.casting_vote = Some(approve);
+    TALLIES.save(deps.storage, proposal_id, &tally)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_break_tie")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("approve", approve.to_string()))
+}
+
+fn execute_withdraw_deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let mut proposal = PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalDoesNotExist {})?;
+    match &proposal.deposit {
+        Some(deposit) if deposit.depositor == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let tally = TALLIES.load(deps.storage, proposal_id)?;
+    let total_weight = electorate(deps.storage, &tally)?;
+    let status = proposal_status(&config, &proposal, &tally, total_weight, &env.block);
+    if !matches!(
+        status,
+        ProposalStatus::Passed | ProposalStatus::Executed | ProposalStatus::Lapsed
+    ) {
+        return Err(ContractError::InvalidInput(
+            "deposits are refunded once the proposal passes".to_string(),
+        ));
+    }
+
+    let refund = settle_deposit(deps.storage, &mut proposal)?;
+    if refund.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    save_proposal(deps.storage, &proposal)?;
+
+    Ok(Response::new()
+        .add_message(grant_transfer(&info.sender, refund, &config.denom))
+        .add_attribute("method", "execute_withdraw_deposit")
+        .add_attribute("amount", refund))
+}
+
+fn execute_close(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let mut proposal = PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalDoesNotExist {})?;
+    let mut tally = TALLIES.load(deps.storage, proposal_id)?;
+    ensure_unlocked(&tally)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let total_weight = electorate(deps.storage, &tally)?;
+    let status = proposal_status(&config, &proposal, &tally, total_weight, &env.block);
+    if !matches!(
+        status,
+        ProposalStatus::Rejected | ProposalStatus::Expired | ProposalStatus::Lapsed
+    ) {
+        return Err(ContractError::InvalidInput(
+            "only rejected, expired or lapsed proposals can be closed".to_string(),
+        ));
+    }
+
+    proposal.status = status;
+    // a lapsed proposal did pass, its deposit stays withdrawable
+    let forfeited = if status == ProposalStatus::Lapsed {
+        Uint128::zero()
+    } else {
+        settle_deposit(deps.storage, &mut proposal)?
+    };
+    let bounty = settle_bounty(
+        deps.storage,
+        &config,
+        &tally,
+        &mut proposal,
+        &info.sender,
+        &env,
+    )?;
+    save_proposal(deps.storage, &proposal)?;
+    release_commitment(deps.storage, &mut tally)?;
+    tally.locked = true;
+    TALLIES.save(deps.storage, proposal_id, &tally)?;
+    if status == ProposalStatus::Rejected {
+        let turnout = participation(&tally, total_weight);
+        record_analytics(deps.storage, &env, |stats| {
+            stats.rejected += 1;
+            stats.participation += turnout;
+        })?;
+    }
+
+    let mut response = Response::new();
+    if tied(&config, &tally, total_weight) {
+        response = response.add_attribute("tie_break", config.tie_break.as_str());
+    }
+    response = forfeit_deposit(&config, forfeited, response);
+    if let Some((recipient, bounty)) = bounty {
+        response = response
+            .add_message(grant_transfer(&recipient, bounty, &config.denom))
+            .add_attribute("bounty_paid_to", recipient);
+    }
+
+    let (status, event) = match status {
+        ProposalStatus::Rejected => ("rejected", ProposalEvent::Rejected),
+        ProposalStatus::Lapsed => ("lapsed", ProposalEvent::Lapsed),
+        _ => ("expired", ProposalEvent::Expired),
+    };
+    Ok(response
+        .add_attribute("method", "execute_close")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("status", status)
+        .add_submessages(notify(deps.as_ref(), proposal_id, event)?))
+}
+
+fn execute_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let mut proposal = PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalDoesNotExist {})?;
+    if proposal.proposer.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut tally = TALLIES.load(deps.storage, proposal_id)?;
+    ensure_unlocked(&tally)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let total_weight = electorate(deps.storage, &tally)?;
+    let status = proposal_status(&config, &proposal, &tally, total_weight, &env.block);
+    if status != ProposalStatus::Open {
+        return Err(ContractError::InvalidInput(
+            "only open proposals can be cancelled".to_string(),
+        ));
+    }
+    let own_weight = BALLOTS
+        .may_load(deps.storage, (proposal_id, &info.sender))?
+        .map(|ballot| ballot.counted_weight())
+        .unwrap_or_default();
+    if tally.votes_for + tally.votes_against > own_weight {
+        return Err(ContractError::InvalidInput(
+            "other members have already voted".to_string(),
+        ));
+    }
+
+    proposal.status = ProposalStatus::Cancelled;
+    let refund = settle_deposit(deps.storage, &mut proposal)?;
+    let bounty = settle_bounty(
+        deps.storage,
+        &config,
+        &tally,
+        &mut proposal,
+        &info.sender,
+        &env,
+    )?
+    .map(|(_, amount)| amount)
+    .unwrap_or_default();
+    save_proposal(deps.storage, &proposal)?;
+    release_commitment(deps.storage, &mut tally)?;
+    tally.locked = true;
+    TALLIES.save(deps.storage, proposal_id, &tally)?;
+
+    let mut response = Response::new();
+    if !(refund + bounty).is_zero() {
+        response = response
+            .add_message(grant_transfer(&info.sender, refund + bounty, &config.denom))
+            .add_attribute("refunded", refund + bounty);
+    }
+    Ok(response
+        .add_attribute("method", "execute_cancel")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_submessages(notify(
+            deps.as_ref(),
+            proposal_id,
+            ProposalEvent::Cancelled,
+        )?))
+}
+
+fn execute_veto_proposal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.guardian.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut proposal = PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalDoesNotExist {})?;
+    let mut tally = TALLIES.load(deps.storage, proposal_id)?;
+    ensure_unlocked(&tally)?;
+
+    let total_weight = electorate(deps.storage, &tally)?;
+    let status = proposal_status(&config, &proposal, &tally, total_weight, &env.block);
+    let timelocked = config
+        .execution_delay
+        .zip(effective_voting_end(&config, &tally, total_weight))
+        .is_some_and(|(delay, end)| !delayed(end, delay).is_triggered(&env.block));
+    if status != ProposalStatus::Passed || !timelocked {
+        return Err(ContractError::InvalidInput(
+            "only passed proposals still in their timelock can be vetoed".to_string(),
+        ));
+    }
+
+    proposal.status = ProposalStatus::Vetoed;
+    let forfeited = settle_deposit(deps.storage, &mut proposal)?;
+    // the guardian is not owed a finalization bounty, it goes back to the proposer
+    let bounty = match proposal.proposer.clone() {
+        Some(proposer) => settle_bounty(
+            deps.storage,
+            &config,
+            &tally,
+            &mut proposal,
+            &proposer,
+            &env,
+        )?,
+        None => None,
+    };
+    save_proposal(deps.storage, &proposal)?;
+    release_commitment(deps.storage, &mut tally)?;
+    tally.locked = true;
+    TALLIES.save(deps.storage, proposal_id, &tally)?;
+
+    let mut response = forfeit_deposit(&config, forfeited, Response::new());
+    if let Some((recipient, bounty)) = bounty {
+        response = response
+            .add_message(grant_transfer(&recipient, bounty, &config.denom))
+            .add_attribute("bounty_paid_to", recipient);
+    }
+    Ok(response
+        .add_attribute("method", "execute_veto_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_submessages(notify(deps.as_ref(), proposal_id, ProposalEvent::Vetoed)?))
+}
+
+// forfeited deposits stay in the treasury unless configured to burn
+fn forfeit_deposit(config: &Config, forfeited: Uint128, response: Response) -> Response {
+    if forfeited.is_zero() {
+        return response;
+    }
+    let response = if config.burn_forfeited_deposits {
+        response.add_message(BankMsg::Burn {
+            amount: vec![Coin {
+                denom: config.denom.clone(),
+                amount: forfeited,
+            }],
+        })
+    } else {
+        response
+    };
+    response.add_attribute("deposit_forfeited", forfeited)
+}
+
+/// Where a proposal stands as of `block`, only final statuses are ever stored
+fn proposal_status(
+    config: &Config,
+    proposal: &Proposal,
+    tally: &Tally,
+    total_weight: Uint128,
+    block: &BlockInfo,
+) -> ProposalStatus {
+    let status = vote_outcome(config, proposal, tally, total_weight, block);
+    let lapses_at = config
+        .execution_expiration
+        .zip(effective_voting_end(config, tally, total_weight))
+        .map(|(expiration, end)| {
+            delayed(end, config.execution_delay.unwrap_or_default() + expiration)
+        });
+    if status == ProposalStatus::Passed && lapses_at.is_some_and(|at| at.is_triggered(block)) {
+        return ProposalStatus::Lapsed;
+    }
+    status
+}
+
+fn vote_outcome(
+    config: &Config,
+    proposal: &Proposal,
+    tally: &Tally,
+    total_weight: Uint128,
+    block: &BlockInfo,
+) -> ProposalStatus {
+    if proposal.execution.is_some() {
+        // also covers proposals executed before statuses were stored
+        return ProposalStatus::Executed;
+    }
+    if proposal.status != ProposalStatus::Open {
+        return proposal.status;
+    }
+
+    let voting = effective_voting_end(config, tally, total_weight)
+        .is_some_and(|end| !end.is_triggered(block));
+    if passes(config, tally, total_weight) {
+        if voting && !decided_early(config, tally, total_weight) {
+            return ProposalStatus::Open;
+        }
+        return ProposalStatus::Passed;
+    }
+    let outstanding = total_weight.saturating_sub(cast_weight(tally));
+    let ended = if tally.voting_end.is_some() {
+        !voting
+    } else {
+        outstanding.is_zero()
+    };
+    if ended && tied(config, tally, total_weight) {
+        match config.tie_break {
+            TieBreak::TiePasses => return ProposalStatus::Passed,
+            TieBreak::ProposerDecides if proposal.proposer.is_some() => {
+                return match tally.casting_vote {
+                    Some(true) => ProposalStatus::Passed,
+                    Some(false) => ProposalStatus::Rejected,
+                    None => ProposalStatus::Tied,
+                };
+            }
+            // an extended vote that is still tied fails like any other
+            _ => {}
+        }
+    }
+    if !voting && tally.voting_end.is_some() {
+        if quorum_met(config, tally, total_weight) {
+            return ProposalStatus::Rejected;
+        }
+        return ProposalStatus::Expired;
+    }
+
+    // settled before the deadline, or without one, once even the weight yet to vote could
+    // not carry it, a tie carries it too unless ties fail
+    // group quorums are assumed reachable, whose weight is outstanding is not tracked
+    let best_case = Tally {
+        votes_for: tally.votes_for + outstanding,
+        weight_cast: tally.weight_cast.map(|cast| cast + outstanding),
+        groups: Vec::new(),
+        ..tally.clone()
+    };
+    if passes(config, &best_case, total_weight)
+        || (config.tie_break != TieBreak::TieFails && tied(config, &best_case, total_weight))
+    {
+        ProposalStatus::Open
+    } else {
+        ProposalStatus::Rejected
+    }
+}
+
+fn quorum_met(config: &Config, tally: &Tally, total_weight: Uint128) -> bool {
+    let cast = cast_weight(tally);
+    // a decaying quorum counts as met at its floor, `effective_voting_end` keeps voting open
+    // for as long as the falling quorum takes to get down to the weight cast
+    let mut quorum = quorum_of(config, tally);
+    if let Some(decay) = &tally.quorum_decay {
+        quorum = quorum.min(decay.floor);
+    }
+    !cast.is_zero()
+        && cast >= total_weight.mul_ceil(quorum)
+        && tally
+            .groups
+            .iter()
+            .all(|group| group.votes_for + group.votes_against >= group.required)
+}
+
+fn threshold_met(config: &Config, tally: &Tally) -> bool {
+    let cast = tally.votes_for + tally.votes_against;
+    tally.votes_for > cast.mul_floor(threshold_of(config, tally))
+}
+
+fn quorum_of(config: &Config, tally: &Tally) -> Decimal {
+    tally.quorum.or(config.quorum).unwrap_or_default()
+}
+
+// `None` for tallies from before it was fixed and for proposals opened without a deadline,
+// which only the config can speak for
+fn voting_period_of(config: &Config, tally: &Tally) -> Option<u64> {
+    tally.voting_period.or(config.voting_period)
+}
+
+fn threshold_of(config: &Config, tally: &Tally) -> Decimal {
+    tally
+        .threshold
+        .or(config.threshold)
+        .unwrap_or(Decimal::percent(50))
+}
+
+/// Whether `tally` carries the proposal under the configured quorum and threshold
+fn passes(config: &Config, tally: &Tally, total_weight: Uint128) -> bool {
+    quorum_met(config, tally, total_weight) && threshold_met(config, tally)
+}
+
+/// Exactly as much weight for as against with quorum met, which the threshold alone treats as
+/// a rejection
+fn tied(config: &Config, tally: &Tally, total_weight: Uint128) -> bool {
+    tally.votes_for == tally.votes_against
+        && quorum_met(config, tally, total_weight)
+        && !passes(config, tally, total_weight)
+}
+
+/// `voting_end`, pushed back by another voting period while tied under `TieBreak::ExtendVoting`
+/// and by the quorum grace while short of quorum
+fn effective_voting_end(
+    config: &Config,
+    tally: &Tally,
+    total_weight: Uint128,
+) -> Option<Scheduled> {
+    let mut end = tally.voting_end?;
+    match voting_period_of(config, tally) {
+        Some(period)
+            if config.tie_break == TieBreak::ExtendVoting && tied(config, tally, total_weight) =>
+        {
+            end = delayed(end, period);
+        }
+        _ => {}
+    }
+    match &tally.quorum_decay {
+        Some(decay) => Some(delayed(
+            end,
+            quorum_grace(config, decay, tally, total_weight),
+        )),
+        None => Some(end),
+    }
+}
+
+// the quorum falls linearly from the configured one to the floor over the grace, voting stays
+// open until it is down to the weight cast or the grace runs out
+fn quorum_grace(config: &Config, decay: &QuorumDecay, tally: &Tally, total_weight: Uint128) -> u64 {
+    let cast = cast_weight(tally).u128();
+    let full = total_weight.mul_ceil(quorum_of(config, tally)).u128();
+    let floor = total_weight.mul_ceil(decay.floor).u128();
+    if cast >= full || floor >= full {
+        return 0;
+    }
+    if cast < floor || cast == 0 {
+        return decay.grace;
+    }
+    let span = full - floor;
+    ((decay.grace as u128 * (full - cast)).div_ceil(span)) as u64
+}
+
+// passed before the deadline when even the weight yet to vote could not overturn it
+fn decided_early(config: &Config, tally: &Tally, total_weight: Uint128) -> bool {
+    let outstanding = total_weight.saturating_sub(cast_weight(tally));
+    let worst_case = Tally {
+        votes_against: tally.votes_against + outstanding,
+        weight_cast: tally.weight_cast.map(|cast| cast + outstanding),
+        ..tally.clone()
+    };
+    passes(config, &worst_case, total_weight)
+}
+
+fn parent_dao(storage: &dyn Storage) -> Result<Addr, ContractError> {
+    CONFIG
+        .load(storage)?
+        .parent_dao
+        .ok_or_else(|| ContractError::InvalidInput("no parent DAO is configured".to_string()))
+}
+
+// deposits still owed back to proposers and funds reserved for passing proposals are not the
+// treasury's to spend, and in shadow mode only the simulated treasury is
+fn available_funds(deps: Deps, env: &Env, denom: &str) -> StdResult<Uint128> {
+    let committed = COMMITTED.may_load(deps.storage)?.unwrap_or_default();
+    if let Some(funds) = CONFIG.load(deps.storage)?.shadow_funds {
+        let spent = SHADOW_SPENT.may_load(deps.storage)?.unwrap_or_default();
+        return Ok(funds.saturating_sub(spent + committed));
+    }
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address.clone(), denom)?
+        .amount;
+    let held = HELD_DEPOSITS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(balance.saturating_sub(held + committed))
+}
+
+/// What executing `proposal` would take from the treasury
+fn commitment(storage: &dyn Storage, denom: &str, proposal: &Proposal) -> StdResult<Uint128> {
+    Ok(match &proposal.proposal_type {
+        ProposalType::Grant | ProposalType::VestedGrant { .. } => {
+            proposal.amount + referral_amount(&referral(storage, proposal)?)
+        }
+        ProposalType::GrantBatch { .. } | ProposalType::Expense { .. } => proposal.amount,
+        ProposalType::DistributeRewards { pool, .. } => *pool,
+        ProposalType::Custom { msgs } => outgoing(msgs, denom),
+        ProposalType::Swap { offer, .. } => *offer,
+        _ => Uint128::zero(),
+    })
+}
+
+// what executed proposals still owe, unvested grants and unpaid rewards, stays reserved until
+// it is paid out, so later proposals cannot spend it
+fn reserve_owed(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    let committed = COMMITTED.may_load(storage)?.unwrap_or_default();
+    COMMITTED.save(storage, &(committed + amount))
+}
+
+fn release_owed(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    let committed = COMMITTED.may_load(storage)?.unwrap_or_default();
+    COMMITTED.save(storage, &committed.saturating_sub(amount))
+}
+
+// hands a proposal's reservation back once it no longer passes or is decided
+fn release_commitment(storage: &mut dyn Storage, tally: &mut Tally) -> StdResult<()> {
+    if tally.committed {
+        COMMITTED.update(storage, |committed| -> StdResult<_> {
+            Ok(committed.saturating_sub(tally.commitment))
+        })?;
+        tally.committed = false;
+    }
+    Ok(())
+}
+
+/// Lets the proposer fund a reward for whoever finalizes the proposal once voting ends, held
+/// alongside deposits until then
+fn execute_attach_bounty(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let mut proposal = PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .ok_or(ContractError::ProposalDoesNotExist {})?;
+    if proposal.proposer.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if proposal.bounty.is_some() {
+        return Err(ContractError::InvalidInput(
+            "the proposal already has a bounty".to_string(),
+        ));
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let tally = TALLIES.load(deps.storage, proposal_id)?;
+    ensure_unlocked(&tally)?;
+    let end = tally.voting_end.ok_or_else(|| {
+        ContractError::InvalidInput("bounties need a voting deadline".to_string())
+    })?;
+    if end.is_triggered(&env.block) {
+        return Err(ContractError::VotingClosed {});
+    }
+    if bounty_deadline(&config, end).is_none() {
+        return Err(ContractError::InvalidInput(
+            "bounties on block heights need an expected block time".to_string(),
+        ));
+    }
+
+    let amount = must_pay(&info, &config.denom)?;
+    let held = HELD_DEPOSITS.may_load(deps.storage)?.unwrap_or_default();
+    HELD_DEPOSITS.save(deps.storage, &(held + amount))?;
+    proposal.bounty = Some(Bounty {
+        amount,
+        settled: false,
+    });
+    save_proposal(deps.storage, &proposal)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_attach_bounty")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("bounty", amount))
+}
+
+/// Until when after `end` finalizing a proposal earns its bounty, block heights are only
+/// converted with an expected block time
+fn bounty_deadline(config: &Config, end: Scheduled) -> Option<Scheduled> {
+    match end {
+        Scheduled::AtTime(time) => Some(Scheduled::AtTime(time.plus_seconds(BOUNTY_WINDOW))),
+        Scheduled::AtHeight(height) => config
+            .expected_block_time
+            .filter(|seconds| *seconds > 0)
+            .map(|seconds| Scheduled::AtHeight(height + BOUNTY_WINDOW.div_ceil(seconds))),
+    }
+}
+
+/// Settles an outstanding bounty, returning who it is owed to: the caller when the proposal is
+/// finalized in time, the proposer when it was left for too long
+fn settle_bounty(
+    storage: &mut dyn Storage,
+    config: &Config,
+    tally: &Tally,
+    proposal: &mut Proposal,
+    caller: &Addr,
+    env: &Env,
+) -> StdResult<Option<(Addr, Uint128)>> {
+    let bounty = match &mut proposal.bounty {
+        Some(bounty) if !bounty.settled => bounty,
+        _ => return Ok(None),
+    };
+    bounty.settled = true;
+    HELD_DEPOSITS.update(storage, |held| -> StdResult<_> {
+        Ok(held.saturating_sub(bounty.amount))
+    })?;
+
+    let total_weight = electorate(storage, tally)?;
+    let in_time = effective_voting_end(config, tally, total_weight)
+        .and_then(|end| bounty_deadline(config, end))
+        .is_some_and(|deadline| !deadline.is_triggered(&env.block));
+    let recipient = match &proposal.proposer {
+        Some(proposer) if !in_time => proposer.clone(),
+        _ => caller.clone(),
+    };
+    Ok(Some((recipient, bounty.amount)))
+}
+
+/// Settles an outstanding deposit, returning how much was released
+fn settle_deposit(storage: &mut dyn Storage, proposal: &mut Proposal) -> StdResult<Uint128> {
+    let deposit = match &mut proposal.deposit {
+        Some(deposit) if !deposit.settled => deposit,
+        _ => return Ok(Uint128::zero()),
+    };
+    deposit.settled = true;
+    HELD_DEPOSITS.update(storage, |held| -> StdResult<_> {
+        Ok(held.saturating_sub(deposit.amount))
+    })?;
+    Ok(deposit.amount)
+}
+
+/// Fee owed on a grant to the frontend it was submitted through, nothing if the frontend has
+/// since been removed or no fee is configured
+fn referral(storage: &dyn Storage, proposal: &Proposal) -> StdResult<Option<(Frontend, Uint128)>> {
+    let fee = match CONFIG.load(storage)?.referral_fee {
+        Some(fee) => fee,
+        None => return Ok(None),
+    };
+    let frontend = match &proposal.frontend_id {
+        Some(id) => FRONTENDS.may_load(storage, id.as_str())?,
+        None => None,
+    };
+    Ok(frontend
+        .map(|frontend| (frontend, proposal.amount.mul_floor(fee)))
+        .filter(|(_, amount)| !amount.is_zero()))
+}
+
+fn referral_amount(referral: &Option<(Frontend, Uint128)>) -> Uint128 {
+    referral
+        .as_ref()
+        .map_or(Uint128::zero(), |(_, amount)| *amount)
+}
+
+fn pay_referral(
+    storage: &mut dyn Storage,
+    shadow: bool,
+    referral: Option<(Frontend, Uint128)>,
+    denom: &str,
+    response: Response,
+) -> StdResult<Response> {
+    let (frontend, amount) = match referral {
+        Some(referral) => referral,
+        None => return Ok(response),
+    };
+    REFERRAL_FEES.update(storage, frontend.id.as_str(), |paid| -> StdResult<_> {
+        Ok(paid.unwrap_or_default() + amount)
+    })?;
+
+    let response = payout(storage, shadow, &frontend.operator, amount, denom, response)?;
+    Ok(response
+        .add_attribute("referral_fee", amount)
+        .add_attribute("referral_recipient", frontend.operator))
+}
+
+/// Coins of `denom` that `msgs` send out of the treasury
+fn outgoing(msgs: &[CosmosMsg], denom: &str) -> Uint128 {
+    msgs.iter()
+        .flat_map(|msg| match msg {
+            CosmosMsg::Bank(BankMsg::Send { amount, .. })
+            | CosmosMsg::Bank(BankMsg::Burn { amount }) => amount.as_slice(),
+            CosmosMsg::Wasm(WasmMsg::Execute { funds, .. })
+            | CosmosMsg::Wasm(WasmMsg::Instantiate { funds, .. }) => funds.as_slice(),
+            _ => &[],
+        })
+        .filter(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .sum()
+}
+
+/// Sends `amount` to `recipient`, or in shadow mode only books it in the shadow ledger
+fn payout(
+    storage: &mut dyn Storage,
+    shadow: bool,
+    recipient: &Addr,
+    amount: Uint128,
+    denom: &str,
+    response: Response,
+) -> StdResult<Response> {
+    if !shadow {
+        return Ok(response.add_message(grant_transfer(recipient, amount, denom)));
+    }
+    SHADOW_LEDGER.update(storage, recipient, |paid| -> StdResult<_> {
+        Ok(paid.unwrap_or_default() + amount)
+    })?;
+    let spent = SHADOW_SPENT.may_load(storage)?.unwrap_or_default();
+    SHADOW_SPENT.save(storage, &(spent + amount))?;
+    Ok(response.add_attribute(
+        "shadow_payout",
+        format!("{}{} to {}", amount, denom, recipient),
+    ))
+}
+
+fn grant_transfer(recipient: &Addr, amount: Uint128, denom: &str) -> BankMsg {
+    BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount: vec![Coin {
+            denom: denom.to_string(),
+            amount,
+        }],
+    }
+}
+
+fn invalid_member_entry(address: &Addr, reason: &str) -> ContractError {
+    ContractError::InvalidMemberEntry {
+        address: address.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+// checks that don't depend on current membership, so bad batches are rejected at propose time
+fn validate_member_changes(
+    api: &dyn Api,
+    add: &[Member],
+    remove: &[Addr],
+    update: &[Member],
+) -> Result<(), ContractError> {
+    let mut seen = HashSet::new();
+
+    let weighted = add
+        .iter()
+        .chain(update)
+        .map(|m| (&m.address, Some(m.weight)));
+    for (address, weight) in weighted.chain(remove.iter().map(|a| (a, None))) {
+        api.addr_validate(address.as_str())
+            .map_err(|_| invalid_member_entry(address, "invalid address"))?;
+        if !seen.insert(address) {
+            return Err(invalid_member_entry(address, "listed more than once"));
+        }
+        if weight == Some(Uint128::zero()) {
+            return Err(invalid_member_entry(address, "weight must be positive"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Moves `member` into `group`, or out of any when `None`, keeping the group weights in step
+fn set_group(storage: &mut dyn Storage, member: &Member, group: Option<GroupId>) -> StdResult<()> {
+    if let Some(old) = MEMBER_GROUPS.may_load(storage, &member.address)? {
+        let mut entry = GROUPS.load(storage, old.as_str())?;
+        entry.members -= 1;
+        entry.weight -= member.weight;
+        if entry.members == 0 {
+            GROUPS.remove(storage, old.as_str());
+        } else {
+            GROUPS.save(storage, old.as_str(), &entry)?;
+        }
+        MEMBER_GROUPS.remove(storage, &member.address);
+    }
+    if let Some(group) = group {
+        let mut entry = GROUPS.may_load(storage, group.as_str())?.unwrap_or(Group {
+            id: group.clone(),
+            members: 0,
+            weight: Uint128::zero(),
+        });
+        entry.members += 1;
+        entry.weight += member.weight;
+        GROUPS.save(storage, group.as_str(), &entry)?;
+        MEMBER_GROUPS.save(storage, &member.address, &group)?;
+    }
+    Ok(())
+}
+
+// when a member removed now loses their pending payouts, `None` if they never do
+fn claim_window(storage: &dyn Storage, env: &Env) -> StdResult<Option<Scheduled>> {
+    Ok(match CONFIG.load(storage)?.removal_grace_period {
+        Some(grace_period) => Some(clock(storage, env)?.after(grace_period)),
+        None => None,
+    })
+}
+
+// applies a validated batch against current membership, returning the new total weight
+fn apply_member_changes(
+    storage: &mut dyn Storage,
+    height: u64,
+    claim_until: Option<Scheduled>,
+    add: &[Member],
+    remove: &[Addr],
+    update: &[Member],
+) -> Result<Uint128, ContractError> {
+    let mut total_weight = TOTAL_WEIGHT.may_load(storage)?.unwrap_or_default();
+
+    for address in remove {
+        let member = MEMBERS
+            .may_load(storage, address)?
+            .ok_or_else(|| invalid_member_entry(address, "not a member"))?;
+        total_weight -= member.weight;
+        set_group(storage, &member, None)?;
+        tracked_remove(storage, &MEMBERS, USAGE_MEMBERS, address)?;
+        VOTING_POWER.remove(storage, address, height)?;
+        index_weight(storage, address, Some(member.weight), None)?;
+        PROFILES.remove(storage, address);
+        VOTE_OPERATORS.remove(storage, address);
+        undelegate(storage, address)?;
+        for delegator in delegators(storage, address)? {
+            undelegate(storage, &delegator)?;
+        }
+        if let Some(claim_until) = claim_until {
+            CLAIM_WINDOWS.save(storage, address, &claim_until)?;
+        }
+    }
+
+    for member in update {
+        let current = MEMBERS
+            .may_load(storage, &member.address)?
+            .ok_or_else(|| invalid_member_entry(&member.address, "not a member"))?;
+        total_weight = total_weight - current.weight + member.weight;
+        // the group's weight follows the member's
+        if let Some(group) = MEMBER_GROUPS.may_load(storage, &member.address)? {
+            set_group(storage, &current, None)?;
+            set_group(storage, member, Some(group))?;
+        }
+        tracked_save(storage, &MEMBERS, USAGE_MEMBERS, &member.address, member)?;
+        VOTING_POWER.save(storage, &member.address, &member.weight, height)?;
+        index_weight(
+            storage,
+            &member.address,
+            Some(current.weight),
+            Some(member.weight),
+        )?;
+    }
+
+    for member in add {
+        if MEMBERS.has(storage, &member.address) {
+            return Err(invalid_member_entry(&member.address, "already a member"));
+        }
+        total_weight += member.weight;
+        tracked_save(storage, &MEMBERS, USAGE_MEMBERS, &member.address, member)?;
+        VOTING_POWER.save(storage, &member.address, &member.weight, height)?;
+        index_weight(storage, &member.address, None, Some(member.weight))?;
+        CLAIM_WINDOWS.remove(storage, &member.address);
+    }
+
+    TOTAL_WEIGHT.save(storage, &total_weight)?;
+    Ok(total_weight)
+}
+
+// `None` on either side for a member joining or leaving
+fn index_weight(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    old: Option<Uint128>,
+    new: Option<Uint128>,
+) -> StdResult<()> {
+    if let Some(old) = old {
+        MEMBERS_BY_WEIGHT.remove(storage, (old.u128(), address));
+    }
+    if let Some(new) = new {
+        MEMBERS_BY_WEIGHT.save(storage, (new.u128(), address), &Empty {})?;
+    }
+    Ok(())
+}
+
+/// `map.save` that also keeps the `category` entry of `STORAGE_USAGE` in step
+fn tracked_save<'a, K, T>(
+    storage: &mut dyn Storage,
+    map: &Map<'a, K, T>,
+    category: &str,
+    key: K,
+    value: &T,
+) -> StdResult<()>
+where
+    K: PrimaryKey<'a>,
+    T: Serialize + DeserializeOwned,
+{
+    let path = map.key(key);
+    let old = storage.get(&path).map(|old| path.len() + old.len());
+    let value = to_json_vec(value)?;
+    storage.set(&path, &value);
+    record_usage(storage, category, old, Some(path.len() + value.len()))
+}
+
+// `PROPOSALS` keeps its own indexes, only the storage accounting is done here
+fn save_proposal(storage: &mut dyn Storage, proposal: &Proposal) -> StdResult<()> {
+    let path = PROPOSALS.key(proposal.id);
+    let old = storage.get(&path).map(|old| path.len() + old.len());
+    PROPOSALS.save(storage, proposal.id, proposal)?;
+    let new = storage.get(&path).map(|new| path.len() + new.len());
+    record_usage(storage, USAGE_PROPOSALS, old, new)
+}
+
+fn remove_proposal(storage: &mut dyn Storage, proposal: &Proposal) -> StdResult<()> {
+    let path = PROPOSALS.key(proposal.id);
+    let old = storage.get(&path).map(|old| path.len() + old.len());
+    PROPOSALS.replace(storage, proposal.id, None, Some(proposal))?;
+    record_usage(storage, USAGE_PROPOSALS, old, None)
+}
+
+fn tracked_remove<'a, K, T>(
+    storage: &mut dyn Storage,
+    map: &Map<'a, K, T>,
+    category: &str,
+    key: K,
+) -> StdResult<()>
+where
+    K: PrimaryKey<'a>,
+    T: Serialize + DeserializeOwned,
+{
+    let path = map.key(key);
+    match storage.get(&path) {
+        Some(old) => {
+            storage.remove(&path);
+            record_usage(storage, category, Some(path.len() + old.len()), None)
+        }
+        None => Ok(()),
+    }
+}
+
+// saturating, entries written before tracking began are not counted
+fn record_usage(
+    storage: &mut dyn Storage,
+    category: &str,
+    removed: Option<usize>,
+    added: Option<usize>,
+) -> StdResult<()> {
+    let mut usage = STORAGE_USAGE
+        .may_load(storage, category)?
+        .unwrap_or_default();
+    if let Some(bytes) = removed {
+        usage.count = usage.count.saturating_sub(1);
+        usage.bytes = usage.bytes.saturating_sub(bytes as u64);
+    }
+    if let Some(bytes) = added {
+        usage.count += 1;
+        usage.bytes += bytes as u64;
+    }
+    STORAGE_USAGE.save(storage, category, &usage)
+}
+
+fn clock<'a>(storage: &dyn Storage, env: &'a Env) -> StdResult<Clock<'a>> {
+    let mode = CONFIG.load(storage)?.clock.unwrap_or_default();
+    Ok(Clock::new(mode, &env.block))
+}
+
+fn validate_config(deps: Deps, config: &Config) -> StdResult<()> {
+    validate_denom(&config.denom)?;
+    if let Some(notifier) = &config.notifier {
+        deps.api.addr_validate(notifier.as_str())?;
+    }
+    if let Some(parent_dao) = &config.parent_dao {
+        deps.api.addr_validate(parent_dao.as_str())?;
+    }
+    if let MembershipSource::Cw4 { group } = &config.membership {
+        deps.api.addr_validate(group.as_str())?;
+    }
+    if let Some(router) = &config.dex_router {
+        deps.api.addr_validate(router.as_str())?;
+    }
+    for token in &config.cw20_tokens {
+        deps.api.addr_validate(token.as_str())?;
+    }
+    if let Some(lane) = &config.security_lane {
+        for member in &lane.committee {
+            deps.api.addr_validate(member.as_str())?;
+        }
+        if lane.required_approvals == 0 || lane.required_approvals as usize > lane.committee.len() {
+            return Err(StdError::generic_err(
+                "a security lane needs between one and all of its committee to approve",
+            ));
+        }
+    }
+    if let Some(guardian) = &config.guardian {
+        deps.api.addr_validate(guardian.as_str())?;
+        if config.execution_delay.is_none() {
+            return Err(StdError::generic_err(
+                "a guardian needs an execution delay to veto within",
+            ));
+        }
+    }
+    for (name, share) in [
+        ("quorum", config.quorum),
+        ("threshold", config.threshold),
+        ("expense threshold", config.expense_threshold),
+    ] {
+        if share.is_some_and(|share| share > Decimal::one()) {
+            return Err(StdError::generic_err(format!(
+                "{} cannot exceed 100%",
+                name
+            )));
+        }
+    }
+    if config
+        .group_quorums
+        .iter()
+        .any(|rule| rule.quorum > Decimal::one())
+    {
+        return Err(StdError::generic_err("group quorum cannot exceed 100%"));
+    }
+    match config.voting_strategy {
+        VotingStrategy::Conviction if config.voting_period.is_none() => {
+            return Err(StdError::generic_err(
+                "conviction voting needs a voting period to count conviction over",
+            ));
+        }
+        VotingStrategy::Capped { cap } if cap.is_zero() => {
+            return Err(StdError::generic_err("a voting weight cap cannot be zero"));
+        }
+        _ => {}
+    }
+    if !config.quorum_decay.is_empty()
+        && (config.voting_period.is_none() || config.quorum.is_none())
+    {
+        return Err(StdError::generic_err(
+            "quorum decay needs a voting period and a quorum to decay from",
+        ));
+    }
+    if config
+        .quorum_decay
+        .iter()
+        .any(|rule| rule.grace == 0 || Some(rule.floor) >= config.quorum)
+    {
+        return Err(StdError::generic_err(
+            "quorum decay needs a grace and a floor below the quorum",
+        ));
+    }
+    if (config.execution_delay.is_some() || config.execution_expiration.is_some())
+        && config.voting_period.is_none()
+    {
+        return Err(StdError::generic_err(
+            "execution delays and expirations need a voting period to count from",
+        ));
+    }
+    if config
+        .referral_fee
+        .is_some_and(|fee| fee > MAX_REFERRAL_FEE)
+    {
+        return Err(StdError::generic_err(format!(
+            "referral fee cannot exceed {}",
+            MAX_REFERRAL_FEE
+        )));
+    }
+    Ok(())
+}
+
+// the sdk's own denom rules, so a bad denom fails here instead of on every payout
+fn validate_denom(denom: &str) -> StdResult<()> {
+    let mut chars = denom.chars();
+    let valid = (3..=128).contains(&denom.len())
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c));
+    if !valid {
+        return Err(StdError::generic_err(format!("invalid denom {}", denom)));
+    }
+    Ok(())
+}
+
+// scheduled config changes are applied by the first execute, sudo or migrate at or after
+// `effective_at`
+fn apply_pending_config(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    if PENDING_CONFIG
+        .may_load(storage)?
+        .is_some_and(|pending| pending.effective_at.is_triggered(&env.block))
+    {
+        let config = current_config(storage, env)?;
+        CONFIG.save(storage, &config)?;
+        PENDING_CONFIG.remove(storage);
+    }
+    Ok(())
+}
+
+/// The config with any scheduled changes that are due merged in, whether or not a write has
+/// applied them yet
+fn current_config(storage: &dyn Storage, env: &Env) -> StdResult<Config> {
+    let mut config = CONFIG.load(storage)?;
+    if let Some(pending) = PENDING_CONFIG.may_load(storage)? {
+        if pending.effective_at.is_triggered(&env.block) {
+            pending.changes.apply(&mut config);
+        }
+    }
+    Ok(config)
+}
+
+fn record_analytics(
+    storage: &mut dyn Storage,
+    env: &Env,
+    update: impl FnOnce(&mut PeriodStats),
+) -> StdResult<()> {
+    let period = env.block.time.seconds() / ANALYTICS_PERIOD;
+    let mut stats = ANALYTICS.may_load(storage, period)?.unwrap_or_default();
+    update(&mut stats);
+    ANALYTICS.save(storage, period, &stats)
+}
+
+fn participation(tally: &Tally, total_weight: Uint128) -> Decimal {
+    if total_weight.is_zero() {
+        return Decimal::zero();
+    }
+    Decimal::from_ratio(cast_weight(tally), total_weight)
+}
+
+// one entry per response that sends anything, notifications are added after and not logged
+fn record_outbox(
+    storage: &mut dyn Storage,
+    env: &Env,
+    proposal_id: u64,
+    response: &Response,
+) -> StdResult<()> {
+    if response.messages.is_empty() {
+        return Ok(());
+    }
+    let msgs: Vec<&CosmosMsg> = response.messages.iter().map(|sub| &sub.msg).collect();
+    let id = NEXT_OUTBOX_ID.may_load(storage)?.unwrap_or_default();
+    NEXT_OUTBOX_ID.save(storage, &(id + 1))?;
+    let entry = OutboxEntry {
+        id,
+        proposal_id,
+        digest: HexBinary::from(Sha256::digest(to_json_vec(&msgs)?).to_vec()),
+        messages: msgs.len() as u32,
+        height: env.block.height,
+        time: env.block.time,
+    };
+    OUTBOX.save(storage, id, &entry)
+}
+
+// notifications are fire-and-forget, a failing notifier must never block governance
+fn notify(deps: Deps, proposal_id: u64, event: ProposalEvent) -> StdResult<Option<SubMsg>> {
+    let notifier = match CONFIG.may_load(deps.storage)?.and_then(|c| c.notifier) {
+        Some(notifier) => notifier,
+        None => return Ok(None),
+    };
+
+    let msg = WasmMsg::Execute {
+        contract_addr: notifier.to_string(),
+        msg: to_json_binary(&NotifierMsg::ProposalUpdated { proposal_id, event })?,
+        funds: vec![],
+    };
+
+    Ok(Some(SubMsg::reply_on_error(msg, NOTIFY_REPLY_ID)))
+}
+
+fn execute_set_vote_operator(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: Option<String>,
+) -> Result<Response, ContractError> {
+    if !MEMBERS.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let response = Response::new().add_attribute("method", "execute_set_vote_operator");
+    match operator {
+        Some(operator) => {
+            let operator = deps.api.addr_validate(&operator)?;
+            if operator == info.sender {
+                return Err(ContractError::InvalidInput(
+                    "members vote for themselves without an operator".to_string(),
+                ));
+            }
+            VOTE_OPERATORS.save(deps.storage, &info.sender, &operator)?;
+            Ok(response.add_attribute("operator", operator))
+        }
+        None => {
+            VOTE_OPERATORS.remove(deps.storage, &info.sender);
+            Ok(response.add_attribute("operator", "none"))
+        }
+    }
+}
+
+fn execute_delegate(
+    deps: DepsMut,
+    info: MessageInfo,
+    delegate: String,
+) -> Result<Response, ContractError> {
+    if !MEMBERS.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let delegate = deps.api.addr_validate(&delegate)?;
+    if delegate == info.sender || !MEMBERS.has(deps.storage, &delegate) {
+        return Err(invalid_member_entry(
+            &delegate,
+            "not a member to delegate to",
+        ));
+    }
+    // delegations are one level deep, which is what rules out cycles
+    if DELEGATIONS.has(deps.storage, &delegate) {
+        return Err(invalid_member_entry(
+            &delegate,
+            "delegates their own weight",
+        ));
+    }
+    if !delegators(deps.storage, &info.sender)?.is_empty() {
+        return Err(ContractError::InvalidInput(
+            "members others delegate to cannot delegate".to_string(),
+        ));
+    }
+    if delegators(deps.storage, &delegate)?.len() >= MAX_DELEGATORS {
+        return Err(invalid_member_entry(&delegate, "has too many delegators"));
+    }
+
+    undelegate(deps.storage, &info.sender)?;
+    DELEGATIONS.save(deps.storage, &info.sender, &delegate)?;
+    DELEGATORS.save(deps.storage, (&delegate, &info.sender), &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_delegate")
+        .add_attribute("delegate", delegate))
+}
+
+fn execute_undelegate(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    if !undelegate(deps.storage, &info.sender)? {
+        return Err(ContractError::InvalidInput(
+            "the sender has not delegated".to_string(),
+        ));
+    }
+
+    Ok(Response::new().add_attribute("method", "execute_undelegate"))
+}
+
+/// Whether `delegator` had delegated
+fn undelegate(storage: &mut dyn Storage, delegator: &Addr) -> StdResult<bool> {
+    let delegate = DELEGATIONS.may_load(storage, delegator)?;
+    if let Some(delegate) = &delegate {
+        DELEGATIONS.remove(storage, delegator);
+        DELEGATORS.remove(storage, (delegate, delegator));
+    }
+    Ok(delegate.is_some())
+}
+
+// any contract can claim to be a token, but it only ever credits its own entry
+fn execute_receive(
+    deps: DepsMut,
+    info: MessageInfo,
+    receive: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // anyone can call `Receive`, only a listed token is trusted to have sent what it claims
+    if !CONFIG
+        .load(deps.storage)?
+        .cw20_tokens
+        .contains(&info.sender)
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    let donor = deps.api.addr_validate(&receive.sender)?;
+    if receive.amount.is_zero() {
+        return Err(ContractError::InvalidInput(
+            "nothing was received".to_string(),
+        ));
+    }
+    let held = CW20_TREASURY
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    CW20_TREASURY.save(deps.storage, &info.sender, &(held + receive.amount))?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_receive")
+        .add_attribute("token", info.sender)
+        .add_attribute("donor", donor)
+        .add_attribute("amount", receive.amount))
+}
+
+// saturating, tokens can also leave through calls the ledger does not recognise
+fn debit_cw20(storage: &mut dyn Storage, msgs: &[CosmosMsg]) -> StdResult<()> {
+    for (token, amount) in msgs.iter().filter_map(cw20_outgoing) {
+        let token = Addr::unchecked(token);
+        if let Some(held) = CW20_TREASURY.may_load(storage, &token)? {
+            CW20_TREASURY.save(storage, &token, &held.saturating_sub(amount))?;
+        }
+    }
+    Ok(())
+}
+
+fn execute_update_profile(
+    deps: DepsMut,
+    info: MessageInfo,
+    display_name: Option<DisplayName>,
+    avatar_uri: Option<Uri>,
+    bio_hash: Option<ContentHash>,
+) -> Result<Response, ContractError> {
+    if !MEMBERS.has(deps.storage, &info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let profile = Profile {
+        display_name,
+        avatar_uri,
+        bio_hash,
+    };
+
+    PROFILES.save(deps.storage, &info.sender, &profile)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "execute_update_profile")
+        .add_attribute("member", info.sender))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let version = cw2::get_contract_version(deps.storage)?;
+    if version.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidInput(format!(
+            "cannot migrate from {}",
+            version.contract
+        )));
+    }
+    match (
+        parse_version(&version.version),
+        parse_version(CONTRACT_VERSION),
+    ) {
+        (Some(from), Some(to)) if from <= to => {}
+        _ => {
+            return Err(ContractError::InvalidInput(format!(
+                "cannot migrate from version {} to {}",
+                version.version, CONTRACT_VERSION
+            )))
+        }
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    apply_pending_config(deps.storage, &env)?;
+
+    // earlier versions kept no voting power history, it starts from current membership
+    if VOTING_POWER
+        .range(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_none()
+    {
+        let members = MEMBERS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (address, member) in members {
+            VOTING_POWER.save(deps.storage, &address, &member.weight, env.block.height)?;
+        }
+    }
+    // nor the weight index
+    if MEMBERS_BY_WEIGHT
+        .keys_raw(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_none()
+    {
+        let members = MEMBERS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (address, member) in members {
+            index_weight(deps.storage, &address, None, Some(member.weight))?;
+        }
+    }
+
+    let response = Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", version.version);
+    match msg {
+        MigrateMsg::Upgrade {} => Ok(response),
+        MigrateMsg::UpgradeOriginalLayout {} => upgrade_original_layout(deps, env, response),
+        MigrateMsg::BackfillStatuses { start_after, limit } => {
+            backfill_statuses(deps, start_after, limit, response)
+        }
+    }
+}
+
+/// Proposal as the original contract stored it
+#[derive(Deserialize)]
+struct OriginalProposal {
+    title: String,
+    description: String,
+    recipient: Addr,
+    amount: Uint128,
+    votes_for: Uint128,
+    votes_against: Uint128,
+    executed: bool,
+}
+
+fn upgrade_original_layout(
+    deps: DepsMut,
+    env: Env,
+    response: Response,
+) -> Result<Response, ContractError> {
+    if CONFIG.exists(deps.storage) {
+        return Err(ContractError::InvalidInput(
+            "already on the current layout".to_string(),
+        ));
+    }
+    let config = Config {
+        denom: DEFAULT_DENOM.to_string(),
+        notifier: None,
+        receipt_salt: None,
+        admin: None,
+        key_rotation_delay: None,
+        clock: None,
+        expected_block_time: None,
+        archive_after: None,
+        referral_fee: None,
+        parent_dao: None,
+        voting_period: None,
+        quorum: None,
+        threshold: None,
+        expense_threshold: None,
+        proposal_deposit: None,
+        burn_forfeited_deposits: false,
+        tie_break: TieBreak::TieFails,
+        group_quorums: vec![],
+        shadow_funds: None,
+        execution_delay: None,
+        execution_expiration: None,
+        private_ballots: false,
+        guardian: None,
+        quorum_decay: vec![],
+        max_proposal_amount: None,
+        lock_self_voted_vesting: false,
+        voting_strategy: VotingStrategy::Linear,
+        membership: MembershipSource::Internal,
+        removal_grace_period: None,
+        private_drafts: false,
+        security_lane: None,
+        dex_router: None,
+        cw20_tokens: vec![],
+    };
+    CONFIG.save(deps.storage, &config)?;
+
+    // members kept their layout, they are only summed and counted
+    let members = MEMBERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut total_weight = Uint128::zero();
+    for (address, member) in &members {
+        total_weight += member.weight;
+        MEMBERS.remove(deps.storage, address);
+        tracked_save(deps.storage, &MEMBERS, USAGE_MEMBERS, address, member)?;
+    }
+    TOTAL_WEIGHT.save(deps.storage, &total_weight)?;
+
+    // every proposal was saved under id 0, so there is at most the one
+    let original = deps
+        .storage
+        .get(&PROPOSALS.key(0))
+        .map(|raw| from_json::<OriginalProposal>(&raw))
+        .transpose()?;
+    NEXT_PROPOSAL_ID.save(deps.storage, &(original.is_some() as u64))?;
+    if let Some(original) = original {
+        let proposal = Proposal {
+            id: 0,
+            title: Title::new(original.title).or_else(|_| Title::new("Proposal 0"))?,
+            description: Description::new(original.description)
+                .or_else(|_| Description::new(""))?,
+            recipient: original.recipient,
+            amount: original.amount,
+            executed: original.executed,
+            proposal_type: ProposalType::Grant,
+            execution: None,
+            frontend_id: None,
+            parent_proposal_id: None,
+            status: if original.executed {
+                ProposalStatus::Executed
+            } else {
+                ProposalStatus::Open
+            },
+            deposit: None,
+            proposer: None,
+            submitted_at: None,
+            bounty: None,
+            created_at: None,
+            created_height: None,
+        };
+        // who voted which way was never recorded, so no further votes can be checked against
+        // the ones already counted
+        let tally = Tally {
+            votes_for: original.votes_for,
+            votes_against: original.votes_against,
+            locked: original.executed,
+            voting_end: Some(Clock::new(ClockMode::Time, &env.block).now()),
+            migrated: true,
+            ..Tally::default()
+        };
+        // the original record does not parse as a `Proposal`, so it goes without the indexes
+        deps.storage.remove(&PROPOSALS.key(0));
+        save_proposal(deps.storage, &proposal)?;
+        TALLIES.save(deps.storage, 0, &tally)?;
+    }
+
+    Ok(response
+        .add_attribute("members", members.len().to_string())
+        .add_attribute("total_weight", total_weight))
+}
+
+/// `major.minor.patch`, pre-release and build suffixes are ignored
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+fn backfill_statuses(
+    deps: DepsMut,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    mut response: Response,
+) -> Result<Response, ContractError> {
+    let limit = limit
+        .unwrap_or(DEFAULT_MIGRATE_LIMIT)
+        .min(MAX_MIGRATE_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let proposals = PROPOSALS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut locked = 0;
+    for (id, proposal) in &proposals {
+        // proposals stored before the indexes were kept, writing them back indexes them
+        PROPOSALS.replace(deps.storage, *id, Some(proposal), None)?;
+        let execution = match &proposal.execution {
+            Some(execution) => execution,
+            None => continue,
+        };
+        EXECUTED_AT.save(deps.storage, (execution.time.nanos(), *id), &Empty {})?;
+        let mut tally = TALLIES.may_load(deps.storage, *id)?.unwrap_or_default();
+        if !tally.locked {
+            tally.locked = true;
+            TALLIES.save(deps.storage, *id, &tally)?;
+            locked += 1;
+        }
+    }
+
+    response = response.add_attribute("locked", locked.to_string());
+    // a full chunk means there may be more to go
+    if proposals.len() == limit {
+        if let Some((id, _)) = proposals.last() {
+            response = response.add_attribute("last_proposal_id", id.to_string());
+        }
+    }
+    Ok(response)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    apply_pending_config(deps.storage, &env)?;
+    match msg {
+        SudoMsg::AssertInvariants {} => assert_invariants(deps.as_ref(), &env),
+    }
+}
+
+fn assert_invariants(deps: Deps, env: &Env) -> Result<Response, ContractError> {
+    let violated = |invariant: String| ContractError::InvariantViolated { invariant };
+
+    // the total only holds over the whole list, so unlike the samples below every member is
+    // read, one read each as migrate already pays
+    let mut members = 0;
+    let mut weight = Uint128::zero();
+    for item in MEMBERS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, member) = item?;
+        members += 1;
+        weight += member.weight;
+    }
+    let total_weight = TOTAL_WEIGHT.load(deps.storage)?;
+    if weight != total_weight {
+        return Err(violated(format!(
+            "total weight {} but members sum to {}",
+            total_weight, weight
+        )));
+    }
+
+    // balances may run ahead of the ledgers, tokens can arrive without the DAO being told, but
+    // never behind them
+    let config = CONFIG.load(deps.storage)?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &config.denom)?
+        .amount;
+    let held = HELD_DEPOSITS.may_load(deps.storage)?.unwrap_or_default();
+    // in shadow mode reservations are against the simulated funds, not the balance
+    let committed = match config.shadow_funds {
+        Some(_) => Uint128::zero(),
+        None => COMMITTED.may_load(deps.storage)?.unwrap_or_default(),
+    };
+    if held + committed > balance {
+        return Err(violated(format!(
+            "{} held and {} committed but the balance is {}",
+            held, committed, balance
+        )));
+    }
+    let tokens = CW20_TREASURY
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(INVARIANT_SAMPLE)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (token, held) in &tokens {
+        let balance = cw20_balance(deps, token, &env.contract.address)?;
+        if *held > balance {
+            return Err(violated(format!(
+                "ledger holds {} of {} but the balance is {}",
+                held, token, balance
+            )));
+        }
+    }
+
+    let tallies = TALLIES
+        .range(deps.storage, None, None, Order::Descending)
+        .take(INVARIANT_SAMPLE)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, tally) in tallies.iter().filter(|(_, tally)| !tally.migrated) {
+        let mut counted = Tally::default();
+        for item in BALLOTS
+            .prefix(*id)
+            .range(deps.storage, None, None, Order::Ascending)
+        {
+            let (_, ballot) = item?;
+            if ballot.approve {
+                counted.votes_for += ballot.counted_weight();
+            } else {
+                counted.votes_against += ballot.counted_weight();
+            }
+        }
+        if (counted.votes_for, counted.votes_against) != (tally.votes_for, tally.votes_against) {
+            return Err(violated(format!(
+                "proposal {} tallies {}/{} but its ballots sum to {}/{}",
+                id, tally.votes_for, tally.votes_against, counted.votes_for, counted.votes_against
+            )));
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "assert_invariants")
+        .add_attribute("members", members.to_string())
+        .add_attribute("tokens", tokens.len().to_string())
+        .add_attribute("proposals", tallies.len().to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        NOTIFY_REPLY_ID => {
+            let err = ContractError::NotificationFailed {
+                reason: msg.result.into_result().err().unwrap_or_default(),
+            };
+            Ok(Response::new()
+                .add_attribute("notification", "failed")
+                .add_attribute("error_code", err.code().to_string())
+                .add_attribute("error", err.to_string()))
+        }
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetProposal { proposal_id } => {
+            to_json_binary(&query_get_proposal(deps, env, proposal_id)?)
+        }
+        QueryMsg::ListProposals {
+            start_after,
+            limit,
+            cursor,
+            order,
+        } => to_json_binary(&query_list_proposals(
+            deps,
+            env,
+            order.unwrap_or_default().into(),
+            start_after,
+            limit,
+            cursor,
+        )?),
+        QueryMsg::GetVote { proposal_id, voter } => {
+            to_json_binary(&query_get_vote(deps, proposal_id, voter)?)
+        }
+        QueryMsg::ListVotes {
+            proposal_id,
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_list_votes(
+            deps,
+            proposal_id,
+            start_after,
+            limit,
+            cursor,
+        )?),
+        QueryMsg::ExportBallots {
+            proposal_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query_export_ballots(
+            deps,
+            env,
+            proposal_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::GetMember { address } => to_json_binary(&query_get_member(deps, address)?),
+        QueryMsg::ListMembersByWeight {
+            descending,
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_list_members_by_weight(
+            deps,
+            descending,
+            start_after,
+            limit,
+            cursor,
+        )?),
+        QueryMsg::ListMembers {
+            start_after,
+            limit,
+            cursor,
+            order,
+        } => to_json_binary(&query_list_members(
+            deps,
+            order.unwrap_or_default().into(),
+            start_after,
+            limit,
+            cursor,
+        )?),
+        QueryMsg::GetSuspension { address } => {
+            to_json_binary(&query_get_suspension(deps, env, address)?)
+        }
+        QueryMsg::GetClaimWindow { address } => to_json_binary(
+            &CLAIM_WINDOWS
+                .may_load(deps.storage, &address)?
+                .map(|closes_at| query_deadline(deps, &env, closes_at))
+                .transpose()?,
+        ),
+        QueryMsg::GetKeyRotation { new_address } => {
+            to_json_binary(&query_get_key_rotation(deps, env, new_address)?)
+        }
+        QueryMsg::GetVesting { proposal_id } => {
+            to_json_binary(&query_get_vesting(deps, env, proposal_id)?)
+        }
+        QueryMsg::GetGrantStatus { proposal_id } => {
+            to_json_binary(&query_get_grant_status(deps, env, proposal_id)?)
+        }
+        QueryMsg::GetConfig {} => to_json_binary(&current_config(deps.storage, &env)?),
+        QueryMsg::GetPendingConfig {} => to_json_binary(&query_get_pending_config(deps, env)?),
+        QueryMsg::GetRewardDistribution { proposal_id } => {
+            to_json_binary(&REWARD_DISTRIBUTIONS.load(deps.storage, proposal_id)?)
+        }
+        QueryMsg::ParticipationSnapshot { selection } => {
+            to_json_binary(&query_participation_snapshot(deps, selection)?)
+        }
+        QueryMsg::ListAnnouncements {
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_list_announcements(deps, start_after, limit, cursor)?),
+        QueryMsg::CanVote {
+            proposal_id,
+            address,
+        } => to_json_binary(&query_can_vote(deps, env, proposal_id, address)?),
+        QueryMsg::CanPropose { address } => to_json_binary(&query_can_propose(deps, env, address)?),
+        QueryMsg::ListProposalsByStatus {
+            status,
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_list_proposals_by_status(
+            deps,
+            env,
+            status,
+            start_after,
+            limit,
+            cursor,
+        )?),
+        QueryMsg::ListProposalsByProposer {
+            proposer,
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_list_indexed_proposals(
+            deps,
+            env,
+            PROPOSALS.idx.proposer.prefix(proposer),
+            start_after,
+            limit,
+            cursor,
+        )?),
+        QueryMsg::ListProposalsByRecipient {
+            recipient,
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_list_indexed_proposals(
+            deps,
+            env,
+            PROPOSALS.idx.recipient.prefix(recipient),
+            start_after,
+            limit,
+            cursor,
+        )?),
+        QueryMsg::TreasuryStats {} => to_json_binary(&query_treasury_stats(deps, env)?),
+        QueryMsg::ListTreasuryBalances { start_after, limit } => to_json_binary(
+            &query_list_treasury_balances(deps, env, start_after, limit)?,
+        ),
+        QueryMsg::GetAnalytics { period } => to_json_binary(&query_analytics(deps, env, period)?),
+        QueryMsg::StorageStats {} => to_json_binary(&query_storage_stats(deps)?),
+        QueryMsg::VoteProof { proposal_id, voter } => {
+            to_json_binary(&query_vote_proof(deps, proposal_id, voter)?)
+        }
+        QueryMsg::Outbox {
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_outbox(deps, start_after, limit, cursor)?),
+        QueryMsg::ShadowLedger {
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_shadow_ledger(deps, start_after, limit, cursor)?),
+        QueryMsg::ListGroups {
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_list_groups(deps, start_after, limit, cursor)?),
+        QueryMsg::ListSecurityPayouts {
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_list_security_payouts(
+            deps,
+            env,
+            start_after,
+            limit,
+            cursor,
+        )?),
+        QueryMsg::Threshold {} => to_json_binary(&query_cw3_threshold(deps)?),
+        QueryMsg::Proposal { proposal_id } => {
+            let record = query_get_proposal(deps, env.clone(), proposal_id)?;
+            to_json_binary(&cw3_proposal(deps, &env, record)?)
+        }
+        QueryMsg::Cw3ListProposals { start_after, limit } => to_json_binary(&query_cw3_proposals(
+            deps,
+            env,
+            Order::Ascending,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ReverseProposals {
+            start_before,
+            limit,
+        } => to_json_binary(&query_cw3_proposals(
+            deps,
+            env,
+            Order::Descending,
+            start_before,
+            limit,
+        )?),
+        QueryMsg::Vote { proposal_id, voter } => {
+            to_json_binary(&query_cw3_vote(deps, proposal_id, voter)?)
+        }
+        QueryMsg::Cw3ListVotes {
+            proposal_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query_cw3_list_votes(
+            deps,
+            proposal_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Voter { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            let weight = voter_weight(deps, &env, &address)?;
+            to_json_binary(&cw3::VoterResponse { weight })
+        }
+        QueryMsg::ListVoters { start_after, limit } => {
+            let start_after = start_after
+                .map(|address| deps.api.addr_validate(&address))
+                .transpose()?;
+            let page = query_list_members(deps, Order::Ascending, start_after, limit, None)?;
+            let voters = page
+                .members
+                .into_iter()
+                .map(|member| {
+                    Ok(cw3::VoterDetail {
+                        weight: voter_weight(deps, &env, &member.address)?.unwrap_or_default(),
+                        addr: member.address.into_string(),
+                    })
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            to_json_binary(&cw3::VoterListResponse { voters })
+        }
+        QueryMsg::ListFrontends {
+            start_after,
+            limit,
+            cursor,
+        } => to_json_binary(&query_list_frontends(deps, start_after, limit, cursor)?),
+    }
+}
+
+fn query_can_vote(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+    address: Addr,
+) -> StdResult<CanVoteResponse> {
+    let err = ineligibility(ensure_can_vote(deps, &env, proposal_id, &address))?;
+    Ok(CanVoteResponse {
+        can_vote: err.is_none(),
+        error_code: err.as_ref().map(ContractError::code),
+        reason: err.map(|err| err.to_string()),
+    })
+}
+
+fn query_can_propose(deps: Deps, env: Env, address: Addr) -> StdResult<CanProposeResponse> {
+    let err = ineligibility(ensure_can_propose(deps.storage, &env, &address))?;
+    Ok(CanProposeResponse {
+        can_propose: err.is_none(),
+        error_code: err.as_ref().map(ContractError::code),
+        reason: err.map(|err| err.to_string()),
+    })
+}
+
+/// The error an eligibility check failed with, storage errors still fail the query
+fn ineligibility<T>(result: Result<T, ContractError>) -> StdResult<Option<ContractError>> {
+    match result {
+        Ok(_) => Ok(None),
+        Err(ContractError::Std(err)) => Err(err),
+        Err(err) => Ok(Some(err)),
+    }
+}
+
+fn query_get_proposal(deps: Deps, env: Env, proposal_id: u64) -> StdResult<ProposalRecord> {
+    query_visible_proposal(deps, env, proposal_id)?
+        .ok_or_else(|| StdError::generic_err("drafts are private until voting opens"))
+}
+
+// `None` for a draft kept private under `private_drafts`
+fn query_visible_proposal(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+) -> StdResult<Option<ProposalRecord>> {
+    match PROPOSALS.may_load(deps.storage, proposal_id)? {
+        Some(proposal) if draft_hidden(&CONFIG.load(deps.storage)?, &proposal) => Ok(None),
+        Some(proposal) => Ok(Some(ProposalRecord::Full(proposal_response(
+            deps, &env, proposal,
+        )?))),
+        None => Ok(Some(ProposalRecord::Archived(
+            ARCHIVE.load(deps.storage, proposal_id)?,
+        ))),
+    }
+}
+
+fn draft_hidden(config: &Config, proposal: &Proposal) -> bool {
+    config.private_drafts && proposal.status == ProposalStatus::Draft
+}
+
+// archived proposals keep their execution so snapshots over them still work
+fn execution_of(storage: &dyn Storage, proposal_id: u64) -> StdResult<Option<Execution>> {
+    match PROPOSALS.may_load(storage, proposal_id)? {
+        Some(proposal) => Ok(proposal.execution),
+        None => Ok(Some(ARCHIVE.load(storage, proposal_id)?.execution)),
+    }
+}
+
+fn proposal_response(deps: Deps, env: &Env, proposal: Proposal) -> StdResult<ProposalResponse> {
+    let tally = TALLIES
+        .may_load(deps.storage, proposal.id)?
+        .unwrap_or_default();
+    let config = CONFIG.load(deps.storage)?;
+    let total_weight = electorate(deps.storage, &tally)?;
+    let status = proposal_status(&config, &proposal, &tally, total_weight, &env.block);
+    let voting_end = effective_voting_end(&config, &tally, total_weight);
+    let time_remaining = voting_end.map(|end| remaining(&env.block, end));
+    Ok(ProposalResponse {
+        id: proposal.id,
+        title: proposal.title,
+        description: proposal.description,
+        recipient: proposal.recipient,
+        amount: proposal.amount,
+        votes_for: tally.votes_for,
+        votes_against: tally.votes_against,
+        executed: proposal.executed,
+        proposal_type: proposal.proposal_type,
+        execution: proposal.execution,
+        frontend_id: proposal.frontend_id,
+        parent_proposal_id: proposal.parent_proposal_id,
+        voting_end,
+        time_remaining,
+        status,
+        total_weight,
+        quorum_reached: quorum_met(&config, &tally, total_weight),
+        threshold_reached: threshold_met(&config, &tally),
+        deposit: proposal.deposit,
+        proposer: proposal.proposer,
+        submitted_at: proposal.submitted_at,
+        bounty: proposal.bounty,
+        created_at: proposal.created_at,
+        created_height: proposal.created_height,
+        funds_committed: tally.committed,
+        groups: tally.groups,
+    })
+}
+
+fn query_get_vote(deps: Deps, proposal_id: u64, voter: Addr) -> StdResult<Ballot> {
+    ensure_ballots_public(deps, proposal_id)?;
+    let ballot = BALLOTS.load(deps.storage, (proposal_id, &voter))?;
+    Ok(ballot)
+}
+
+fn query_list_votes(
+    deps: Deps,
+    proposal_id: u64,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ListVotesResponse> {
+    ensure_ballots_public(deps, proposal_id)?;
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = page_start(start_after.as_ref(), cursor);
+
+    let items = BALLOTS
+        .prefix(proposal_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let (votes, next_cursor) = paginate(items, limit);
+
+    Ok(ListVotesResponse { votes, next_cursor })
+}
+
+fn query_export_ballots(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<ExportBallotsResponse> {
+    ensure_ballots_public(deps, proposal_id)?;
+    // ballots can still change while open, and a tie may yet be broken
+    let status = query_get_proposal(deps, env, proposal_id)?.status();
+    if matches!(
+        status,
+        ProposalStatus::Open | ProposalStatus::Tied | ProposalStatus::Draft
+    ) {
+        return Err(StdError::generic_err(format!(
+            "voting on proposal {} has not closed",
+            proposal_id
+        )));
+    }
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let ballots = BALLOTS
+        .prefix(proposal_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(_, ballot)| ExportedBallot {
+                counted: ballot.counted_weight(),
+                voter: ballot.voter,
+                approve: ballot.approve,
+                weight: ballot.weight,
+                height: ballot.height,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(ExportBallotsResponse {
+        proposal_id,
+        status,
+        ballots,
+    })
+}
+
+fn ensure_ballots_public(deps: Deps, proposal_id: u64) -> StdResult<()> {
+    if CONFIG.load(deps.storage)?.private_ballots
+        && !DISCLOSED_BALLOTS.has(deps.storage, proposal_id)
+    {
+        return Err(StdError::generic_err("ballots are private"));
+    }
+    Ok(())
+}
+
+fn query_vote_proof(deps: Deps, proposal_id: u64, voter: Addr) -> StdResult<VoteProofResponse> {
+    let raw = |key: Vec<u8>| RawEntry {
+        value: deps.storage.get(&key).map(Binary::from),
+        key: Binary::from(key),
+    };
+    let mut ballot = raw(ballot_key(proposal_id, &voter));
+    if CONFIG.load(deps.storage)?.private_ballots
+        && !DISCLOSED_BALLOTS.has(deps.storage, proposal_id)
+    {
+        ballot.value = None;
+    }
+    Ok(VoteProofResponse {
+        member: raw(member_key(&voter)),
+        total_weight: raw(total_weight_key()),
+        ballot,
+    })
+}
+
+/// Proposals executed in `[start, end)` by id, reading at most one more entry than a snapshot
+/// may cover so an oversized period is caught without reading all of it
+fn executed_between(
+    storage: &dyn Storage,
+    start: Timestamp,
+    end: Timestamp,
+) -> StdResult<Vec<u64>> {
+    let mut ids = EXECUTED_AT
+        .keys(
+            storage,
+            Some(Bound::inclusive((start.nanos(), 0))),
+            Some(Bound::exclusive((end.nanos(), 0))),
+            Order::Ascending,
+        )
+        .take(MAX_SNAPSHOT_PROPOSALS + 1)
+        .map(|key| key.map(|(_, id)| id))
+        .collect::<StdResult<Vec<_>>>()?;
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+fn query_participation_snapshot(
+    deps: Deps,
+    selection: ProposalSelection,
+) -> StdResult<ParticipationSnapshotResponse> {
+    let proposal_ids = match selection {
+        ProposalSelection::Ids { proposal_ids } => {
+            for id in &proposal_ids {
+                if execution_of(deps.storage, *id)?.is_none() {
+                    return Err(StdError::generic_err(format!(
+                        "proposal {} is not executed",
+                        id
+                    )));
+                }
+            }
+            proposal_ids
+        }
+        ProposalSelection::ExecutedBetween { start, end } => {
+            executed_between(deps.storage, start, end)?
+        }
+    };
+
+    if proposal_ids.len() > MAX_SNAPSHOT_PROPOSALS {
+        return Err(StdError::generic_err(format!(
+            "snapshot covers more than {} proposals, narrow the selection",
+            MAX_SNAPSHOT_PROPOSALS
+        )));
+    }
+
+    let mut participants: BTreeMap<Addr, Participation> = BTreeMap::new();
+    for id in &proposal_ids {
+        for item in BALLOTS
+            .prefix(*id)
+            .range(deps.storage, None, None, Order::Ascending)
+        {
+            let (voter, ballot) = item?;
+            // what delegators lent is their participation, ballots from before it was
+            // recorded credit it all to the voter
+            let credited = if ballot.lent.len() == ballot.delegators.len() {
+                ballot.delegators.into_iter().zip(ballot.lent).collect()
+            } else {
+                vec![]
+            };
+            let own = credited
+                .iter()
+                .fold(ballot.weight, |own, (_, lent)| own.saturating_sub(*lent));
+            for (address, weight) in std::iter::once((voter, own)).chain(credited) {
+                let participation =
+                    participants
+                        .entry(address.clone())
+                        .or_insert_with(|| Participation {
+                            address,
+                            proposals_voted: 0,
+                            weight: Uint128::zero(),
+                        });
+                participation.proposals_voted += 1;
+                participation.weight += weight;
+            }
+        }
+    }
+
+    Ok(ParticipationSnapshotResponse {
+        proposal_ids,
+        participants: participants.into_values().collect(),
+    })
+}
+
+fn query_get_vesting(deps: Deps, env: Env, proposal_id: u64) -> StdResult<VestingResponse> {
+    let vesting = VESTINGS.load(deps.storage, proposal_id)?;
+    let vested = vested_amount(&vesting, &env)?;
+    Ok(VestingResponse {
+        claimable: vested - vesting.claimed,
+        vested,
+        fully_vested: query_deadline(deps, &env, vesting.end)?,
+        vesting,
+    })
+}
+
+fn query_get_grant_status(
+    deps: Deps,
+    env: Env,
+    proposal_id: u64,
+) -> StdResult<GrantStatusResponse> {
+    let proposal = query_get_proposal(deps, env.clone(), proposal_id)?;
+    // archived records no longer carry a type but only executed proposals are archived
+    let (amount, executed) = match &proposal {
+        ProposalRecord::Full(proposal) => match proposal.proposal_type {
+            ProposalType::Grant
+            | ProposalType::GrantBatch { .. }
+            | ProposalType::VestedGrant { .. } => (proposal.amount, proposal.execution.is_some()),
+            _ => {
+                return Err(StdError::generic_err(format!(
+                    "proposal {} is not a grant",
+                    proposal_id
+                )))
+            }
+        },
+        ProposalRecord::Archived(archived) => (archived.amount, true),
+    };
+
+    let vesting = if VESTINGS.has(deps.storage, proposal_id) {
+        Some(query_get_vesting(deps, env, proposal_id)?)
+    } else {
+        None
+    };
+    let (paid, remaining) = match &vesting {
+        Some(response) => {
+            let vesting = &response.vesting;
+            (
+                vesting.upfront + vesting.claimed,
+                vesting.vesting_amount - vesting.claimed,
+            )
+        }
+        None if executed => (amount, Uint128::zero()),
+        None => (Uint128::zero(), amount),
+    };
+
+    Ok(GrantStatusResponse {
+        proposal,
+        vesting,
+        paid,
+        remaining,
+    })
+}
+
+fn query_storage_stats(deps: Deps) -> StdResult<StorageStatsResponse> {
+    let usage = |category| -> StdResult<StorageUsage> {
+        Ok(STORAGE_USAGE
+            .may_load(deps.storage, category)?
+            .unwrap_or_default())
+    };
+    Ok(StorageStatsResponse {
+        proposals: usage(USAGE_PROPOSALS)?,
+        ballots: usage(USAGE_BALLOTS)?,
+        members: usage(USAGE_MEMBERS)?,
+        archive: usage(USAGE_ARCHIVE)?,
+    })
+}
+
+fn query_get_suspension(
+    deps: Deps,
+    env: Env,
+    address: Addr,
+) -> StdResult<Option<SuspensionResponse>> {
+    SUSPENSIONS
+        .may_load(deps.storage, &address)?
+        .map(|suspension| {
+            Ok(SuspensionResponse {
+                expires: query_deadline(deps, &env, suspension.expires_at)?,
+                suspension,
+            })
+        })
+        .transpose()
+}
+
+fn query_get_key_rotation(
+    deps: Deps,
+    env: Env,
+    new_address: Addr,
+) -> StdResult<Option<KeyRotationResponse>> {
+    KEY_ROTATIONS
+        .may_load(deps.storage, &new_address)?
+        .map(|rotation| {
+            Ok(KeyRotationResponse {
+                executable: query_deadline(deps, &env, rotation.executable_at)?,
+                rotation,
+            })
+        })
+        .transpose()
+}
+
+fn query_get_pending_config(deps: Deps, env: Env) -> StdResult<Option<PendingConfigResponse>> {
+    PENDING_CONFIG
+        .may_load(deps.storage)?
+        .map(|pending| {
+            Ok(PendingConfigResponse {
+                effective: query_deadline(deps, &env, pending.effective_at)?,
+                pending,
+            })
+        })
+        .transpose()
+}
+
+fn query_deadline(deps: Deps, env: &Env, at: Scheduled) -> StdResult<Deadline> {
+    let expected_block_time = CONFIG.load(deps.storage)?.expected_block_time;
+    Ok(deadline(&env.block, at, expected_block_time))
+}
+
+fn query_get_member(deps: Deps, address: Addr) -> StdResult<MemberResponse> {
+    let member = MEMBERS.load(deps.storage, &address)?;
+    member_response(deps, member)
+}
+
+fn member_response(deps: Deps, member: Member) -> StdResult<MemberResponse> {
+    let profile = PROFILES.may_load(deps.storage, &member.address)?;
+    let group = MEMBER_GROUPS.may_load(deps.storage, &member.address)?;
+    let vote_operator = VOTE_OPERATORS.may_load(deps.storage, &member.address)?;
+    let delegate = DELEGATIONS.may_load(deps.storage, &member.address)?;
+    let delegators = delegators(deps.storage, &member.address)?;
+    Ok(MemberResponse {
+        address: member.address,
+        weight: member.weight,
+        profile,
+        group,
+        vote_operator,
+        delegate,
+        delegators,
+    })
+}
+
+fn query_list_proposals(
+    deps: Deps,
+    env: Env,
+    order: Order,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ListProposalsResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let (min, max) = page_range(order, page_start(start_after, cursor));
+
+    // ids are unique across both maps, so the first `limit + 1` of each merged cover the page
+    let config = CONFIG.load(deps.storage)?;
+    let mut items = PROPOSALS
+        .range(deps.storage, min.clone(), max.clone(), order)
+        .filter(|item| !matches!(item, Ok((_, proposal)) if draft_hidden(&config, proposal)))
+        .take(limit + 1)
+        .map(|item| {
+            let (id, proposal) = item?;
+            Ok((
+                id,
+                ProposalRecord::Full(proposal_response(deps, &env, proposal)?),
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for item in ARCHIVE.range(deps.storage, min, max, order).take(limit + 1) {
+        let (id, archived) = item?;
+        items.push((id, ProposalRecord::Archived(archived)));
+    }
+    items.sort_by_key(|(id, _)| *id);
+    if order == Order::Descending {
+        items.reverse();
+    }
+    items.truncate(limit + 1);
+    let (proposals, next_cursor) = paginate(items, limit);
+
+    Ok(ListProposalsResponse {
+        proposals,
+        next_cursor,
+    })
+}
+
+// proposals whose derived status is `status`: those stored with it, and undecided ones still
+// stored as open that have come to it since
+fn query_list_proposals_by_status(
+    deps: Deps,
+    env: Env,
+    status: ProposalStatus,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ListProposalsResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = page_start(start_after, cursor);
+
+    let mut stored = vec![status];
+    if status != ProposalStatus::Open {
+        stored.push(ProposalStatus::Open);
+    }
+    let mut candidates = vec![];
+    for stored in stored {
+        for id in PROPOSALS
+            .idx
+            .status
+            .prefix(stored.as_str())
+            .keys(deps.storage, start.clone(), None, Order::Ascending)
+            .take(limit + 1)
+        {
+            candidates.push(id?);
+        }
+    }
+    candidates.sort_unstable();
+
+    // a page examines at most `limit` proposals whatever their current status, so it may come
+    // back short of `limit` with a cursor to carry on from
+    let next_cursor = if candidates.len() > limit {
+        candidates.truncate(limit);
+        candidates.last().map(|id| Binary::from(id.joined_key()))
+    } else {
+        None
+    };
+    let mut proposals = vec![];
+    for id in candidates {
+        match query_visible_proposal(deps, env.clone(), id)? {
+            Some(record) if record.status() == status => proposals.push(record),
+            _ => {}
+        }
+    }
+
+    Ok(ListProposalsResponse {
+        proposals,
+        next_cursor,
+    })
+}
+
+fn query_list_indexed_proposals(
+    deps: Deps,
+    env: Env,
+    index: Prefix<u64, Empty, u64>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ListProposalsResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = page_start(start_after, cursor);
+
+    let items = index
+        .keys(deps.storage, start, None, Order::Ascending)
+        .filter_map(|id| {
+            let visible = id.and_then(|id| {
+                Ok(query_visible_proposal(deps, env.clone(), id)?.map(|record| (id, record)))
+            });
+            visible.transpose()
+        })
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let (proposals, next_cursor) = paginate(items, limit);
+
+    Ok(ListProposalsResponse {
+        proposals,
+        next_cursor,
+    })
+}
+
+// cw3 counts weight in u64
+fn cw3_weight(weight: Uint128) -> StdResult<u64> {
+    Ok(Uint64::try_from(weight)?.u64())
+}
+
+// the configured rules, or those a proposal's tally fixed when it opened
+fn cw3_threshold(
+    config: &Config,
+    tally: &Tally,
+    total_weight: Uint128,
+) -> StdResult<cw3::ThresholdResponse> {
+    Ok(cw3::ThresholdResponse::ThresholdQuorum {
+        threshold: threshold_of(config, tally),
+        quorum: quorum_of(config, tally),
+        total_weight: cw3_weight(total_weight)?,
+    })
+}
+
+fn query_cw3_threshold(deps: Deps) -> StdResult<cw3::ThresholdResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let total_weight = config.membership.backend().total_weight(deps)?;
+    cw3_threshold(&config, &Tally::default(), total_weight)
+}
+
+// every way a proposal can fail reads as rejected, and drafts as pending
+fn cw3_status(status: ProposalStatus) -> cw3::Status {
+    match status {
+        ProposalStatus::Draft => cw3::Status::Pending,
+        ProposalStatus::Open => cw3::Status::Open,
+        ProposalStatus::Passed => cw3::Status::Passed,
+        ProposalStatus::Executed => cw3::Status::Executed,
+        ProposalStatus::Rejected
+        | ProposalStatus::Expired
+        | ProposalStatus::Tied
+        | ProposalStatus::Lapsed
+        | ProposalStatus::Vetoed
+        | ProposalStatus::Cancelled => cw3::Status::Rejected,
+    }
+}
+
+// proposals opened by the contract itself, such as mirrored ones, have no proposer of their own
+fn cw3_proposal(deps: Deps, env: &Env, record: ProposalRecord) -> StdResult<cw3::ProposalResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let proposal = match record {
+        ProposalRecord::Full(proposal) => proposal,
+        ProposalRecord::Archived(archived) => {
+            let total_weight = config.membership.backend().total_weight(deps)?;
+            return Ok(cw3::ProposalResponse {
+                id: archived.id,
+                title: archived.title_hash.to_hex(),
+                description: String::new(),
+                msgs: vec![],
+                status: cw3::Status::Executed,
+                expires: Expiration::Never {},
+                threshold: cw3_threshold(&config, &Tally::default(), total_weight)?,
+                proposer: env.contract.address.clone(),
+                deposit: None,
+            });
+        }
+    };
+    let tally = TALLIES
+        .may_load(deps.storage, proposal.id)?
+        .unwrap_or_default();
+    let msgs = match proposal.proposal_type {
+        ProposalType::Grant | ProposalType::Expense { .. } => {
+            vec![grant_transfer(&proposal.recipient, proposal.amount, &config.denom).into()]
+        }
+        ProposalType::GrantBatch { payouts } => payouts
+            .iter()
+            .map(|payout| grant_transfer(&payout.recipient, payout.amount, &config.denom).into())
+            .collect(),
+        ProposalType::Custom { msgs } => msgs,
+        ProposalType::Swap {
+            offer,
+            ask_denom,
+            min_receive,
+        } => match &config.dex_router {
+            Some(router) => vec![swap_msg(
+                router,
+                offer,
+                &config.denom,
+                &ask_denom,
+                min_receive,
+            )?],
+            None => vec![],
+        },
+        _ => vec![],
+    };
+    let expires = match proposal.voting_end {
+        Some(Scheduled::AtHeight(height)) => Expiration::AtHeight(height),
+        Some(Scheduled::AtTime(time)) => Expiration::AtTime(time),
+        None => Expiration::Never {},
+    };
+    // deposits are forfeited when a proposal fails
+    let deposit = proposal.deposit.map(|deposit| cw3::DepositInfo {
+        amount: deposit.amount,
+        denom: cw3::Denom::Native(config.denom.clone()),
+        refund_failed_proposals: false,
+    });
+
+    Ok(cw3::ProposalResponse {
+        id: proposal.id,
+        title: proposal.title.into_inner(),
+        description: proposal.description.into_inner(),
+        msgs,
+        status: cw3_status(proposal.status),
+        expires,
+        threshold: cw3_threshold(&config, &tally, proposal.total_weight)?,
+        proposer: proposal
+            .proposer
+            .unwrap_or_else(|| env.contract.address.clone()),
+        deposit,
+    })
+}
+
+fn query_cw3_vote(deps: Deps, proposal_id: u64, voter: String) -> StdResult<cw3::VoteResponse> {
+    ensure_ballots_public(deps, proposal_id)?;
+    let voter = deps.api.addr_validate(&voter)?;
+    let vote = BALLOTS
+        .may_load(deps.storage, (proposal_id, &voter))?
+        .map(|ballot| cw3_vote_info(proposal_id, ballot))
+        .transpose()?;
+    Ok(cw3::VoteResponse { vote })
+}
+
+fn query_cw3_list_votes(
+    deps: Deps,
+    proposal_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<cw3::VoteListResponse> {
+    let start_after = start_after
+        .map(|voter| deps.api.addr_validate(&voter))
+        .transpose()?;
+    let votes = query_list_votes(deps, proposal_id, start_after, limit, None)?
+        .votes
+        .into_iter()
+        .map(|ballot| cw3_vote_info(proposal_id, ballot))
+        .collect::<StdResult<_>>()?;
+    Ok(cw3::VoteListResponse { votes })
+}
+
+fn cw3_vote_info(proposal_id: u64, ballot: Ballot) -> StdResult<cw3::VoteInfo> {
+    Ok(cw3::VoteInfo {
+        proposal_id,
+        voter: ballot.voter.into_string(),
+        vote: if ballot.approve {
+            cw3::Vote::Yes
+        } else {
+            cw3::Vote::No
+        },
+        weight: cw3_weight(ballot.weight)?,
+    })
+}
+
+fn query_cw3_proposals(
+    deps: Deps,
+    env: Env,
+    order: Order,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<cw3::ProposalListResponse> {
+    let page = query_list_proposals(deps, env.clone(), order, start_after, limit, None)?;
+    let proposals = page
+        .proposals
+        .into_iter()
+        .map(|record| cw3_proposal(deps, &env, record))
+        .collect::<StdResult<_>>()?;
+    Ok(cw3::ProposalListResponse { proposals })
+}
+
+fn voter_weight(deps: Deps, env: &Env, address: &Addr) -> StdResult<Option<u64>> {
+    let backend = CONFIG.load(deps.storage)?.membership.backend();
+    backend
+        .weight_at(deps, address, env.block.height)?
+        .map(cw3_weight)
+        .transpose()
+}
+
+fn query_list_members(
+    deps: Deps,
+    order: Order,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ListMembersResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let (min, max) = page_range(order, page_start(start_after.as_ref(), cursor));
+
+    let items = MEMBERS
+        .range(deps.storage, min, max, order)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let (members, next_cursor) = paginate(items, limit);
+    let members = members
+        .into_iter()
+        .map(|member| member_response(deps, member))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ListMembersResponse {
+        members,
+        next_cursor,
+    })
+}
+
+fn query_list_members_by_weight(
+    deps: Deps,
+    descending: bool,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ListMembersResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start_after = match &start_after {
+        Some(address) => Some((MEMBERS.load(deps.storage, address)?.weight.u128(), address)),
+        None => None,
+    };
+    let order = if descending {
+        Order::Descending
+    } else {
+        Order::Ascending
+    };
+    let (min, max) = page_range(order, page_start(start_after, cursor));
+
+    let items = MEMBERS_BY_WEIGHT
+        .keys(deps.storage, min, max, order)
+        .take(limit + 1)
+        .map(|key| key.map(|key| (key.clone(), key.1)))
+        .collect::<StdResult<Vec<_>>>()?;
+    let (addresses, next_cursor) = paginate(items, limit);
+    let members = addresses
+        .into_iter()
+        .map(|address| member_response(deps, MEMBERS.load(deps.storage, &address)?))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ListMembersResponse {
+        members,
+        next_cursor,
+    })
+}
+
+fn query_list_announcements(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ListAnnouncementsResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = page_start(start_after, cursor);
+
+    let items = ANNOUNCEMENTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let (announcements, next_cursor) = paginate(items, limit);
+
+    Ok(ListAnnouncementsResponse {
+        announcements,
+        next_cursor,
+    })
+}
+
+fn query_treasury_stats(deps: Deps, env: Env) -> StdResult<TreasuryStatsResponse> {
+    let denom = CONFIG.load(deps.storage)?.denom;
+    let balance = deps
+        .querier
+        .query_balance(env.contract.address.clone(), &denom)?
+        .amount;
+    Ok(TreasuryStatsResponse {
+        balance,
+        held: HELD_DEPOSITS.may_load(deps.storage)?.unwrap_or_default(),
+        committed: COMMITTED.may_load(deps.storage)?.unwrap_or_default(),
+        available: available_funds(deps, &env, &denom)?,
+        granted: GRANTED.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+fn query_list_treasury_balances(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<TreasuryBalancesResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let cw20 = CW20_TREASURY
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(token, amount)| Cw20Balance { token, amount }))
+        .collect::<StdResult<_>>()?;
+
+    Ok(TreasuryBalancesResponse {
+        native: deps.querier.query_all_balances(env.contract.address)?,
+        cw20,
+    })
+}
+
+fn query_analytics(deps: Deps, env: Env, period: Option<u64>) -> StdResult<AnalyticsResponse> {
+    let period = period.unwrap_or(env.block.time.seconds() / ANALYTICS_PERIOD);
+    let mut stats = ANALYTICS
+        .may_load(deps.storage, period)?
+        .unwrap_or_default();
+
+    let decided = stats.passed + stats.rejected;
+    let average_participation = if decided == 0 {
+        None
+    } else {
+        Some(stats.participation / Decimal::from_ratio(decided, 1_u32))
+    };
+    stats.execution_times.sort_unstable();
+    let count = stats.execution_times.len();
+    let median_time_to_execution = if count == 0 {
+        None
+    } else if count % 2 == 1 {
+        Some(stats.execution_times[count / 2])
+    } else {
+        Some((stats.execution_times[count / 2 - 1] + stats.execution_times[count / 2]) / 2)
+    };
+
+    Ok(AnalyticsResponse {
+        period,
+        start: Timestamp::from_seconds(period * ANALYTICS_PERIOD),
+        end: Timestamp::from_seconds((period + 1) * ANALYTICS_PERIOD),
+        proposals_created: stats.created,
+        proposals_passed: stats.passed,
+        proposals_rejected: stats.rejected,
+        funds_granted: stats.granted,
+        average_participation,
+        median_time_to_execution,
+    })
+}
+
+fn query_outbox(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<OutboxResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = page_start(start_after, cursor);
+
+    let items = OUTBOX
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let (entries, next_cursor) = paginate(items, limit);
+
+    Ok(OutboxResponse {
+        entries,
+        next_cursor,
+    })
+}
+
+fn query_shadow_ledger(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ShadowLedgerResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = page_start(start_after.as_ref(), cursor);
+
+    let items = SHADOW_LEDGER
+        .range(deps.storage, start, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(recipient, amount)| (recipient.clone(), ShadowPayout { recipient, amount }))
+        })
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let (payouts, next_cursor) = paginate(items, limit);
+
+    let spent = SHADOW_SPENT.may_load(deps.storage)?.unwrap_or_default();
+    Ok(ShadowLedgerResponse {
+        shadow_funds_left: CONFIG
+            .load(deps.storage)?
+            .shadow_funds
+            .map(|funds| funds.saturating_sub(spent)),
+        payouts,
+        next_cursor,
+    })
+}
+
+fn query_list_groups(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ListGroupsResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = page_start(start_after.as_deref(), cursor);
+
+    let items = GROUPS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let (groups, next_cursor) = paginate(items, limit);
+
+    Ok(ListGroupsResponse {
+        groups,
+        next_cursor,
+    })
+}
+
+fn query_list_security_payouts(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ListSecurityPayoutsResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = page_start(start_after, cursor);
+
+    let items = SECURITY_PAYOUTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .map(|item| item.map(|(id, payout)| (id, (id, payout))))
+        .collect::<StdResult<Vec<_>>>()?;
+    let (payouts, next_cursor) = paginate(items, limit);
+    let payouts = payouts
+        .into_iter()
+        .map(|(id, payout)| {
+            let disclosed = payout
+                .disclose_at
+                .is_some_and(|at| at.is_triggered(&env.block));
+            Ok(SecurityPayoutResponse {
+                id,
+                paid: payout.paid_at.is_some(),
+                disclosure: payout
+                    .disclose_at
+                    .map(|at| query_deadline(deps, &env, at))
+                    .transpose()?,
+                payout: disclosed.then_some(payout),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let budget = CONFIG
+        .load(deps.storage)?
+        .security_lane
+        .map_or(Uint128::zero(), |lane| lane.budget);
+    let spent = SECURITY_SPENT.may_load(deps.storage)?.unwrap_or_default();
+    Ok(ListSecurityPayoutsResponse {
+        payouts,
+        remaining_budget: budget.saturating_sub(spent),
+        next_cursor,
+    })
+}
+
+fn query_list_frontends(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    cursor: Option<Binary>,
+) -> StdResult<ListFrontendsResponse> {
+    let limit = limit.unwrap_or(MAX_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = page_start(start_after.as_deref(), cursor);
+
+    let items = FRONTENDS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let (frontends, next_cursor) = paginate(items, limit);
+    let frontends = frontends
+        .into_iter()
+        .map(|frontend| {
+            let fees_paid = REFERRAL_FEES
+                .may_load(deps.storage, frontend.id.as_str())?
+                .unwrap_or_default();
+            Ok(FrontendResponse {
+                id: frontend.id,
+                operator: frontend.operator,
+                fees_paid,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(ListFrontendsResponse {
+        frontends,
+        next_cursor,
+    })
+}
+
+// a cursor is the raw storage key of the last item returned, so it works the same
+// for simple and composite keys
+fn page_start<'a, K: PrimaryKey<'a>>(
+    start_after: Option<K>,
+    cursor: Option<Binary>,
+) -> Option<Bound<'a, K>> {
+    match cursor {
+        Some(cursor) => Some(Bound::ExclusiveRaw(cursor.into())),
+        None => start_after.map(Bound::exclusive),
+    }
+}
+
+// a page continues from below its start when listed in reverse
+fn page_range<B>(order: Order, start: Option<B>) -> (Option<B>, Option<B>) {
+    match order {
+        Order::Ascending => (start, None),
+        Order::Descending => (None, start),
+    }
+}
+
+// expects up to `limit + 1` items, the extra one only signals that another page exists
+fn paginate<'a, K: PrimaryKey<'a>, T>(
+    mut items: Vec<(K, T)>,
+    limit: usize,
+) -> (Vec<T>, Option<Binary>) {
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        items.last().map(|(key, _)| Binary::from(key.joined_key()))
+    } else {
+        None
+    };
+
+    (
+        items.into_iter().map(|(_, item)| item).collect(),
+        next_cursor,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ClockMode;
+    use crate::cw3::{self, Cw3ExecuteMsg};
+    use crate::querier::GrantDaoQuerier;
+    use crate::state::{AddrIndex, GroupQuorum, Member, ProposalCategory, QuorumDecay};
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
+    };
+    use cosmwasm_std::{
+        coin, coins, from_json, Addr, Attribute, ContractResult, Empty, Event, QuerierWrapper,
+        Record, StdError, Storage, SystemError, SystemResult, Uint128, WasmQuery,
+    };
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use std::cell::Cell;
+
+    fn instantiate_msg(members: Vec<Member>) -> InstantiateMsg {
+        InstantiateMsg {
+            members,
+            denom: None,
+            notifier: None,
+            receipt_salt: None,
+            admin: None,
+            key_rotation_delay: None,
+            clock: None,
+            expected_block_time: None,
+            archive_after: None,
+            referral_fee: None,
+            parent_dao: None,
+            voting_period: None,
+            quorum: None,
+            threshold: None,
+            expense_threshold: None,
+            proposal_deposit: None,
+            burn_forfeited_deposits: None,
+            tie_break: None,
+            groups: None,
+            group_quorums: None,
+            shadow_funds: None,
+            execution_delay: None,
+            execution_expiration: None,
+            private_ballots: None,
+            guardian: None,
+            quorum_decay: None,
+            max_proposal_amount: None,
+            lock_self_voted_vesting: None,
+            voting_strategy: None,
+            membership: None,
+            removal_grace_period: None,
+            private_drafts: None,
+            security_lane: None,
+            dex_router: None,
+            cw20_tokens: None,
+        }
+    }
+
+    /// Counts the storage traffic of whatever runs against it
+    struct CountingStorage<'a> {
+        inner: &'a mut dyn Storage,
+        reads: Cell<u32>,
+        writes: u32,
+        bytes_written: usize,
+    }
+
+    impl<'a> CountingStorage<'a> {
+        fn new(inner: &'a mut dyn Storage) -> Self {
+            CountingStorage {
+                inner,
+                reads: Cell::new(0),
+                writes: 0,
+                bytes_written: 0,
+            }
+        }
+    }
+
+    impl Storage for CountingStorage<'_> {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.reads.set(self.reads.get() + 1);
+            self.inner.get(key)
+        }
+
+        fn range<'b>(
+            &'b self,
+            start: Option<&[u8]>,
+            end: Option<&[u8]>,
+            order: Order,
+        ) -> Box<dyn Iterator<Item = Record> + 'b> {
+            self.inner.range(start, end, order)
+        }
+
+        fn set(&mut self, key: &[u8], value: &[u8]) {
+            self.writes += 1;
+            self.bytes_written += key.len() + value.len();
+            self.inner.set(key, value)
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.writes += 1;
+            self.inner.remove(key)
+        }
+    }
+
+    fn dao_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(execute, instantiate, query).with_reply(reply);
+        Box::new(contract)
+    }
+
+    fn failing_notifier_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            |_, _, _, _: NotifierMsg| -> StdResult<Response> {
+                Err(StdError::generic_err("notifier down"))
+            },
+            |_, _, _, _: Empty| -> StdResult<Response> { Ok(Response::new()) },
+            |_, _, _: Empty| -> StdResult<Binary> { Ok(Binary::default()) },
+        );
+        Box::new(contract)
+    }
+
+    #[cosmwasm_schema::cw_serde]
+    enum GroupQueryMsg {
+        Member {
+            addr: String,
+            at_height: Option<u64>,
+        },
+        TotalWeight {
+            at_height: Option<u64>,
+        },
+    }
+
+    // both of a cw4 group's weight responses, only members may have none
+    #[cosmwasm_schema::cw_serde]
+    struct GroupWeight {
+        weight: Option<u64>,
+    }
+
+    // a cw4 group where only "grouped" has weight, 7 of 10
+    fn group_contract() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            |_, _, _, _: Empty| -> StdResult<Response> { Ok(Response::new()) },
+            |_, _, _, _: Empty| -> StdResult<Response> { Ok(Response::new()) },
+            |_, _, msg: GroupQueryMsg| -> StdResult<Binary> {
+                match msg {
+                    GroupQueryMsg::Member { addr, at_height } => {
+                        assert!(at_height.is_some());
+                        let weight = (addr == "grouped").then_some(7_u64);
+                        to_json_binary(&GroupWeight { weight })
+                    }
+                    GroupQueryMsg::TotalWeight { .. } => {
+                        to_json_binary(&GroupWeight { weight: Some(10) })
+                    }
+                }
+            },
+        );
+        Box::new(contract)
+    }
+
+    #[test]
+    fn proper_instantiation() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(10_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(20_u128),
+            },
+        ];
+        let msg = instantiate_msg(members);
+        let info = mock_info("creator", &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+    }
+
+    #[test]
+    fn instantiation_rejects_bad_members() {
+        let member = |address: &str, weight: u128| Member {
+            address: Addr::unchecked(address),
+            weight: Uint128::from(weight),
+        };
+        let instantiate_with = |members| {
+            let mut deps = mock_dependencies();
+            let info = mock_info("creator", &[]);
+            instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap_err()
+        };
+
+        let err = instantiate_with(vec![]);
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+        let err = instantiate_with(vec![member("addr1", 10), member("addr1", 20)]);
+        assert!(matches!(
+            err,
+            ContractError::InvalidMemberEntry { reason, .. } if reason == "listed more than once"
+        ));
+        let err = instantiate_with(vec![member("addr1", 10), member("addr2", 0)]);
+        assert!(matches!(
+            err,
+            ContractError::InvalidMemberEntry { address, .. } if address == "addr2"
+        ));
+    }
+
+    #[test]
+    fn proposal_creation() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let mut msg = instantiate_msg(members);
+        msg.max_proposal_amount = Some(Uint128::from(100_u128));
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Propose
+        let info = mock_info("addr1", &[]);
+        let propose = |description: &str, recipient: &str, amount: u128| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Test Proposal".parse().unwrap(),
+            description: description.parse().unwrap(),
+            amount: Some(Uint128::from(amount)),
+            recipient: Some(recipient.to_string()),
+            proposal_type: None,
+        };
+        let msg = propose("Description for test", "recipient_address", 100);
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let msg = propose(" ", "recipient_address", 100);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::EmptyDescription {}));
+        let msg = propose("Description for test", "Recipient_Address", 100);
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+        let msg = propose("Description for test", "recipient_address", 101);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::AmountAboveCap { .. }));
+        assert_eq!(21, err.code());
+
+        let proposal = proposal_response(
+            deps.as_ref(),
+            &mock_env(),
+            PROPOSALS.load(&deps.storage, 0).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Some(Addr::unchecked("addr1")), proposal.proposer);
+        assert_eq!(Some(mock_env().block.time), proposal.created_at);
+        assert_eq!(Some(mock_env().block.height), proposal.created_height);
+    }
+
+    #[test]
+    fn vote_for_proposal() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let msg = instantiate_msg(members);
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Propose
+        let info = mock_info("addr1", &[]);
+        let proposal_msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: Some(Uint128::from(100_u128)),
+            recipient: Some("recipient_address".to_string()),
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), proposal_msg).unwrap();
+
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), vote_msg.clone()).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let ballot = query_get_vote(deps.as_ref(), 0, Addr::unchecked("addr1")).unwrap();
+        assert!(ballot.approve);
+        assert_eq!(Uint128::from(10_u128), ballot.weight);
+        assert_eq!(mock_env().block.height, ballot.height);
+        assert_eq!(mock_env().block.time, ballot.time);
+        assert_eq!(
+            ballot_receipt("", &Addr::unchecked("addr1"), 0, true, ballot.height),
+            ballot.receipt
+        );
+        assert_eq!(res.attributes, vec![("receipt", ballot.receipt.to_hex())]);
+
+        // Voting again should fail
+        execute(deps.as_mut(), mock_env(), info, vote_msg).unwrap_err();
+    }
+
+    #[test]
+    fn execute_proposal() {
+        let sender = Addr::unchecked("sender");
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &sender, coins(100_000_000_000, DEFAULT_DENOM))
+                .unwrap();
+        });
+
+        let contract_id = app.store_code(dao_contract());
+
+        let members = vec![Member {
+            address: sender.clone(),
+            weight: Uint128::from(10_u128),
+        }];
+
+        let contract_addr = app
+            .instantiate_contract(
+                contract_id,
+                sender.clone(),
+                &instantiate_msg(members),
+                &[],
+                "grant-dao",
+                None,
+            )
+            .unwrap();
+
+        // Propose
+        let proposal_msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: Some(Uint128::from(100_u128)),
+            recipient: Some("recipient_address".to_string()),
+            proposal_type: None,
+        };
+
+        app.execute_contract(sender.clone(), contract_addr.clone(), &proposal_msg, &[])
+            .unwrap();
+
+        // Vote
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+
+        app.execute_contract(sender.clone(), contract_addr.clone(), &vote_msg, &[])
+            .unwrap();
+
+        // Execute the proposal
+        // Should fail because no funds in contract
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        app.execute_contract(sender.clone(), contract_addr.clone(), &execute_msg, &[])
+            .unwrap_err();
+
+        // Send funds to contract so that proposal can be executed
+        app.send_tokens(
+            sender.clone(),
+            contract_addr.clone(),
+            &coins(100, DEFAULT_DENOM),
+        )
+        .unwrap();
+
+        // Executing the proposal should succeed now
+        app.execute_contract(sender.clone(), contract_addr.clone(), &execute_msg, &[])
+            .unwrap();
+
+        // Check balance of recipient
+        let balance = app
+            .wrap()
+            .query_balance(Addr::unchecked("recipient_address"), DEFAULT_DENOM)
+            .unwrap();
+
+        assert_eq!(balance, coin(100, DEFAULT_DENOM));
+
+        // Replaying the execution reports where it originally happened
+        let height = app.block_info().height;
+        app.update_block(|block| block.height += 1);
+        let err: ContractError = app
+            .execute_contract(sender.clone(), contract_addr.clone(), &execute_msg, &[])
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert!(matches!(err, ContractError::AlreadyExecuted { height: h, .. } if h == height));
+    }
+
+    #[test]
+    fn list_members_with_cursor() {
+        let mut deps = mock_dependencies();
+
+        let members = (1..=3)
+            .map(|i| Member {
+                address: Addr::unchecked(format!("addr{}", i)),
+                weight: Uint128::from(10_u128),
+            })
+            .collect();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let page =
+            query_list_members(deps.as_ref(), Order::Ascending, None, Some(2), None).unwrap();
+        assert_eq!(2, page.members.len());
+        assert_eq!(Addr::unchecked("addr2"), page.members[1].address);
+        assert!(page.next_cursor.is_some());
+
+        let page = query_list_members(
+            deps.as_ref(),
+            Order::Ascending,
+            None,
+            Some(2),
+            page.next_cursor,
+        )
+        .unwrap();
+        assert_eq!(1, page.members.len());
+        assert_eq!(Addr::unchecked("addr3"), page.members[0].address);
+        assert_eq!(None, page.next_cursor);
+
+        let page =
+            query_list_members(deps.as_ref(), Order::Descending, None, Some(2), None).unwrap();
+        assert_eq!(Addr::unchecked("addr3"), page.members[0].address);
+        let page = query_list_members(
+            deps.as_ref(),
+            Order::Descending,
+            None,
+            Some(2),
+            page.next_cursor,
+        )
+        .unwrap();
+        assert_eq!(Addr::unchecked("addr1"), page.members[0].address);
+        assert_eq!(None, page.next_cursor);
+    }
+
+    #[test]
+    fn list_members_by_weight() {
+        let mut deps = mock_dependencies();
+
+        let members = [
+            ("addr1", 5_u128),
+            ("addr2", 20),
+            ("addr3", 10),
+            ("addr4", 10),
+        ]
+        .iter()
+        .map(|(address, weight)| Member {
+            address: Addr::unchecked(*address),
+            weight: Uint128::from(*weight),
+        })
+        .collect();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let addresses = |page: &ListMembersResponse| {
+            page.members
+                .iter()
+                .map(|member| member.address.to_string())
+                .collect::<Vec<_>>()
+        };
+        let page = query_list_members_by_weight(deps.as_ref(), true, None, Some(3), None).unwrap();
+        assert_eq!(vec!["addr2", "addr4", "addr3"], addresses(&page));
+        let page =
+            query_list_members_by_weight(deps.as_ref(), true, None, Some(3), page.next_cursor)
+                .unwrap();
+        assert_eq!(vec!["addr1"], addresses(&page));
+        assert_eq!(None, page.next_cursor);
+
+        // the index follows weight changes, removals and rotations
+        let update = Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(30_u128),
+        };
+        let remove = [Addr::unchecked("addr3")];
+        apply_member_changes(deps.as_mut().storage, 1, None, &[], &remove, &[update]).unwrap();
+        move_membership(
+            deps.as_mut().storage,
+            1,
+            &Addr::unchecked("addr4"),
+            &Addr::unchecked("addr5"),
+        )
+        .unwrap();
+        let start_after = Some(Addr::unchecked("addr5"));
+        let page =
+            query_list_members_by_weight(deps.as_ref(), false, start_after, None, None).unwrap();
+        assert_eq!(vec!["addr2", "addr1"], addresses(&page));
+        let page = query_list_members_by_weight(deps.as_ref(), false, None, None, None).unwrap();
+        assert_eq!(vec!["addr5", "addr2", "addr1"], addresses(&page));
+    }
+
+    #[test]
+    fn update_profile() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let msg = ExecuteMsg::UpdateProfile {
+            display_name: Some("Alice".parse().unwrap()),
+            avatar_uri: Some("ipfs://avatar".parse().unwrap()),
+            bio_hash: None,
+        };
+
+        // Non-members cannot set a profile
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), msg).unwrap();
+
+        let member = query_get_member(deps.as_ref(), Addr::unchecked("addr1")).unwrap();
+        let profile = member.profile.unwrap();
+        assert_eq!("Alice", profile.display_name.unwrap());
+        assert_eq!("ipfs://avatar", profile.avatar_uri.unwrap());
+
+        // Oversized fields never make it past deserialization
+        let msg = format!(
+            r#"{{"update_profile":{{"display_name":"{}","avatar_uri":null,"bio_hash":null}}}}"#,
+            "a".repeat(65)
+        );
+        from_json::<ExecuteMsg>(msg.as_bytes()).unwrap_err();
+    }
+
+    #[test]
+    fn announcement_proposal() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let info = mock_info("addr1", &[]);
+        let msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Round 1".parse().unwrap(),
+            description: "Announce the first round".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::Announce {
+                title: "Round 1 is open".parse().unwrap(),
+                body: "Applications close in two weeks".parse().unwrap(),
+            }),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // A second proposal gets its own id
+        let msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Another".parse().unwrap(),
+            description: "Another proposal".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(
+            1,
+            query_get_proposal(deps.as_ref(), mock_env(), 1)
+                .unwrap()
+                .id()
+        );
+
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        let res = execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let page = query_list_announcements(deps.as_ref(), None, None, None).unwrap();
+        assert_eq!(1, page.announcements.len());
+        assert_eq!("Round 1 is open", page.announcements[0].title);
+        assert_eq!(mock_env().block.time, page.announcements[0].published_at);
+    }
+
+    #[test]
+    fn typed_querier() {
+        let sender = Addr::unchecked("sender");
+        let mut app = App::default();
+
+        let contract_id = app.store_code(dao_contract());
+        let members = vec![Member {
+            address: sender.clone(),
+            weight: Uint128::from(10_u128),
+        }];
+        let contract_addr = app
+            .instantiate_contract(
+                contract_id,
+                sender.clone(),
+                &instantiate_msg(members),
+                &[],
+                "grant-dao",
+                None,
+            )
+            .unwrap();
+        let proposal_msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: None,
+            recipient: None,
+            proposal_type: None,
+        };
+        app.execute_contract(sender.clone(), contract_addr.clone(), &proposal_msg, &[])
+            .unwrap();
+
+        let dao = GrantDaoQuerier::new(app.wrap(), contract_addr);
+        assert_eq!(Uint128::from(10_u128), dao.member_weight(&sender).unwrap());
+        dao.member(&Addr::unchecked("stranger")).unwrap_err();
+        assert_eq!(ProposalStatus::Open, dao.proposal_status(0).unwrap());
+        dao.proposal_status(1).unwrap_err();
+        assert_eq!(DEFAULT_DENOM, dao.config().unwrap().denom);
+    }
+
+    #[test]
+    fn cw4_membership() {
+        let sender = Addr::unchecked("sender");
+        let mut app = App::default();
+
+        let group_id = app.store_code(group_contract());
+        let group = app
+            .instantiate_contract(group_id, sender.clone(), &Empty {}, &[], "group", None)
+            .unwrap();
+        let contract_id = app.store_code(dao_contract());
+        let members = vec![Member {
+            address: sender.clone(),
+            weight: Uint128::one(),
+        }];
+        let contract_addr = app
+            .instantiate_contract(
+                contract_id,
+                sender.clone(),
+                &InstantiateMsg {
+                    membership: Some(MembershipSource::Cw4 {
+                        group: group.clone(),
+                    }),
+                    ..instantiate_msg(members)
+                },
+                &[],
+                "grant-dao",
+                None,
+            )
+            .unwrap();
+
+        // proposing stays with the member list, weight comes from the group
+        let proposal_msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: None,
+            recipient: None,
+            proposal_type: None,
+        };
+        app.execute_contract(sender.clone(), contract_addr.clone(), &proposal_msg, &[])
+            .unwrap();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        let err = app
+            .execute_contract(sender.clone(), contract_addr.clone(), &vote_msg, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast().unwrap(),
+            ContractError::Unauthorized {}
+        ));
+        app.execute_contract(
+            Addr::unchecked("grouped"),
+            contract_addr.clone(),
+            &vote_msg,
+            &[],
+        )
+        .unwrap();
+
+        let dao = GrantDaoQuerier::new(app.wrap(), contract_addr.clone());
+        let ProposalRecord::Full(proposal) = dao.proposal(0).unwrap() else {
+            panic!("proposal was archived");
+        };
+        assert_eq!(Uint128::from(7_u128), proposal.votes_for);
+        assert_eq!(Uint128::from(10_u128), proposal.total_weight);
+        assert_eq!(ProposalStatus::Passed, proposal.status);
+
+        // the group's hook brings the member list in line with it
+        let hook = ExecuteMsg::MemberChangedHook(MemberChangedHookMsg {
+            diffs: vec![
+                MemberDiff {
+                    key: "grouped".to_string(),
+                    old: Some(7),
+                    new: Some(7),
+                },
+                MemberDiff {
+                    key: sender.to_string(),
+                    old: Some(3),
+                    new: None,
+                },
+            ],
+        });
+        let err = app
+            .execute_contract(sender.clone(), contract_addr.clone(), &hook, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast().unwrap(),
+            ContractError::Unauthorized {}
+        ));
+        app.execute_contract(group, contract_addr.clone(), &hook, &[])
+            .unwrap();
+        let dao = GrantDaoQuerier::new(app.wrap(), contract_addr);
+        assert_eq!(
+            Uint128::from(7_u128),
+            dao.member_weight(&Addr::unchecked("grouped")).unwrap()
+        );
+        dao.member(&sender).unwrap_err();
+    }
+
+    #[test]
+    fn member_changed_hook() {
+        let mut deps = mock_dependencies();
+
+        let members = [("addr1", 3_u128), ("addr2", 3), ("addr3", 4)]
+            .iter()
+            .map(|(address, weight)| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::from(*weight),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.membership = Some(MembershipSource::Cw4 {
+            group: Addr::unchecked("group"),
+        });
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let diff = |key: &str, old, new| MemberDiff {
+            key: key.to_string(),
+            old,
+            new,
+        };
+        let hook = |diffs| ExecuteMsg::MemberChangedHook(MemberChangedHookMsg { diffs });
+
+        // only the group speaks for the group
+        let msg = hook(vec![diff("addr4", None, Some(5))]);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        assert!(!MEMBERS.has(&deps.storage, &Addr::unchecked("addr4")));
+
+        // the same batch rules as a membership proposal
+        let msg = hook(vec![
+            diff("addr4", None, Some(5)),
+            diff("addr4", Some(5), Some(6)),
+        ]);
+        let err = execute(deps.as_mut(), mock_env(), mock_info("group", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMemberEntry { .. }));
+
+        let msg = hook(vec![
+            diff("addr4", None, Some(5)),
+            diff("addr1", Some(3), Some(8)),
+            diff("addr2", Some(3), None),
+            diff("addr3", Some(4), Some(0)),
+        ]);
+        execute(deps.as_mut(), mock_env(), mock_info("group", &[]), msg).unwrap();
+        let weight = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, address: &str| {
+            MEMBERS
+                .may_load(&deps.storage, &Addr::unchecked(address))
+                .unwrap()
+                .map(|member| member.weight)
+        };
+        assert_eq!(Some(Uint128::from(5_u128)), weight(&deps, "addr4"));
+        assert_eq!(Some(Uint128::from(8_u128)), weight(&deps, "addr1"));
+        assert_eq!(None, weight(&deps, "addr2"));
+        // weight 0 leaves the list rather than holding a member with no say
+        assert_eq!(None, weight(&deps, "addr3"));
+        assert_eq!(
+            Uint128::from(13_u128),
+            TOTAL_WEIGHT.load(&deps.storage).unwrap()
+        );
+    }
+
+    #[test]
+    fn failing_notifier_does_not_block_proposals() {
+        let sender = Addr::unchecked("sender");
+        let mut app = App::default();
+
+        let notifier_id = app.store_code(failing_notifier_contract());
+        let notifier_addr = app
+            .instantiate_contract(
+                notifier_id,
+                sender.clone(),
+                &Empty {},
+                &[],
+                "notifier",
+                None,
+            )
+            .unwrap();
+
+        let contract_id = app.store_code(dao_contract());
+        let members = vec![Member {
+            address: sender.clone(),
+            weight: Uint128::from(10_u128),
+        }];
+        let contract_addr = app
+            .instantiate_contract(
+                contract_id,
+                sender.clone(),
+                &InstantiateMsg {
+                    notifier: Some(notifier_addr.to_string()),
+                    ..instantiate_msg(members)
+                },
+                &[],
+                "grant-dao",
+                None,
+            )
+            .unwrap();
+
+        let proposal_msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: None,
+            recipient: None,
+            proposal_type: None,
+        };
+        let res = app
+            .execute_contract(sender.clone(), contract_addr.clone(), &proposal_msg, &[])
+            .unwrap();
+        assert!(res.has_event(
+            &Event::new("wasm")
+                .add_attribute("notification", "failed")
+                .add_attribute("error_code", "13")
+        ));
+
+        let proposal: ProposalRecord = app
+            .wrap()
+            .query_wasm_smart(contract_addr, &QueryMsg::GetProposal { proposal_id: 0 })
+            .unwrap();
+        let ProposalRecord::Full(proposal) = proposal else {
+            panic!("proposal was archived");
+        };
+        assert_eq!("Some Title", proposal.title);
+    }
+
+    #[test]
+    fn scheduled_config_update() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let effective_at = mock_env().block.time.plus_seconds(3600);
+        let new_config = Config {
+            denom: DEFAULT_DENOM.to_string(),
+            notifier: Some(Addr::unchecked("notifier")),
+            receipt_salt: None,
+            admin: None,
+            key_rotation_delay: None,
+            clock: None,
+            expected_block_time: None,
+            archive_after: None,
+            referral_fee: None,
+            parent_dao: None,
+            voting_period: None,
+            quorum: None,
+            threshold: None,
+            expense_threshold: None,
+            proposal_deposit: None,
+            burn_forfeited_deposits: false,
+            tie_break: TieBreak::TieFails,
+            group_quorums: vec![],
+            shadow_funds: None,
+            execution_delay: None,
+            execution_expiration: None,
+            private_ballots: false,
+            guardian: None,
+            quorum_decay: vec![],
+            max_proposal_amount: None,
+            lock_self_voted_vesting: false,
+            voting_strategy: VotingStrategy::Linear,
+            membership: MembershipSource::Internal,
+            removal_grace_period: None,
+            private_drafts: false,
+            security_lane: None,
+            dex_router: None,
+            cw20_tokens: vec![],
+        };
+
+        let info = mock_info("addr1", &[]);
+        let msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "New notifier".parse().unwrap(),
+            description: "Switch to the new notifier next round".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::UpdateConfig {
+                config: Box::new(new_config.clone()),
+                effective_at: Some(Scheduled::AtTime(effective_at)),
+            }),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(deps.as_mut(), mock_env(), info.clone(), execute_msg).unwrap();
+
+        // Not effective yet
+        assert_eq!(None, CONFIG.load(&deps.storage).unwrap().notifier);
+        let pending = PENDING_CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(Scheduled::AtTime(effective_at), pending.effective_at);
+        // only what the proposal changes is scheduled
+        assert_eq!(
+            ConfigUpdate {
+                notifier: Some(new_config.notifier.clone()),
+                ..ConfigUpdate::default()
+            },
+            pending.changes
+        );
+
+        // a change made in place meanwhile, the way GoLive leaves shadow mode, is kept
+        CONFIG
+            .update(deps.as_mut().storage, |mut config| -> StdResult<_> {
+                config.archive_after = Some(7);
+                Ok(config)
+            })
+            .unwrap();
+        let expected = Config {
+            archive_after: Some(7),
+            ..new_config
+        };
+
+        // readers see it from the effective time, before any write applies it
+        let mut env = mock_env();
+        env.block.time = effective_at;
+        let config: Config =
+            from_json(query(deps.as_ref(), env.clone(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!(expected, config);
+
+        // The first interaction after the effective time applies it
+        let msg = ExecuteMsg::UpdateProfile {
+            display_name: None,
+            avatar_uri: None,
+            bio_hash: None,
+        };
+        execute(deps.as_mut(), env, info, msg).unwrap();
+
+        assert_eq!(expected, CONFIG.load(&deps.storage).unwrap());
+        assert_eq!(None, PENDING_CONFIG.may_load(&deps.storage).unwrap());
+    }
+
+    #[test]
+    fn vested_grant() {
+        let sender = Addr::unchecked("sender");
+        let recipient = Addr::unchecked("recipient_address");
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &sender, coins(1_000, DEFAULT_DENOM))
+                .unwrap();
+        });
+
+        let contract_id = app.store_code(dao_contract());
+        let members = vec![Member {
+            address: sender.clone(),
+            weight: Uint128::from(10_u128),
+        }];
+        let contract_addr = app
+            .instantiate_contract(
+                contract_id,
+                sender.clone(),
+                &instantiate_msg(members),
+                &coins(1_000, DEFAULT_DENOM),
+                "grant-dao",
+                None,
+            )
+            .unwrap();
+
+        // 20% upfront, the rest over 100 seconds
+        let proposal_msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Vested".parse().unwrap(),
+            description: "Vested grant".parse().unwrap(),
+            amount: Some(Uint128::from(1_000_u128)),
+            recipient: Some(recipient.to_string()),
+            proposal_type: Some(ProposalType::VestedGrant {
+                upfront: Uint128::from(200_u128),
+                duration: 100,
+            }),
+        };
+        app.execute_contract(sender.clone(), contract_addr.clone(), &proposal_msg, &[])
+            .unwrap();
+
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        app.execute_contract(sender.clone(), contract_addr.clone(), &vote_msg, &[])
+            .unwrap();
+
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        app.execute_contract(sender.clone(), contract_addr.clone(), &execute_msg, &[])
+            .unwrap();
+
+        let balance = app.wrap().query_balance(&recipient, DEFAULT_DENOM).unwrap();
+        assert_eq!(balance, coin(200, DEFAULT_DENOM));
+
+        // Halfway through, half of the remainder is claimable
+        app.update_block(|block| block.time = block.time.plus_seconds(50));
+        let vesting: VestingResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetVesting { proposal_id: 0 })
+            .unwrap();
+        assert_eq!(Uint128::from(400_u128), vesting.claimable);
+
+        let claim_msg = ExecuteMsg::ClaimVested { proposal_id: 0 };
+        app.execute_contract(sender.clone(), contract_addr.clone(), &claim_msg, &[])
+            .unwrap_err();
+        app.execute_contract(recipient.clone(), contract_addr.clone(), &claim_msg, &[])
+            .unwrap();
+        app.execute_contract(recipient.clone(), contract_addr.clone(), &claim_msg, &[])
+            .unwrap_err();
+
+        let status: GrantStatusResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::GetGrantStatus { proposal_id: 0 })
+            .unwrap();
+        assert_eq!(Uint128::from(600_u128), status.paid);
+        assert_eq!(Uint128::from(400_u128), status.remaining);
+        assert!(status.vesting.is_some());
+
+        // Past the end everything has vested
+        app.update_block(|block| block.time = block.time.plus_seconds(500));
+        app.execute_contract(recipient.clone(), contract_addr.clone(), &claim_msg, &[])
+            .unwrap();
+
+        let balance = app.wrap().query_balance(&recipient, DEFAULT_DENOM).unwrap();
+        assert_eq!(balance, coin(1_000, DEFAULT_DENOM));
+    }
+
+    #[test]
+    fn update_members_batch() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(10_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(5_u128),
+            },
+        ];
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let info = mock_info("addr1", &[]);
+        let propose =
+            |add: Vec<Member>, remove: Vec<Addr>, update: Vec<Member>| ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Rotation".parse().unwrap(),
+                description: "Annual committee rotation".parse().unwrap(),
+                recipient: None,
+                amount: None,
+                proposal_type: Some(ProposalType::UpdateMembersBatch {
+                    add,
+                    remove,
+                    update,
+                }),
+            };
+
+        // Invalid entries are reported individually at propose time
+        let msg = propose(
+            vec![Member {
+                address: Addr::unchecked("addr3"),
+                weight: Uint128::zero(),
+            }],
+            vec![],
+            vec![],
+        );
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(
+            matches!(err, ContractError::InvalidMemberEntry { address, .. } if address == "addr3")
+        );
+
+        let msg = propose(
+            vec![Member {
+                address: Addr::unchecked("addr3"),
+                weight: Uint128::from(7_u128),
+            }],
+            vec![Addr::unchecked("addr2")],
+            vec![Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(20_u128),
+            }],
+        );
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+
+        assert_eq!(
+            Uint128::from(27_u128),
+            TOTAL_WEIGHT.load(&deps.storage).unwrap()
+        );
+        assert!(!MEMBERS.has(&deps.storage, &Addr::unchecked("addr2")));
+        let member = query_get_member(deps.as_ref(), Addr::unchecked("addr1")).unwrap();
+        assert_eq!(Uint128::from(20_u128), member.weight);
+    }
+
+    #[test]
+    fn suspend_member() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(10_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(5_u128),
+            },
+        ];
+        let msg = InstantiateMsg {
+            admin: Some("admin".to_string()),
+            ..instantiate_msg(members)
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let suspend_msg = ExecuteMsg::SuspendMember {
+            member: "addr2".to_string(),
+            reason: "Key reported compromised".parse().unwrap(),
+            duration: 3600,
+        };
+
+        // Only the admin may suspend
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            suspend_msg.clone(),
+        )
+        .unwrap_err();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            suspend_msg,
+        )
+        .unwrap();
+
+        let suspension = SUSPENSIONS
+            .load(&deps.storage, &Addr::unchecked("addr2"))
+            .unwrap();
+        assert_eq!(0, suspension.confirmation_proposal_id);
+
+        // The suspended member can neither vote nor propose
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: false,
+        };
+        let err =
+            execute(deps.as_mut(), mock_env(), mock_info("addr2", &[]), vote_msg).unwrap_err();
+        assert!(matches!(err, ContractError::MemberSuspended {}));
+
+        // Governance confirms, removing the member
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), vote_msg).unwrap();
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            execute_msg,
+        )
+        .unwrap();
+
+        assert!(!MEMBERS.has(&deps.storage, &Addr::unchecked("addr2")));
+        assert!(!SUSPENSIONS.has(&deps.storage, &Addr::unchecked("addr2")));
+        assert_eq!(
+            Uint128::from(10_u128),
+            TOTAL_WEIGHT.load(&deps.storage).unwrap()
+        );
+    }
+
+    #[test]
+    fn suspension_in_blocks() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let msg = InstantiateMsg {
+            admin: Some("admin".to_string()),
+            clock: Some(ClockMode::Height),
+            expected_block_time: Some(6),
+            ..instantiate_msg(members)
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let suspend_msg = ExecuteMsg::SuspendMember {
+            member: "addr1".to_string(),
+            reason: "Key reported compromised".parse().unwrap(),
+            duration: 10,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            suspend_msg,
+        )
+        .unwrap();
+
+        let suspension = SUSPENSIONS
+            .load(&deps.storage, &Addr::unchecked("addr1"))
+            .unwrap();
+        assert_eq!(
+            Scheduled::AtHeight(mock_env().block.height + 10),
+            suspension.expires_at
+        );
+
+        // Reported in seconds as well from the expected block time
+        let res = query_get_suspension(deps.as_ref(), mock_env(), Addr::unchecked("addr1"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Some(mock_env().block.time.plus_seconds(60)),
+            res.expires.time
+        );
+
+        // A long stretch of wall-clock time does not lift it, ten blocks do
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(86_400);
+        let err = ensure_not_suspended(&deps.storage, &env, &Addr::unchecked("addr1")).unwrap_err();
+        assert!(matches!(err, ContractError::MemberSuspended {}));
+
+        env.block.height += 10;
+        ensure_not_suspended(&deps.storage, &env, &Addr::unchecked("addr1")).unwrap();
+    }
+
+    #[test]
+    fn rotate_key() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let msg = InstantiateMsg {
+            key_rotation_delay: Some(60),
+            ..instantiate_msg(members)
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let info = mock_info("addr1", &[]);
+        let proposal_msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: None,
+            recipient: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), proposal_msg).unwrap();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+
+        let rotate_msg = ExecuteMsg::RotateKey {
+            new_address: "addr1_new".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), info, rotate_msg).unwrap();
+
+        // The new address has to wait out the delay
+        let new_info = mock_info("addr1_new", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            new_info.clone(),
+            ExecuteMsg::ConfirmKeyRotation {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::KeyRotationPending { .. }));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        execute(
+            deps.as_mut(),
+            env,
+            new_info,
+            ExecuteMsg::ConfirmKeyRotation {},
+        )
+        .unwrap();
+
+        assert!(!MEMBERS.has(&deps.storage, &Addr::unchecked("addr1")));
+        let member = query_get_member(deps.as_ref(), Addr::unchecked("addr1_new")).unwrap();
+        assert_eq!(Uint128::from(10_u128), member.weight);
+
+        let ballot = query_get_vote(deps.as_ref(), 0, Addr::unchecked("addr1_new")).unwrap();
+        assert_eq!(Addr::unchecked("addr1_new"), ballot.voter);
+        assert!(!BALLOTS.has(&deps.storage, (0, &Addr::unchecked("addr1"))));
+    }
+
+    #[test]
+    fn participation_snapshot() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(10_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(5_u128),
+            },
+        ];
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let proposal_msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: None,
+            recipient: None,
+            proposal_type: None,
+        };
+        for _ in 0..3 {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("addr1", &[]),
+                proposal_msg.clone(),
+            )
+            .unwrap();
+        }
+
+        // addr1 votes on everything, addr2 only on the first proposal
+        for (voter, proposal_id) in [("addr1", 0), ("addr1", 1), ("addr1", 2), ("addr2", 0)] {
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id,
+                approve: true,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+        }
+
+        // Proposal 2 stays unexecuted
+        for proposal_id in [0, 1] {
+            let execute_msg = ExecuteMsg::Execute { proposal_id };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("addr1", &[]),
+                execute_msg,
+            )
+            .unwrap();
+        }
+
+        let start = mock_env().block.time;
+        let selection = ProposalSelection::ExecutedBetween {
+            start,
+            end: start.plus_seconds(1),
+        };
+        let snapshot = query_participation_snapshot(deps.as_ref(), selection).unwrap();
+        assert_eq!(vec![0, 1], snapshot.proposal_ids);
+        assert_eq!(
+            vec![
+                Participation {
+                    address: Addr::unchecked("addr1"),
+                    proposals_voted: 2,
+                    weight: Uint128::from(20_u128),
+                },
+                Participation {
+                    address: Addr::unchecked("addr2"),
+                    proposals_voted: 1,
+                    weight: Uint128::from(5_u128),
+                },
+            ],
+            snapshot.participants
+        );
+
+        let selection = ProposalSelection::Ids {
+            proposal_ids: vec![1, 2],
+        };
+        query_participation_snapshot(deps.as_ref(), selection).unwrap_err();
+    }
+
+    #[test]
+    fn delegated_participation() {
+        let mut deps = mock_dependencies();
+        let members = [("addr1", 10_u128), ("addr2", 5), ("addr3", 1)]
+            .iter()
+            .map(|(address, weight)| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::from(*weight),
+            })
+            .collect();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let delegate = ExecuteMsg::Delegate {
+            delegate: "addr1".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr2", &[]), delegate).unwrap();
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: None,
+            recipient: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), vote_msg).unwrap();
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            execute_msg,
+        )
+        .unwrap();
+
+        // the delegate's ballot carried 15, only 10 of it was theirs
+        let selection = ProposalSelection::Ids {
+            proposal_ids: vec![0],
+        };
+        let snapshot = query_participation_snapshot(deps.as_ref(), selection).unwrap();
+        assert_eq!(
+            vec![
+                Participation {
+                    address: Addr::unchecked("addr1"),
+                    proposals_voted: 1,
+                    weight: Uint128::from(10_u128),
+                },
+                Participation {
+                    address: Addr::unchecked("addr2"),
+                    proposals_voted: 1,
+                    weight: Uint128::from(5_u128),
+                },
+            ],
+            snapshot.participants
+        );
+    }
+
+    #[test]
+    fn reward_periods_are_bounded() {
+        let mut deps = mock_dependencies();
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }];
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let now = mock_env().block.time;
+        let propose = |start: Timestamp, end| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: None,
+            recipient: None,
+            proposal_type: Some(ProposalType::DistributeRewards {
+                pool: Uint128::from(100_u128),
+                start,
+                end,
+                min_participation: Decimal::zero(),
+            }),
+        };
+
+        // a period still running could grow past the bound before execution
+        let msg = propose(now.minus_seconds(100), now.plus_seconds(1));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+
+        // one more executed proposal than a snapshot covers, the last at the period's end
+        for id in 0..=MAX_SNAPSHOT_PROPOSALS as u64 {
+            let time = now.minus_seconds(100).plus_nanos(id);
+            EXECUTED_AT
+                .save(deps.as_mut().storage, (time.nanos(), id), &Empty {})
+                .unwrap();
+        }
+        let last = now
+            .minus_seconds(100)
+            .plus_nanos(MAX_SNAPSHOT_PROPOSALS as u64);
+        let msg = propose(now.minus_seconds(100), last.plus_nanos(1));
+        let err = execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+
+        // the end is exclusive, leaving the last one out fits
+        let msg = propose(now.minus_seconds(100), last);
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn participation_rewards() {
+        let sender = Addr::unchecked("addr1");
+        let mut app = App::new(|router, _api, storage| {
+            router
+                .bank
+                .init_balance(storage, &sender, coins(1_000, DEFAULT_DENOM))
+                .unwrap();
+        });
+
+        let contract_id = app.store_code(dao_contract());
+        let members = vec![
+            Member {
+                address: sender.clone(),
+                weight: Uint128::from(30_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(10_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr3"),
+                weight: Uint128::from(10_u128),
+            },
+        ];
+        let contract_addr = app
+            .instantiate_contract(
+                contract_id,
+                sender.clone(),
+                &instantiate_msg(members),
+                &coins(1_000, DEFAULT_DENOM),
+                "grant-dao",
+                None,
+            )
+            .unwrap();
+
+        let start = app.block_info().time;
+        let propose = |proposal_type| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: None,
+            recipient: None,
+            proposal_type,
+        };
+
+        // Two executed proposals: addr1 and addr2 vote on both, addr3 on one
+        for (proposal_id, voters) in [
+            (0, vec!["addr1", "addr2", "addr3"]),
+            (1, vec!["addr1", "addr2"]),
+        ] {
+            app.execute_contract(sender.clone(), contract_addr.clone(), &propose(None), &[])
+                .unwrap();
+            for voter in voters {
+                let vote_msg = ExecuteMsg::Vote {
+                    proposal_id,
+                    approve: true,
+                };
+                app.execute_contract(
+                    Addr::unchecked(voter),
+                    contract_addr.clone(),
+                    &vote_msg,
+                    &[],
+                )
+                .unwrap();
+            }
+            let execute_msg = ExecuteMsg::Execute { proposal_id };
+            app.execute_contract(sender.clone(), contract_addr.clone(), &execute_msg, &[])
+                .unwrap();
+        }
+
+        app.update_block(|block| block.time = block.time.plus_seconds(10));
+        let rewards = propose(Some(ProposalType::DistributeRewards {
+            pool: Uint128::from(800_u128),
+            start,
+            end: app.block_info().time,
+            min_participation: Decimal::one(),
+        }));
+        app.execute_contract(sender.clone(), contract_addr.clone(), &rewards, &[])
+            .unwrap();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 2,
+            approve: true,
+        };
+        app.execute_contract(sender.clone(), contract_addr.clone(), &vote_msg, &[])
+            .unwrap();
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 2 };
+        app.execute_contract(sender.clone(), contract_addr.clone(), &execute_msg, &[])
+            .unwrap();
+
+        // what is scheduled stays reserved until it is paid
+        let treasury = |app: &App| -> TreasuryStatsResponse {
+            app.wrap()
+                .query_wasm_smart(&contract_addr, &QueryMsg::TreasuryStats {})
+                .unwrap()
+        };
+        assert_eq!(Uint128::from(800_u128), treasury(&app).committed);
+        assert_eq!(Uint128::from(200_u128), treasury(&app).available);
+
+        // Paid in chunks of one
+        let pay_msg = ExecuteMsg::PayRewards {
+            proposal_id: 2,
+            limit: Some(1),
+        };
+        for committed in [200_u128, 0] {
+            app.execute_contract(sender.clone(), contract_addr.clone(), &pay_msg, &[])
+                .unwrap();
+            assert_eq!(Uint128::from(committed), treasury(&app).committed);
+        }
+        app.execute_contract(sender.clone(), contract_addr.clone(), &pay_msg, &[])
+            .unwrap_err();
+
+        // addr3 missed a proposal; the pool splits 60/20 by weight cast
+        let balance = |address: &str| {
+            app.wrap()
+                .query_balance(Addr::unchecked(address), DEFAULT_DENOM)
+                .unwrap()
+                .amount
+                .u128()
+        };
+        assert_eq!(600, balance("addr1"));
+        assert_eq!(200, balance("addr2"));
+        assert_eq!(0, balance("addr3"));
+
+        let distribution: RewardDistribution = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::GetRewardDistribution { proposal_id: 2 },
+            )
+            .unwrap();
+        assert_eq!(2, distribution.eligible);
+        assert_eq!(0, distribution.pending);
+        assert_eq!(Uint128::from(800_u128), distribution.paid);
+    }
+
+    #[test]
+    fn vote_storage_cost() {
+        let mut deps = mock_dependencies();
+
+        let members = (0..50)
+            .map(|i| Member {
+                address: Addr::unchecked(format!("addr{:02}", i)),
+                weight: Uint128::one(),
+            })
+            .collect();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: Some(Uint128::from(100_u128)),
+            recipient: Some("recipient_address".to_string()),
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr00", &[]), propose).unwrap();
+
+        let mut costs = vec![];
+        for i in 0..50 {
+            let mut storage = CountingStorage::new(&mut deps.storage);
+            let vote_deps = DepsMut {
+                storage: &mut storage,
+                api: &deps.api,
+                querier: QuerierWrapper::new(&deps.querier),
+            };
+            let info = mock_info(&format!("addr{:02}", i), &[]);
+            execute_vote(vote_deps, mock_env(), info.sender, None, 0, i % 2 == 0).unwrap();
+            costs.push((storage.reads.get(), storage.writes, storage.bytes_written));
+        }
+
+        // one tally, one ballot and one storage usage write, whatever the number of earlier
+        // voters; the only growth in bytes is the tally and usage counters gaining digits and
+        // "false" over "true". Every yes vote breaks a tie and makes the proposal pass, so it
+        // also reads what the treasury has left to reserve, which here is nothing. One more
+        // read checks whether the voter has delegated
+        let bytes_written = costs[0].2;
+        for (i, &(reads, writes, bytes)) in costs.iter().enumerate() {
+            let flips = if i % 2 == 0 { 3 } else { 0 };
+            assert_eq!(3, writes, "voter {}", i);
+            assert!(reads <= 9 + flips, "voter {} read {} keys", i, reads);
+            assert!(
+                bytes <= bytes_written + 6,
+                "voter {} wrote {} bytes",
+                i,
+                bytes
+            );
+        }
+    }
+
+    #[test]
+    fn can_vote_and_propose() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(10_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(5_u128),
+            },
+        ];
+        let mut msg = instantiate_msg(members);
+        msg.admin = Some("admin".to_string());
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            amount: Some(Uint128::from(100_u128)),
+            recipient: Some("recipient_address".to_string()),
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+
+        let check = |deps: Deps, proposal_id: u64, address: &str| {
+            query_can_vote(deps, mock_env(), proposal_id, Addr::unchecked(address)).unwrap()
+        };
+
+        let res = check(deps.as_ref(), 0, "addr1");
+        assert!(res.can_vote);
+        assert_eq!(None, res.error_code);
+
+        let res = check(deps.as_ref(), 0, "stranger");
+        assert!(!res.can_vote);
+        assert_eq!(Some(ContractError::Unauthorized {}.code()), res.error_code);
+
+        let res = check(deps.as_ref(), 7, "addr1");
+        assert_eq!(
+            Some(ContractError::ProposalDoesNotExist {}.code()),
+            res.error_code
+        );
+
+        let vote = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), vote).unwrap();
+        // still able to change the ballot
+        let res = check(deps.as_ref(), 0, "addr1");
+        assert!(res.can_vote);
+
+        let suspend = ExecuteMsg::SuspendMember {
+            member: "addr2".to_string(),
+            reason: "Key reported compromised".parse().unwrap(),
+            duration: 3600,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), suspend).unwrap();
+        let res = check(deps.as_ref(), 0, "addr2");
+        assert_eq!(
+            Some(ContractError::MemberSuspended {}.code()),
+            res.error_code
+        );
+
+        let res = query_can_propose(deps.as_ref(), mock_env(), Addr::unchecked("addr1")).unwrap();
+        assert!(res.can_propose);
+        let res = query_can_propose(deps.as_ref(), mock_env(), Addr::unchecked("addr2")).unwrap();
+        assert!(!res.can_propose);
+        assert_eq!(
+            Some(ContractError::MemberSuspended {}.code()),
+            res.error_code
+        );
+        let res =
+            query_can_propose(deps.as_ref(), mock_env(), Addr::unchecked("stranger")).unwrap();
+        assert_eq!(Some(ContractError::Unauthorized {}.code()), res.error_code);
+    }
+
+    #[test]
+    fn archive_proposals() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let msg = InstantiateMsg {
+            archive_after: Some(100),
+            referral_fee: None,
+            parent_dao: None,
+            voting_period: None,
+            quorum: None,
+            threshold: None,
+            expense_threshold: None,
+            proposal_deposit: None,
+            burn_forfeited_deposits: None,
+            tie_break: None,
+            groups: None,
+            group_quorums: None,
+            shadow_funds: None,
+            execution_delay: None,
+            execution_expiration: None,
+            private_ballots: None,
+            guardian: None,
+            quorum_decay: None,
+            max_proposal_amount: None,
+            lock_self_voted_vesting: None,
+            voting_strategy: None,
+            membership: None,
+            removal_grace_period: None,
+            private_drafts: None,
+            security_lane: None,
+            dex_router: None,
+            cw20_tokens: None,
+            ..instantiate_msg(members)
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let info = mock_info("addr1", &[]);
+        for title in ["Executed", "Still open"] {
+            let msg = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: title.parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: None,
+                amount: None,
+                proposal_type: None,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(deps.as_mut(), mock_env(), info.clone(), execute_msg.clone()).unwrap();
+
+        // Nothing is due before the retention period passes
+        let archive_msg = ExecuteMsg::ArchiveProposals {
+            start_after: None,
+            limit: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), archive_msg.clone()).unwrap();
+        assert_eq!(res.attributes[1], ("archived", "0"));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        // a page past the only executed proposal has nothing to archive
+        let past = ExecuteMsg::ArchiveProposals {
+            start_after: Some(0),
+            limit: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), past).unwrap();
+        assert_eq!(res.attributes[1], ("archived", "0"));
+        let chunk = ExecuteMsg::ArchiveProposals {
+            start_after: None,
+            limit: Some(1),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), chunk).unwrap();
+        assert_eq!(res.attributes[1], ("archived", "1"));
+        assert_eq!(res.attributes[2], ("last_proposal_id", "0"));
+        // archived records are skipped rather than archived again
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), archive_msg).unwrap();
+        assert_eq!(res.attributes[1], ("archived", "0"));
+        assert!(!PROPOSALS.has(&deps.storage, 0));
+
+        let ProposalRecord::Archived(archived) =
+            query_get_proposal(deps.as_ref(), mock_env(), 0).unwrap()
+        else {
+            panic!("proposal was not archived");
+        };
+        assert_eq!(
+            HexBinary::from(Sha256::digest(b"Executed").to_vec()),
+            archived.title_hash
+        );
+        assert_eq!(Uint128::from(10_u128), archived.votes_for);
+
+        // Both kinds are listed together, and snapshots still see the archived one
+        let page = query_list_proposals(
+            deps.as_ref(),
+            mock_env(),
+            Order::Ascending,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let ids: Vec<u64> = page.proposals.iter().map(ProposalRecord::id).collect();
+        assert_eq!(vec![0, 1], ids);
+        assert!(matches!(page.proposals[1], ProposalRecord::Full(_)));
+
+        // newest first, one page each
+        let newest = |cursor| {
+            query_list_proposals(
+                deps.as_ref(),
+                mock_env(),
+                Order::Descending,
+                None,
+                Some(1),
+                cursor,
+            )
+            .unwrap()
+        };
+        let page = newest(None);
+        assert_eq!(1, page.proposals[0].id());
+        let page = newest(page.next_cursor);
+        assert!(matches!(page.proposals[..], [ProposalRecord::Archived(_)]));
+        assert_eq!(None, page.next_cursor);
+
+        let snapshot = query_participation_snapshot(
+            deps.as_ref(),
+            ProposalSelection::Ids {
+                proposal_ids: vec![0],
+            },
+        )
+        .unwrap();
+        assert_eq!(1, snapshot.participants.len());
+
+        let err = execute(deps.as_mut(), env, info, execute_msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyExecuted { .. }));
+    }
+
+    #[test]
+    fn storage_stats() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(10_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(5_u128),
+            },
+        ];
+        let msg = InstantiateMsg {
+            archive_after: Some(0),
+            referral_fee: None,
+            parent_dao: None,
+            voting_period: None,
+            quorum: None,
+            threshold: None,
+            expense_threshold: None,
+            proposal_deposit: None,
+            burn_forfeited_deposits: None,
+            tie_break: None,
+            groups: None,
+            group_quorums: None,
+            shadow_funds: None,
+            execution_delay: None,
+            execution_expiration: None,
+            private_ballots: None,
+            guardian: None,
+            quorum_decay: None,
+            max_proposal_amount: None,
+            lock_self_voted_vesting: None,
+            voting_strategy: None,
+            membership: None,
+            removal_grace_period: None,
+            private_drafts: None,
+            security_lane: None,
+            dex_router: None,
+            cw20_tokens: None,
+            ..instantiate_msg(members)
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let stats = query_storage_stats(deps.as_ref()).unwrap();
+        assert_eq!(2, stats.members.count);
+        assert_eq!(0, stats.proposals.count);
+
+        let info = mock_info("addr1", &[]);
+        let msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+
+        let stats = query_storage_stats(deps.as_ref()).unwrap();
+        assert_eq!(1, stats.proposals.count);
+        let proposal_key = PROPOSALS.key(0);
+        let proposal_value = deps.storage.get(&proposal_key).unwrap();
+        assert_eq!(
+            (proposal_key.len() + proposal_value.len()) as u64,
+            stats.proposals.bytes
+        );
+        assert_eq!(1, stats.ballots.count);
+
+        // Executing rewrites the proposal in place, archiving moves it over
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(deps.as_mut(), mock_env(), info.clone(), execute_msg).unwrap();
+        let stats = query_storage_stats(deps.as_ref()).unwrap();
+        assert_eq!(1, stats.proposals.count);
+        assert!(stats.proposals.bytes > (proposal_key.len() + proposal_value.len()) as u64);
+
+        let archive_msg = ExecuteMsg::ArchiveProposals {
+            start_after: None,
+            limit: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, archive_msg).unwrap();
+        let stats = query_storage_stats(deps.as_ref()).unwrap();
+        assert_eq!(StorageUsage::default(), stats.proposals);
+        assert_eq!(1, stats.archive.count);
+        assert_eq!(1, stats.ballots.count);
+    }
+
+    #[test]
+    fn frontend_registry() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let info = mock_info("addr1", &[]);
+        let propose = |frontend_id: Option<&str>| ExecuteMsg::Propose {
+            frontend_id: frontend_id.map(|id| id.parse().unwrap()),
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+
+        // Unknown frontends are rejected
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            propose(Some("wallet-x")),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+
+        let msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Register".parse().unwrap(),
+            description: "Approve wallet-x".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::UpdateFrontends {
+                register: vec![Frontend {
+                    id: "wallet-x".parse().unwrap(),
+                    operator: Addr::unchecked("operator"),
+                }],
+                remove: vec![],
+            }),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(deps.as_mut(), mock_env(), info.clone(), execute_msg).unwrap();
+
+        let page = query_list_frontends(deps.as_ref(), None, None, None).unwrap();
+        assert_eq!(1, page.frontends.len());
+        assert_eq!(Addr::unchecked("operator"), page.frontends[0].operator);
+
+        let res = execute(deps.as_mut(), mock_env(), info, propose(Some("wallet-x"))).unwrap();
+        assert_eq!(res.attributes, vec![("frontend_id", "wallet-x")]);
+        let ProposalRecord::Full(proposal) =
+            query_get_proposal(deps.as_ref(), mock_env(), 1).unwrap()
+        else {
+            panic!("proposal was archived");
+        };
+        assert_eq!(Some("wallet-x".parse().unwrap()), proposal.frontend_id);
+    }
+
+    #[test]
+    fn referral_fee() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000, DEFAULT_DENOM));
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let mut msg = instantiate_msg(members.clone());
+        msg.referral_fee = Some(Decimal::percent(20));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+
+        let mut msg = instantiate_msg(members);
+        msg.referral_fee = Some(Decimal::percent(2));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let info = mock_info("addr1", &[]);
+        let pass = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, msg: ExecuteMsg, id: u64| {
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: id,
+                approve: true,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+            let execute_msg = ExecuteMsg::Execute { proposal_id: id };
+            execute(deps.as_mut(), mock_env(), info.clone(), execute_msg)
+        };
+
+        let register = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Register".parse().unwrap(),
+            description: "Approve wallet-x".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::UpdateFrontends {
+                register: vec![Frontend {
+                    id: "wallet-x".parse().unwrap(),
+                    operator: Addr::unchecked("operator"),
+                }],
+                remove: vec![],
+            }),
+        };
+        pass(&mut deps, register, 0).unwrap();
+
+        let grant = |amount: u128| ExecuteMsg::Propose {
+            frontend_id: Some("wallet-x".parse().unwrap()),
+            title: "Grant".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: Some("recipient".to_string()),
+            amount: Some(Uint128::from(amount)),
+            proposal_type: None,
+        };
+
+        // the fee is paid on top of the grant, both have to be covered
+        let err = pass(&mut deps, grant(1_000), 1).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        let res = pass(&mut deps, grant(500), 2).unwrap();
+        assert_eq!(
+            res.messages
+                .iter()
+                .map(|msg| msg.msg.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                grant_transfer(
+                    &Addr::unchecked("recipient"),
+                    Uint128::from(500_u128),
+                    DEFAULT_DENOM
+                )
+                .into(),
+                grant_transfer(
+                    &Addr::unchecked("operator"),
+                    Uint128::from(10_u128),
+                    DEFAULT_DENOM
+                )
+                .into(),
+            ]
+        );
+
+        let page = query_list_frontends(deps.as_ref(), None, None, None).unwrap();
+        assert_eq!(Uint128::from(10_u128), page.frontends[0].fees_paid);
+    }
+
+    #[test]
+    fn parent_vote() {
+        let sender = Addr::unchecked("sender");
+        let mut app = App::default();
+        let contract_id = app.store_code(dao_contract());
+
+        let members = vec![Member {
+            address: sender.clone(),
+            weight: Uint128::from(10_u128),
+        }];
+        let parent = app
+            .instantiate_contract(
+                contract_id,
+                sender.clone(),
+                &instantiate_msg(members.clone()),
+                &[],
+                "parent",
+                None,
+            )
+            .unwrap();
+        let orphan = app
+            .instantiate_contract(
+                contract_id,
+                sender.clone(),
+                &instantiate_msg(members.clone()),
+                &[],
+                "orphan",
+                None,
+            )
+            .unwrap();
+        let mut msg = instantiate_msg(members);
+        msg.parent_dao = Some(parent.to_string());
+        let child = app
+            .instantiate_contract(contract_id, sender.clone(), &msg, &[], "child", None)
+            .unwrap();
+
+        let propose = |proposal_type: ProposalType| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(proposal_type),
+        };
+        let pass = |app: &mut App, dao: &Addr, proposal_type: ProposalType, id: u64| {
+            app.execute_contract(sender.clone(), dao.clone(), &propose(proposal_type), &[])
+                .unwrap();
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: id,
+                approve: true,
+            };
+            app.execute_contract(sender.clone(), dao.clone(), &vote_msg, &[])
+                .unwrap();
+            let execute_msg = ExecuteMsg::Execute { proposal_id: id };
+            app.execute_contract(sender.clone(), dao.clone(), &execute_msg, &[])
+        };
+
+        // the parent admits the child as a member, then opens a proposal of its own
+        let admit = ProposalType::UpdateMembersBatch {
+            add: vec![Member {
+                address: child.clone(),
+                weight: Uint128::from(5_u128),
+            }],
+            remove: vec![],
+            update: vec![],
+        };
+        pass(&mut app, &parent, admit, 0).unwrap();
+        app.execute_contract(
+            sender.clone(),
+            parent.clone(),
+            &propose(ProposalType::Grant),
+            &[],
+        )
+        .unwrap();
+
+        let parent_vote = ProposalType::ParentVote {
+            proposal_id: 1,
+            approve: true,
+        };
+        let err: ContractError = app
+            .execute_contract(sender.clone(), orphan, &propose(parent_vote.clone()), &[])
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+
+        pass(&mut app, &child, parent_vote, 0).unwrap();
+
+        let ballot: Ballot = app
+            .wrap()
+            .query_wasm_smart(
+                parent,
+                &QueryMsg::GetVote {
+                    proposal_id: 1,
+                    voter: child,
+                },
+            )
+            .unwrap();
+        assert!(ballot.approve);
+        assert_eq!(Uint128::from(5_u128), ballot.weight);
+    }
+
+    #[test]
+    fn mirror_proposal() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000, DEFAULT_DENOM));
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let mut msg = instantiate_msg(members);
+        msg.parent_dao = Some("parent".to_string());
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mirror = ExecuteMsg::MirrorProposal {
+            parent_proposal_id: 7,
+            title: "Q3 budget".parse().unwrap(),
+            description: "".parse().unwrap(),
+            recipient: Addr::unchecked("recipient"),
+            amount: Uint128::from(400_u128),
+        };
+
+        // only the parent may push proposals, and each only once
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            mirror.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("parent", &[]),
+            mirror.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("parent", &[]), mirror).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+
+        let ProposalRecord::Full(proposal) =
+            query_get_proposal(deps.as_ref(), mock_env(), 0).unwrap()
+        else {
+            panic!("proposal was archived");
+        };
+        assert_eq!(Some(7), proposal.parent_proposal_id);
+
+        // nothing moves until the local members ratify it
+        let info = mock_info("addr1", &[]);
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), execute_msg.clone()).unwrap();
+        assert!(res.messages.is_empty());
+
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            grant_transfer(
+                &Addr::unchecked("recipient"),
+                Uint128::from(400_u128),
+                DEFAULT_DENOM
+            )
+            .into()
+        );
+    }
+
+    #[test]
+    fn locked_after_execution() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(10_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(1_u128),
+            },
+        ];
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let info = mock_info("addr1", &[]);
+        let msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+
+        let addr2 = Addr::unchecked("addr2");
+        let res = query_can_vote(deps.as_ref(), mock_env(), 0, addr2).unwrap();
+        assert_eq!(Some(14), res.error_code);
+
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: false,
+        };
+        let err =
+            execute(deps.as_mut(), mock_env(), mock_info("addr2", &[]), vote_msg).unwrap_err();
+        assert!(matches!(err, ContractError::ProposalLocked {}));
+    }
+
+    #[test]
+    fn migrate_backfills_statuses() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let info = mock_info("addr1", &[]);
+        for id in 0..3 {
+            let msg = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: None,
+                amount: None,
+                proposal_type: None,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+            if id == 1 {
+                continue;
+            }
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: id,
+                approve: true,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+            let execute_msg = ExecuteMsg::Execute { proposal_id: id };
+            execute(deps.as_mut(), mock_env(), info.clone(), execute_msg).unwrap();
+
+            // as if executed before the lock was recorded
+            let mut tally = TALLIES.load(&deps.storage, id).unwrap();
+            tally.locked = false;
+            TALLIES.save(&mut deps.storage, id, &tally).unwrap();
+        }
+
+        let msg = MigrateMsg::BackfillStatuses {
+            start_after: None,
+            limit: Some(2),
+        };
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("locked", "1")));
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("last_proposal_id", "1")));
+
+        let msg = MigrateMsg::BackfillStatuses {
+            start_after: Some(1),
+            limit: Some(2),
+        };
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("locked", "1")));
+        assert!(!res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "last_proposal_id"));
+
+        assert!(TALLIES.load(&deps.storage, 0).unwrap().locked);
+        assert!(!TALLIES.load(&deps.storage, 1).unwrap().locked);
+        assert!(TALLIES.load(&deps.storage, 2).unwrap().locked);
+
+        // never back to an older version
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "999.0.0").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg::Upgrade {}).unwrap_err();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.0.1").unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg::Upgrade {}).unwrap();
+        assert_eq!(None, parse_version("1.2"));
+        assert_eq!(Some((1, 2, 3)), parse_version("1.2.3-rc.1"));
+    }
+
+    #[test]
+    fn voting_deadline() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(5_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(5_u128),
+            },
+        ];
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        let vote = |proposal_id| ExecuteMsg::Vote {
+            proposal_id,
+            approve: true,
+        };
+        let info = mock_info("addr1", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), propose.clone()).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), propose).unwrap();
+
+        let proposal = query_get_proposal(deps.as_ref(), mock_env(), 0).unwrap();
+        let ProposalRecord::Full(proposal) = proposal else {
+            panic!("proposal was archived");
+        };
+        let end = mock_env().block.time.plus_seconds(100);
+        assert_eq!(Some(Scheduled::AtTime(end)), proposal.voting_end);
+        assert_eq!(Some(100), proposal.time_remaining);
+        assert_eq!(Uint128::from(10_u128), proposal.total_weight);
+        assert!(!proposal.quorum_reached && !proposal.threshold_reached);
+
+        // addr2 could still overturn proposal 0, so it waits for the deadline
+        execute(deps.as_mut(), mock_env(), info.clone(), vote(0)).unwrap();
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(40);
+        let ProposalRecord::Full(proposal) = query_get_proposal(deps.as_ref(), later, 0).unwrap()
+        else {
+            panic!("proposal was archived");
+        };
+        assert_eq!(Some(60), proposal.time_remaining);
+        assert!(proposal.quorum_reached && proposal.threshold_reached);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Execute { proposal_id: 0 },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::VotingOpen { .. }));
+
+        // proposal 1 has every vote in, nothing is left to wait for
+        execute(deps.as_mut(), mock_env(), info.clone(), vote(1)).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("addr2", &[]), vote(1)).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Execute { proposal_id: 1 },
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = end;
+        let err =
+            execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), vote(0)).unwrap_err();
+        assert!(matches!(err, ContractError::VotingClosed {}));
+        let res = execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::Execute { proposal_id: 0 },
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("method", "execute_execute")));
+    }
+
+    #[test]
+    fn configurable_denom() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000, "uatom"));
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let mut msg = instantiate_msg(members.clone());
+        msg.denom = Some("1atom".to_string());
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+
+        let mut msg = instantiate_msg(members);
+        msg.denom = Some("uatom".to_string());
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let config: Config =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        assert_eq!("uatom", config.denom);
+
+        let info = mock_info("addr1", &[]);
+        let msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Switch denom".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::UpdateConfig {
+                config: Box::new(Config {
+                    denom: DEFAULT_DENOM.to_string(),
+                    ..config
+                }),
+                effective_at: None,
+            }),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+
+        let msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Grant".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: Some("recipient".to_string()),
+            amount: Some(Uint128::from(300_u128)),
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        let res = execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            BankMsg::Send {
+                to_address: "recipient".to_string(),
+                amount: coins(300, "uatom"),
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn assert_invariants() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(10_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(3_u128),
+            },
+        ];
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), msg).unwrap();
+        for (voter, approve) in [("addr1", true), ("addr2", false)] {
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: 0,
+                approve,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+        }
+
+        let res = sudo(deps.as_mut(), mock_env(), SudoMsg::AssertInvariants {}).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("members", "2")));
+        assert!(res.attributes.contains(&Attribute::new("proposals", "1")));
+
+        // the ledgers have to be covered by what the contract actually holds
+        COMMITTED
+            .save(&mut deps.storage, &Uint128::from(50_u128))
+            .unwrap();
+        let err = sudo(deps.as_mut(), mock_env(), SudoMsg::AssertInvariants {}).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("50 committed but the balance is 0"));
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(50, DEFAULT_DENOM));
+        sudo(deps.as_mut(), mock_env(), SudoMsg::AssertInvariants {}).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "stabletoken" => {
+                let balance = Binary::from(br#"{"balance":"30"}"#.to_vec());
+                SystemResult::Ok(ContractResult::Ok(balance))
+            }
+            _ => SystemResult::Err(SystemError::Unknown {}),
+        });
+        let token = Addr::unchecked("stabletoken");
+        CW20_TREASURY
+            .save(&mut deps.storage, &token, &Uint128::from(30_u128))
+            .unwrap();
+        let res = sudo(deps.as_mut(), mock_env(), SudoMsg::AssertInvariants {}).unwrap();
+        assert!(res.attributes.contains(&Attribute::new("tokens", "1")));
+        CW20_TREASURY
+            .save(&mut deps.storage, &token, &Uint128::from(31_u128))
+            .unwrap();
+        let err = sudo(deps.as_mut(), mock_env(), SudoMsg::AssertInvariants {}).unwrap_err();
+        assert!(err.to_string().contains("ledger holds 31 of stabletoken"));
+        CW20_TREASURY.remove(&mut deps.storage, &token);
+
+        let mut tally = TALLIES.load(&deps.storage, 0).unwrap();
+        tally.votes_against = Uint128::zero();
+        TALLIES.save(&mut deps.storage, 0, &tally).unwrap();
+        let err = sudo(deps.as_mut(), mock_env(), SudoMsg::AssertInvariants {}).unwrap_err();
+        assert!(matches!(err, ContractError::InvariantViolated { .. }));
+
+        TOTAL_WEIGHT
+            .save(&mut deps.storage, &Uint128::from(12_u128))
+            .unwrap();
+        let err = sudo(deps.as_mut(), mock_env(), SudoMsg::AssertInvariants {}).unwrap_err();
+        assert!(err.to_string().contains("total weight 12"));
+    }
+
+    #[test]
+    fn quorum_and_threshold() {
+        let mut deps = mock_dependencies();
+
+        let members = [("addr1", 3_u128), ("addr2", 3), ("addr3", 4)]
+            .iter()
+            .map(|(address, weight)| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::from(*weight),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.quorum = Some(Decimal::percent(50));
+        msg.threshold = Some(Decimal::percent(60));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), msg).unwrap();
+
+        let vote = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, voter: &str, approve| {
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: 0,
+                approve,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+            let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(voter, &[]),
+                execute_msg,
+            );
+            !res.unwrap().attributes.is_empty()
+        };
+
+        // 3 of 10 misses the quorum, 3 of 6 misses the threshold, 7 of 10 clears both
+        assert!(!vote(&mut deps, "addr1", true));
+        assert!(!vote(&mut deps, "addr2", false));
+        assert!(vote(&mut deps, "addr3", true));
+    }
+
+    #[test]
+    fn open_proposals_keep_their_rules() {
+        let mut deps = mock_dependencies();
+
+        let members = [("addr1", 3_u128), ("addr2", 3), ("addr3", 4)]
+            .iter()
+            .map(|(address, weight)| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::from(*weight),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.quorum = Some(Decimal::percent(50));
+        msg.threshold = Some(Decimal::percent(60));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            propose.clone(),
+        )
+        .unwrap();
+
+        // stricter rules land while proposal 0 is being voted on
+        CONFIG
+            .update(deps.as_mut().storage, |mut config| -> StdResult<_> {
+                config.quorum = Some(Decimal::percent(90));
+                config.threshold = Some(Decimal::percent(80));
+                Ok(config)
+            })
+            .unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+
+        let vote =
+            |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, proposal_id, voter: &str, approve| {
+                let vote_msg = ExecuteMsg::Vote {
+                    proposal_id,
+                    approve,
+                };
+                execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+            };
+        for proposal_id in 0..2 {
+            vote(&mut deps, proposal_id, "addr1", true);
+            vote(&mut deps, proposal_id, "addr2", false);
+            vote(&mut deps, proposal_id, "addr3", true);
+        }
+
+        // 7 of 10 clears proposal 0's 50% quorum and 60% threshold, not proposal 1's 90% and 80%
+        let executed = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, proposal_id| {
+            let execute_msg = ExecuteMsg::Execute { proposal_id };
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("addr1", &[]),
+                execute_msg,
+            );
+            !res.unwrap().attributes.is_empty()
+        };
+        assert!(executed(&mut deps, 0));
+        assert!(!executed(&mut deps, 1));
+        let tally = TALLIES.load(&deps.storage, 0).unwrap();
+        assert_eq!(Some(Decimal::percent(50)), tally.quorum);
+        assert_eq!(Some(Decimal::percent(60)), tally.threshold);
+    }
+
+    #[test]
+    fn private_ballots() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(10_u128),
+        }];
+        let mut msg = instantiate_msg(members);
+        msg.private_ballots = Some(true);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let info = mock_info("addr1", &[]);
+        let propose = |proposal_type| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type,
+        };
+        let vote = |proposal_id| ExecuteMsg::Vote {
+            proposal_id,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), propose(None)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), vote(0)).unwrap();
+
+        // the tally stays public, the ballot does not
+        let ProposalRecord::Full(proposal) =
+            query_get_proposal(deps.as_ref(), mock_env(), 0).unwrap()
+        else {
+            panic!("proposal was archived");
+        };
+        assert_eq!(Uint128::from(10_u128), proposal.votes_for);
+        let addr1 = Addr::unchecked("addr1");
+        query_get_vote(deps.as_ref(), 0, addr1.clone()).unwrap_err();
+        query_list_votes(deps.as_ref(), 0, None, None, None).unwrap_err();
+
+        let disclose = Some(ProposalType::DiscloseBallots { proposal_id: 0 });
+        execute(deps.as_mut(), mock_env(), info.clone(), propose(disclose)).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), vote(1)).unwrap();
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 1 };
+        execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+
+        assert!(
+            query_get_vote(deps.as_ref(), 0, addr1.clone())
+                .unwrap()
+                .approve
+        );
+        query_get_vote(deps.as_ref(), 1, addr1).unwrap_err();
+    }
+
+    #[test]
+    fn proposal_status() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(3_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(3_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr3"),
+                weight: Uint128::from(4_u128),
+            },
+        ];
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        msg.quorum = Some(Decimal::percent(50));
+        msg.notifier = Some("notifier".to_string());
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        for _ in 0..3 {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("addr1", &[]),
+                propose.clone(),
+            )
+            .unwrap();
+        }
+        let vote = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, voter, proposal_id, approve| {
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id,
+                approve,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+        };
+        let status =
+            |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: Env, id| match query_get_proposal(
+                deps.as_ref(),
+                env,
+                id,
+            )
+            .unwrap()
+            {
+                ProposalRecord::Full(proposal) => proposal.status,
+                ProposalRecord::Archived(_) => panic!("proposal was archived"),
+            };
+
+        // 0 carries, 1 is voted down, 2 never reaches quorum
+        vote(&mut deps, "addr1", 0, true);
+        vote(&mut deps, "addr3", 0, true);
+        vote(&mut deps, "addr1", 1, false);
+        vote(&mut deps, "addr3", 1, false);
+        vote(&mut deps, "addr2", 2, true);
+
+        assert_eq!(ProposalStatus::Passed, status(&deps, mock_env(), 0));
+        assert_eq!(ProposalStatus::Rejected, status(&deps, mock_env(), 1));
+        assert_eq!(ProposalStatus::Open, status(&deps, mock_env(), 2));
+
+        let close = |id| ExecuteMsg::Close { proposal_id: id };
+        let anyone = mock_info("anyone", &[]);
+        execute(deps.as_mut(), mock_env(), anyone.clone(), close(0)).unwrap_err();
+        execute(deps.as_mut(), mock_env(), anyone.clone(), close(2)).unwrap_err();
+        execute(deps.as_mut(), mock_env(), anyone.clone(), close(1)).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        assert_eq!(ProposalStatus::Expired, status(&deps, env.clone(), 2));
+        let res = execute(deps.as_mut(), env.clone(), anyone.clone(), close(2)).unwrap();
+        let closed = NotifierMsg::ProposalUpdated {
+            proposal_id: 2,
+            event: ProposalEvent::Expired,
+        };
+        assert_eq!(
+            vec![SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: "notifier".to_string(),
+                    msg: to_json_binary(&closed).unwrap(),
+                    funds: vec![],
+                },
+                NOTIFY_REPLY_ID
+            )],
+            res.messages
+        );
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(deps.as_mut(), env.clone(), anyone, execute_msg).unwrap();
+
+        assert_eq!(ProposalStatus::Executed, status(&deps, env.clone(), 0));
+        assert_eq!(ProposalStatus::Rejected, status(&deps, env.clone(), 1));
+        assert_eq!(ProposalStatus::Expired, status(&deps, env, 2));
+        assert!(TALLIES.load(&deps.storage, 1).unwrap().locked);
+    }
+
+    #[test]
+    fn expense_threshold() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000, DEFAULT_DENOM));
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(4_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(6_u128),
+            },
+        ];
+        let mut msg = instantiate_msg(members);
+        msg.expense_threshold = Some(Decimal::percent(30));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = |proposal_type, recipient: Option<&str>| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Hosting".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: recipient.map(str::to_string),
+            amount: Some(Uint128::from(100_u128)),
+            proposal_type: Some(proposal_type),
+        };
+        let expense = || ProposalType::Expense {
+            invoice_hash: "sha256:abcd".parse().unwrap(),
+        };
+        let info = mock_info("addr1", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            propose(expense(), None),
+        )
+        .unwrap_err();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            propose(expense(), Some("vendor")),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            propose(ProposalType::Grant, Some("vendor")),
+        )
+        .unwrap();
+
+        // 4 of 10 carries the expense at 30% but not the grant at 50%
+        let mut executed = vec![];
+        for id in 0..2 {
+            for (voter, approve) in [("addr1", true), ("addr2", false)] {
+                let vote_msg = ExecuteMsg::Vote {
+                    proposal_id: id,
+                    approve,
+                };
+                execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+            }
+            let execute_msg = ExecuteMsg::Execute { proposal_id: id };
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("addr1", &[]),
+                execute_msg,
+            );
+            executed.push(!res.unwrap().messages.is_empty());
+        }
+        assert_eq!(vec![true, false], executed);
+    }
+
+    #[test]
+    fn proposal_deposits() {
+        let mut deps = mock_dependencies_with_balance(&coins(1_000, DEFAULT_DENOM));
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(1_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(1_u128),
+            },
+        ];
+        let mut msg = instantiate_msg(members);
+        msg.proposal_deposit = Some(Uint128::from(100_u128));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: Some("recipient".to_string()),
+            amount: Some(Uint128::from(900_u128)),
+            proposal_type: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            propose.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(18, err.code());
+        let short = mock_info("addr1", &coins(50, DEFAULT_DENOM));
+        execute(deps.as_mut(), mock_env(), short, propose.clone()).unwrap_err();
+
+        let paid = mock_info("addr1", &coins(100, DEFAULT_DENOM));
+        for _ in 0..2 {
+            execute(deps.as_mut(), mock_env(), paid.clone(), propose.clone()).unwrap();
+        }
+        // both deposits are in the balance now, but not the treasury's to grant
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(1_200, DEFAULT_DENOM));
+
+        for voter in ["addr1", "addr2"] {
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: 0,
+                approve: true,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: 1,
+                approve: false,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+        }
+
+        // only the proposer gets the deposit back, and only once the proposal passed
+        let withdraw = |id| ExecuteMsg::WithdrawDeposit { proposal_id: id };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr2", &[]),
+            withdraw(0),
+        )
+        .unwrap_err();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            withdraw(1),
+        )
+        .unwrap_err();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            withdraw(0),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "addr1".to_string(),
+                amount: coins(100, DEFAULT_DENOM),
+            })],
+            res.messages
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            withdraw(0),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NothingToClaim {}));
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(1_100, DEFAULT_DENOM));
+
+        // the rejected proposal's deposit stays in the treasury
+        let close = ExecuteMsg::Close { proposal_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), close).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(HELD_DEPOSITS.load(&deps.storage).unwrap(), Uint128::zero());
+        let deposit = PROPOSALS.load(&deps.storage, 1).unwrap().deposit.unwrap();
+        assert!(deposit.settled);
+
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            execute_msg,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn forfeited_deposits_can_be_burned() {
+        let mut deps = mock_dependencies();
+
+        let mut msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(1_u128),
+        }]);
+        msg.proposal_deposit = Some(Uint128::from(100_u128));
+        msg.burn_forfeited_deposits = Some(true);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        let paid = mock_info("addr1", &coins(100, DEFAULT_DENOM));
+        execute(deps.as_mut(), mock_env(), paid, propose).unwrap();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), vote_msg).unwrap();
+
+        let close = ExecuteMsg::Close { proposal_id: 0 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), close).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Burn {
+                amount: coins(100, DEFAULT_DENOM),
+            })],
+            res.messages
+        );
+    }
+
+    #[test]
+    fn change_vote() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(2_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::from(1_u128),
+            },
+        ];
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+
+        let vote = |approve| ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve,
+        };
+        let addr1 = mock_info("addr1", &[]);
+        execute(deps.as_mut(), mock_env(), addr1.clone(), vote(true)).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), addr1.clone(), vote(true)).unwrap_err();
+        assert!(matches!(err, ContractError::MemberAlreadyVoted {}));
+
+        execute(deps.as_mut(), mock_env(), addr1.clone(), vote(false)).unwrap();
+        let tally = TALLIES.load(&deps.storage, 0).unwrap();
+        assert_eq!(Uint128::zero(), tally.votes_for);
+        assert_eq!(Uint128::from(2_u128), tally.votes_against);
+        let ballot = query_get_vote(deps.as_ref(), 0, Addr::unchecked("addr1")).unwrap();
+        assert!(!ballot.approve);
+        assert_eq!(1, query_storage_stats(deps.as_ref()).unwrap().ballots.count);
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let err = execute(deps.as_mut(), env, addr1, vote(true)).unwrap_err();
+        assert!(matches!(err, ContractError::VotingClosed {}));
+    }
+
+    #[test]
+    fn vote_proof() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(3_u128),
+        }];
+        let msg = instantiate_msg(members);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+        let vote = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), vote).unwrap();
+
+        let voter = Addr::unchecked("addr1");
+        let proof = query_vote_proof(deps.as_ref(), 0, voter.clone()).unwrap();
+        assert_eq!(member_key(&voter), proof.member.key.to_vec());
+        let member: Member = from_json(proof.member.value.unwrap()).unwrap();
+        assert_eq!(Uint128::from(3_u128), member.weight);
+        let total: Uint128 = from_json(proof.total_weight.value.unwrap()).unwrap();
+        assert_eq!(Uint128::from(3_u128), total);
+        let ballot: Ballot = from_json(proof.ballot.value.unwrap()).unwrap();
+        assert_eq!(query_get_vote(deps.as_ref(), 0, voter).unwrap(), ballot);
+
+        let proof = query_vote_proof(deps.as_ref(), 0, Addr::unchecked("stranger")).unwrap();
+        assert_eq!(None, proof.member.value);
+        assert_eq!(None, proof.ballot.value);
+    }
+
+    #[test]
+    fn tie_break() {
+        let tied = |tie_break| {
+            let mut deps = mock_dependencies();
+            let members = vec![
+                Member {
+                    address: Addr::unchecked("addr1"),
+                    weight: Uint128::from(1_u128),
+                },
+                Member {
+                    address: Addr::unchecked("addr2"),
+                    weight: Uint128::from(1_u128),
+                },
+            ];
+            let mut msg = instantiate_msg(members);
+            msg.voting_period = Some(100);
+            msg.tie_break = Some(tie_break);
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: None,
+                amount: None,
+                proposal_type: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+            for (voter, approve) in [("addr1", true), ("addr2", false)] {
+                let vote = ExecuteMsg::Vote {
+                    proposal_id: 0,
+                    approve,
+                };
+                execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote).unwrap();
+            }
+            deps
+        };
+        let status = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: Env| match query_get_proposal(
+            deps.as_ref(),
+            env,
+            0,
+        )
+        .unwrap()
+        {
+            ProposalRecord::Full(proposal) => proposal.status,
+            ProposalRecord::Archived(_) => panic!("proposal was archived"),
+        };
+        let mut ended = mock_env();
+        ended.block.time = ended.block.time.plus_seconds(100);
+        let anyone = mock_info("anyone", &[]);
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+
+        let deps = tied(TieBreak::TieFails);
+        assert_eq!(ProposalStatus::Rejected, status(&deps, ended.clone()));
+
+        let mut deps = tied(TieBreak::TiePasses);
+        assert_eq!(ProposalStatus::Open, status(&deps, mock_env()));
+        assert_eq!(ProposalStatus::Passed, status(&deps, ended.clone()));
+        let res = execute(
+            deps.as_mut(),
+            ended.clone(),
+            anyone.clone(),
+            execute_msg.clone(),
+        )
+        .unwrap();
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("tie_break", "tie_passes")));
+
+        let mut deps = tied(TieBreak::ProposerDecides);
+        assert_eq!(ProposalStatus::Tied, status(&deps, ended.clone()));
+        let break_tie = |approve| ExecuteMsg::BreakTie {
+            proposal_id: 0,
+            approve,
+        };
+        let err = execute(
+            deps.as_mut(),
+            ended.clone(),
+            mock_info("addr2", &[]),
+            break_tie(false),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            break_tie(true),
+        )
+        .unwrap_err();
+        execute(
+            deps.as_mut(),
+            ended.clone(),
+            mock_info("addr1", &[]),
+            break_tie(true),
+        )
+        .unwrap();
+        assert_eq!(ProposalStatus::Passed, status(&deps, ended.clone()));
+        execute(
+            deps.as_mut(),
+            ended.clone(),
+            mock_info("addr1", &[]),
+            break_tie(false),
+        )
+        .unwrap_err();
+
+        // the first vote cast in the extension settles it
+        let mut deps = tied(TieBreak::ExtendVoting);
+        assert_eq!(ProposalStatus::Open, status(&deps, ended.clone()));
+        let err = execute(deps.as_mut(), ended.clone(), anyone.clone(), execute_msg).unwrap_err();
+        assert!(matches!(err, ContractError::VotingOpen { .. }));
+        let vote = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: false,
+        };
+        execute(deps.as_mut(), ended.clone(), mock_info("addr1", &[]), vote).unwrap();
+        assert_eq!(ProposalStatus::Rejected, status(&deps, ended.clone()));
+
+        let mut extension_over = ended;
+        extension_over.block.time = extension_over.block.time.plus_seconds(100);
+        let deps = tied(TieBreak::ExtendVoting);
+        assert_eq!(ProposalStatus::Rejected, status(&deps, extension_over));
+    }
+
+    #[test]
+    fn upgrade_original_layout() {
+        let mut deps = mock_dependencies();
+
+        // state as the original contract left it
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+        for (address, weight) in [("addr1", 3_u128), ("addr2", 4)] {
+            let member = Member {
+                address: Addr::unchecked(address),
+                weight: Uint128::from(weight),
+            };
+            MEMBERS
+                .save(&mut deps.storage, &member.address, &member)
+                .unwrap();
+        }
+        deps.storage.set(
+            &PROPOSALS.key(0),
+            br#"{"id":0,"title":"Round 1","description":"","recipient":"addr1","amount":"100","votes_for":"4","votes_against":"0","voters":["addr2"],"executed":false}"#,
+        );
+
+        let msg = MigrateMsg::UpgradeOriginalLayout {};
+        let res = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("total_weight", "7")));
+        migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+
+        let proposal = match query_get_proposal(deps.as_ref(), mock_env(), 0).unwrap() {
+            ProposalRecord::Full(proposal) => proposal,
+            ProposalRecord::Archived(_) => panic!("proposal was archived"),
+        };
+        assert_eq!(Uint128::from(4_u128), proposal.votes_for);
+        assert_eq!(ProposalStatus::Passed, proposal.status);
+        let stats = query_storage_stats(deps.as_ref()).unwrap();
+        assert_eq!((1, 2), (stats.proposals.count, stats.members.count));
+
+        // the old votes stand, but voting is over
+        let vote = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), vote).unwrap_err();
+        sudo(deps.as_mut(), mock_env(), SudoMsg::AssertInvariants {}).unwrap();
+
+        // new proposals no longer overwrite it
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+        assert_eq!(
+            2,
+            query_storage_stats(deps.as_ref()).unwrap().proposals.count
+        );
+    }
+
+    #[test]
+    fn group_quorums() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![
+            Member {
+                address: Addr::unchecked("core1"),
+                weight: Uint128::from(1_u128),
+            },
+            Member {
+                address: Addr::unchecked("core2"),
+                weight: Uint128::from(1_u128),
+            },
+            Member {
+                address: Addr::unchecked("community1"),
+                weight: Uint128::from(3_u128),
+            },
+        ];
+        let group = |address: &str, group: &str| GroupMember {
+            address: Addr::unchecked(address),
+            group: group.parse().unwrap(),
+        };
+        let mut msg = instantiate_msg(members);
+        msg.groups = Some(vec![
+            group("core1", "core"),
+            group("core2", "core"),
+            group("community1", "community"),
+        ]);
+        msg.group_quorums = Some(vec![GroupQuorum {
+            group: "core".parse().unwrap(),
+            quorum: Decimal::percent(50),
+            categories: vec![ProposalCategory::Grant],
+        }]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = |proposal_type| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(proposal_type),
+        };
+        let info = mock_info("community1", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            propose(ProposalType::Grant),
+        )
+        .unwrap();
+        let announce = ProposalType::Announce {
+            title: "Some Title".parse().unwrap(),
+            body: "Some Body".parse().unwrap(),
+        };
+        execute(deps.as_mut(), mock_env(), info, propose(announce)).unwrap();
+
+        let vote = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, voter, proposal_id| {
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id,
+                approve: true,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+        };
+        let proposal = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, id| match query_get_proposal(
+            deps.as_ref(),
+            mock_env(),
+            id,
+        )
+        .unwrap()
+        {
+            ProposalRecord::Full(proposal) => proposal,
+            ProposalRecord::Archived(_) => panic!("proposal was archived"),
+        };
+
+        // the community alone carries the announcement, but the grant needs core votes too
+        vote(&mut deps, "community1", 0);
+        vote(&mut deps, "community1", 1);
+        assert_eq!(ProposalStatus::Passed, proposal(&deps, 1).status);
+        assert!(proposal(&deps, 1).groups.is_empty());
+        let grant = proposal(&deps, 0);
+        assert_eq!(ProposalStatus::Open, grant.status);
+        assert_eq!(Uint128::from(1_u128), grant.groups[0].required);
+
+        vote(&mut deps, "core2", 0);
+        let grant = proposal(&deps, 0);
+        assert_eq!(ProposalStatus::Passed, grant.status);
+        assert_eq!(Uint128::from(1_u128), grant.groups[0].votes_for);
+
+        let update = ProposalType::UpdateGroups {
+            assign: vec![group("community1", "core")],
+            unassign: vec![Addr::unchecked("core2")],
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("core1", &[]),
+            propose(update),
+        )
+        .unwrap();
+        vote(&mut deps, "community1", 2);
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 2 };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            execute_msg,
+        )
+        .unwrap();
+
+        let groups = query_list_groups(deps.as_ref(), None, None, None)
+            .unwrap()
+            .groups;
+        assert_eq!(
+            vec![Group {
+                id: "core".parse().unwrap(),
+                members: 2,
+                weight: Uint128::from(4_u128),
+            }],
+            groups
+        );
+        let member = query_get_member(deps.as_ref(), Addr::unchecked("core2")).unwrap();
+        assert_eq!(None, member.group);
+    }
+
+    #[test]
+    fn list_votes() {
+        let mut deps = mock_dependencies();
+
+        let members = (1..=3)
+            .map(|i| Member {
+                address: Addr::unchecked(format!("addr{}", i)),
+                weight: Uint128::from(1_u128),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+        for voter in ["addr3", "addr1", "addr2"] {
+            let vote = ExecuteMsg::Vote {
+                proposal_id: 0,
+                approve: voter != "addr2",
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote).unwrap();
+        }
+
+        let page = query_list_votes(deps.as_ref(), 0, None, Some(2), None).unwrap();
+        let voters: Vec<_> = page.votes.iter().map(|b| b.voter.as_str()).collect();
+        assert_eq!(vec!["addr1", "addr2"], voters);
+        let page = query_list_votes(deps.as_ref(), 0, None, Some(2), page.next_cursor).unwrap();
+        assert_eq!(1, page.votes.len());
+        assert_eq!("addr3", page.votes[0].voter.as_str());
+        assert_eq!(None, page.next_cursor);
+
+        let page = query_list_votes(deps.as_ref(), 1, None, None, None).unwrap();
+        assert!(page.votes.is_empty());
+    }
+
+    #[test]
+    fn shadow_mode() {
+        let mut deps = mock_dependencies();
+
+        let mut msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::from(1_u128),
+        }]);
+        msg.shadow_funds = Some(Uint128::from(1_000_u128));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let pass = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>,
+                    proposal_id: u64,
+                    amount: u128,
+                    proposal_type| {
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: Some("recipient".to_string()),
+                amount: Some(Uint128::from(amount)),
+                proposal_type,
+            };
+            let info = mock_info("addr1", &[]);
+            execute(deps.as_mut(), mock_env(), info.clone(), propose).unwrap();
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id,
+                approve: true,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                ExecuteMsg::Execute { proposal_id },
+            )
+        };
+
+        // nothing is sent, the grant is only booked against the simulated treasury
+        let res = pass(&mut deps, 0, 600, None).unwrap();
+        assert!(res.messages.is_empty());
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "shadow_payout"));
+        pass(&mut deps, 1, 600, None).unwrap_err();
+
+        let ledger: ShadowLedgerResponse = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ShadowLedger {
+                    start_after: None,
+                    limit: None,
+                    cursor: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Some(Uint128::from(400_u128)), ledger.shadow_funds_left);
+        assert_eq!(
+            vec![ShadowPayout {
+                recipient: Addr::unchecked("recipient"),
+                amount: Uint128::from(600_u128),
+            }],
+            ledger.payouts
+        );
+
+        pass(&mut deps, 2, 0, Some(ProposalType::GoLive {})).unwrap();
+        assert_eq!(None, CONFIG.load(&deps.storage).unwrap().shadow_funds);
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::GoLive {}),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap_err();
+
+        // live grants draw on the real balance
+        pass(&mut deps, 3, 400, None).unwrap_err();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(1_000, DEFAULT_DENOM));
+        let res = pass(&mut deps, 4, 400, None).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "recipient".to_string(),
+                amount: coins(400, DEFAULT_DENOM),
+            })],
+            res.messages
+        );
+    }
+
+    #[test]
+    fn votes_use_snapshotted_weight() {
+        let mut deps = mock_dependencies();
+
+        let members = ["addr1", "addr2", "addr3"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg(members),
+        )
+        .unwrap();
+
+        let propose = |proposal_type| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type,
+        };
+        let vote = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, voter, proposal_id, env| {
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id,
+                approve: true,
+            };
+            execute(deps.as_mut(), env, mock_info(voter, &[]), vote_msg)
+        };
+        let info = mock_info("addr1", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), propose(None)).unwrap();
+        let batch = ProposalType::UpdateMembersBatch {
+            add: vec![Member {
+                address: Addr::unchecked("addr4"),
+                weight: Uint128::from(5_u128),
+            }],
+            remove: vec![],
+            update: vec![Member {
+                address: Addr::unchecked("addr1"),
+                weight: Uint128::from(10_u128),
+            }],
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            propose(Some(batch)),
+        )
+        .unwrap();
+        for voter in ["addr1", "addr2"] {
+            vote(&mut deps, voter, 1, mock_env()).unwrap();
+        }
+
+        let mut env = mock_env();
+        env.block.height += 1;
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 1 };
+        execute(deps.as_mut(), env.clone(), info.clone(), execute_msg).unwrap();
+        env.block.height += 1;
+        let rotate = ExecuteMsg::RotateKey {
+            new_address: "addr5".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), rotate).unwrap();
+        env.block.height += 1;
+        execute(deps.as_mut(), env.clone(), info, propose(None)).unwrap();
+
+        // proposal 0 opened before the changes, so they do not reach it
+        let weight = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, id, voter| {
+            BALLOTS
+                .load(&deps.storage, (id, &Addr::unchecked(voter)))
+                .unwrap()
+                .weight
+                .u128()
+        };
+        vote(&mut deps, "addr1", 0, env.clone()).unwrap();
+        assert_eq!(1, weight(&deps, 0, "addr1"));
+        let err = vote(&mut deps, "addr4", 0, env.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        vote(&mut deps, "addr5", 0, env.clone()).unwrap();
+        assert_eq!(1, weight(&deps, 0, "addr5"));
+        let tally = TALLIES.load(&deps.storage, 0).unwrap();
+        assert_eq!(Some(Uint128::from(3_u128)), tally.total_weight);
+
+        vote(&mut deps, "addr1", 2, env.clone()).unwrap();
+        assert_eq!(10, weight(&deps, 2, "addr1"));
+        vote(&mut deps, "addr4", 2, env).unwrap();
+        assert_eq!(5, weight(&deps, 2, "addr4"));
+        let tally = TALLIES.load(&deps.storage, 2).unwrap();
+        assert_eq!(Some(Uint128::from(17_u128)), tally.total_weight);
+    }
+
+    #[test]
+    fn custom_messages() {
+        let mut deps = mock_dependencies_with_balance(&coins(300, DEFAULT_DENOM));
+
+        let info = mock_info("addr1", &[]);
+        let msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let propose = |msgs| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::Custom { msgs }),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), propose(vec![])).unwrap_err();
+
+        let msgs: Vec<CosmosMsg> = vec![
+            WasmMsg::UpdateAdmin {
+                contract_addr: "other_contract".to_string(),
+                admin: "new_admin".to_string(),
+            }
+            .into(),
+            WasmMsg::Execute {
+                contract_addr: "other_contract".to_string(),
+                msg: Binary::from(br#"{"ping":{}}"#),
+                funds: coins(200, DEFAULT_DENOM),
+            }
+            .into(),
+            BankMsg::Send {
+                to_address: "vendor".to_string(),
+                amount: coins(200, DEFAULT_DENOM),
+            }
+            .into(),
+        ];
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            propose(msgs.clone()),
+        )
+        .unwrap();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+
+        // the coins the messages send count against the treasury like any grant
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        let err =
+            execute(deps.as_mut(), mock_env(), info.clone(), execute_msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(400, DEFAULT_DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+        assert_eq!(
+            msgs.into_iter().map(SubMsg::new).collect::<Vec<_>>(),
+            res.messages
+        );
+    }
+
+    #[test]
+    fn import_proposals() {
+        let mut deps = mock_dependencies();
+
+        let mut msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }]);
+        msg.admin = Some("admin".to_string());
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let imported = |title: &str, days_ago: u64| ImportedProposal {
+            title: title.parse().unwrap(),
+            description: "From the old tracker".parse().unwrap(),
+            recipient: Addr::unchecked("recipient"),
+            amount: Uint128::zero(),
+            proposal_type: None,
+            submitted_at: mock_env().block.time.minus_seconds(days_ago * 86_400),
+        };
+        let import = ExecuteMsg::ImportProposals {
+            proposals: vec![imported("First", 90), imported("Second", 30)],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            import.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), import).unwrap();
+        // governance can import too, by having the contract call itself
+        let import = ExecuteMsg::ImportProposals {
+            proposals: vec![imported("Third", 10)],
+        };
+        let contract = mock_env().contract.address;
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(contract.as_str(), &[]),
+            import,
+        )
+        .unwrap();
+
+        let ProposalRecord::Full(proposal) =
+            query_get_proposal(deps.as_ref(), mock_env(), 0).unwrap()
+        else {
+            panic!("proposal was archived");
+        };
+        assert_eq!(ProposalStatus::Draft, proposal.status);
+        assert_eq!(
+            Some(mock_env().block.time.minus_seconds(90 * 86_400)),
+            proposal.submitted_at
+        );
+        assert_eq!("Third", PROPOSALS.load(&deps.storage, 2).unwrap().title);
+
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 1,
+            approve: true,
+        };
+        let info = mock_info("addr1", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), vote_msg.clone()).unwrap_err();
+
+        let open = ExecuteMsg::OpenDraft { proposal_id: 1 };
+        execute(deps.as_mut(), mock_env(), info.clone(), open.clone()).unwrap_err();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            open.clone(),
+        )
+        .unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), open).unwrap_err();
+        execute(deps.as_mut(), mock_env(), info, vote_msg).unwrap();
+        let ProposalRecord::Full(proposal) =
+            query_get_proposal(deps.as_ref(), mock_env(), 1).unwrap()
+        else {
+            panic!("proposal was archived");
+        };
+        assert_eq!(ProposalStatus::Passed, proposal.status);
+    }
+
+    #[test]
+    fn finalization_bounty() {
+        let mut deps = mock_dependencies();
+
+        let members = ["addr1", "addr2"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        let attach = |id| ExecuteMsg::AttachBounty { proposal_id: id };
+        for (id, approve) in [(0, true), (1, false), (2, true)] {
+            let info = mock_info("addr1", &[]);
+            execute(deps.as_mut(), mock_env(), info, propose.clone()).unwrap();
+            for voter in ["addr1", "addr2"] {
+                let vote_msg = ExecuteMsg::Vote {
+                    proposal_id: id,
+                    approve,
+                };
+                execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+            }
+        }
+
+        let funded = |sender| mock_info(sender, &coins(50, DEFAULT_DENOM));
+        execute(deps.as_mut(), mock_env(), funded("addr2"), attach(0)).unwrap_err();
+        execute(deps.as_mut(), mock_env(), funded("addr1"), attach(0)).unwrap();
+        execute(deps.as_mut(), mock_env(), funded("addr1"), attach(0)).unwrap_err();
+        execute(deps.as_mut(), mock_env(), funded("addr1"), attach(1)).unwrap();
+        assert_eq!(
+            Uint128::from(100_u128),
+            HELD_DEPOSITS.load(&deps.storage).unwrap()
+        );
+        let mut late = mock_env();
+        late.block.time = late.block.time.plus_seconds(100);
+        let err = execute(deps.as_mut(), late, funded("addr1"), attach(2)).unwrap_err();
+        assert!(matches!(err, ContractError::VotingClosed {}));
+
+        // finalized within the hour, the keeper collects it
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100 + 3_599);
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("keeper", &[]),
+            execute_msg,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "keeper".to_string(),
+                amount: coins(50, DEFAULT_DENOM),
+            })],
+            res.messages
+        );
+
+        // left for longer, it goes back to the proposer
+        env.block.time = env.block.time.plus_seconds(1);
+        let close = ExecuteMsg::Close { proposal_id: 1 };
+        let res = execute(deps.as_mut(), env, mock_info("keeper", &[]), close).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "addr1".to_string(),
+                amount: coins(50, DEFAULT_DENOM),
+            })],
+            res.messages
+        );
+        assert_eq!(Uint128::zero(), HELD_DEPOSITS.load(&deps.storage).unwrap());
+    }
+
+    #[test]
+    fn execution_timelock() {
+        let mut deps = mock_dependencies();
 
         let members = vec![Member {
             address: Addr::unchecked("addr1"),
-            weight: Uint128::from(10_u128),
+            weight: Uint128::one(),
+        }];
+        let mut msg = instantiate_msg(members);
+        msg.execution_delay = Some(50);
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        msg.voting_period = Some(100);
+        msg.execution_expiration = Some(200);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("addr1", &[]);
+        for id in 0..2 {
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: None,
+                amount: None,
+                proposal_type: None,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), propose).unwrap();
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: id,
+                approve: true,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+        }
+        let at = |seconds| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            env
+        };
+        let status =
+            |deps: &cosmwasm_std::OwnedDeps<_, _, _>, env: Env, id| match query_get_proposal(
+                deps.as_ref(),
+                env,
+                id,
+            )
+            .unwrap()
+            {
+                ProposalRecord::Full(proposal) => proposal.status,
+                ProposalRecord::Archived(_) => panic!("proposal was archived"),
+            };
+
+        // decided early, but the delay still counts from the deadline
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        let err =
+            execute(deps.as_mut(), mock_env(), info.clone(), execute_msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Timelocked { .. }));
+        let err = execute(deps.as_mut(), at(149), info.clone(), execute_msg.clone()).unwrap_err();
+        assert_eq!(19, err.code());
+        execute(deps.as_mut(), at(150), info.clone(), execute_msg).unwrap();
+        assert_eq!(ProposalStatus::Executed, status(&deps, at(150), 0));
+
+        // passed but left unexecuted past its window
+        assert_eq!(ProposalStatus::Passed, status(&deps, at(349), 1));
+        assert_eq!(ProposalStatus::Lapsed, status(&deps, at(350), 1));
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 1 };
+        let res = execute(deps.as_mut(), at(350), info.clone(), execute_msg).unwrap();
+        assert!(res.messages.is_empty());
+        let close = ExecuteMsg::Close { proposal_id: 1 };
+        let res = execute(deps.as_mut(), at(350), info, close).unwrap();
+        assert!(res.attributes.iter().any(|attr| attr.value == "lapsed"));
+        assert_eq!(
+            ProposalStatus::Lapsed,
+            PROPOSALS.load(&deps.storage, 1).unwrap().status
+        );
+    }
+
+    #[test]
+    fn vote_operators() {
+        let mut deps = mock_dependencies();
+
+        let members = ["addr1", "addr2"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, instantiate_msg(members)).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            propose.clone(),
+        )
+        .unwrap();
+
+        let set_operator = |operator: Option<&str>| ExecuteMsg::SetVoteOperator {
+            operator: operator.map(str::to_string),
+        };
+        let custodian = mock_info("custodian", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            custodian.clone(),
+            set_operator(Some("addr1")),
+        )
+        .unwrap_err();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            set_operator(Some("custodian")),
+        )
+        .unwrap();
+
+        let vote_for = |member: &str, approve| ExecuteMsg::VoteFor {
+            member: Addr::unchecked(member),
+            proposal_id: 0,
+            approve,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            custodian.clone(),
+            vote_for("addr2", true),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            custodian.clone(),
+            vote_for("addr1", false),
+        )
+        .unwrap();
+
+        // the ballot is the member's, only noting who cast it
+        let ballot = BALLOTS
+            .load(&deps.storage, (0, &Addr::unchecked("addr1")))
+            .unwrap();
+        assert!(!ballot.approve);
+        assert_eq!(Some(Addr::unchecked("custodian")), ballot.operator);
+        assert_eq!(
+            Some(Addr::unchecked("custodian")),
+            query_get_member(deps.as_ref(), Addr::unchecked("addr1"))
+                .unwrap()
+                .vote_operator
+        );
+
+        // voting is all an operator can do
+        let err = execute(deps.as_mut(), mock_env(), custodian.clone(), propose).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            set_operator(None),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            custodian,
+            vote_for("addr1", true),
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn outbox_log() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("addr1", &[]);
+        let msg = instantiate_msg(vec![Member {
+            address: info.sender.clone(),
+            weight: Uint128::from(1_u128),
+        }]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(1_000, DEFAULT_DENOM));
+
+        for (proposal_id, amount) in [(0, 100_u128), (1, 200)] {
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: Some("recipient".to_string()),
+                amount: Some(Uint128::from(amount)),
+                proposal_type: None,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), propose).unwrap();
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id,
+                approve: true,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+            let mut env = mock_env();
+            env.block.height += proposal_id;
+            let execute_msg = ExecuteMsg::Execute { proposal_id };
+            execute(deps.as_mut(), env, info.clone(), execute_msg).unwrap();
+        }
+
+        let outbox = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, limit, cursor| -> OutboxResponse {
+            let msg = QueryMsg::Outbox {
+                start_after: None,
+                limit,
+                cursor,
+            };
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+
+        let first = outbox(&deps, Some(1), None);
+        let sent: Vec<CosmosMsg> = vec![BankMsg::Send {
+            to_address: "recipient".to_string(),
+            amount: coins(100, DEFAULT_DENOM),
+        }
+        .into()];
+        assert_eq!(
+            vec![OutboxEntry {
+                id: 0,
+                proposal_id: 0,
+                digest: HexBinary::from(Sha256::digest(to_json_vec(&sent).unwrap()).to_vec()),
+                messages: 1,
+                height: mock_env().block.height,
+                time: mock_env().block.time,
+            }],
+            first.entries
+        );
+
+        let rest = outbox(&deps, None, first.next_cursor);
+        assert_eq!(1, rest.entries.len());
+        assert_eq!(1, rest.entries[0].proposal_id);
+        assert_eq!(mock_env().block.height + 1, rest.entries[0].height);
+        assert_eq!(None, rest.next_cursor);
+    }
+
+    #[test]
+    fn guardian_veto() {
+        let mut deps = mock_dependencies();
+
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
         }];
-        let msg = InstantiateMsg { members };
+        let mut msg = instantiate_msg(members);
+        msg.guardian = Some("guardian".to_string());
+        msg.voting_period = Some(100);
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        msg.execution_delay = Some(50);
+        msg.proposal_deposit = Some(Uint128::from(10_u128));
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let info = mock_info("addr1", &coins(10, DEFAULT_DENOM));
+        for id in 0..3 {
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: None,
+                amount: None,
+                proposal_type: None,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), propose).unwrap();
+            if id != 1 {
+                let vote_msg = ExecuteMsg::Vote {
+                    proposal_id: id,
+                    approve: true,
+                };
+                execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+            }
+        }
+        let at = |seconds| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            env
+        };
+        let veto = |id| ExecuteMsg::VetoProposal { proposal_id: id };
+        let guardian = mock_info("guardian", &[]);
+
+        let err = execute(deps.as_mut(), at(100), mock_info("addr1", &[]), veto(0)).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        // still open, and past its timelock
+        execute(deps.as_mut(), at(100), guardian.clone(), veto(1)).unwrap_err();
+        execute(deps.as_mut(), at(150), guardian.clone(), veto(2)).unwrap_err();
+
+        let res = execute(deps.as_mut(), at(100), guardian.clone(), veto(0)).unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "deposit_forfeited" && attr.value == "10"));
+        let ProposalRecord::Full(proposal) = query_get_proposal(deps.as_ref(), at(150), 0).unwrap()
+        else {
+            panic!("proposal was archived");
+        };
+        assert_eq!(ProposalStatus::Vetoed, proposal.status);
+        execute(deps.as_mut(), at(150), guardian, veto(0)).unwrap_err();
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(deps.as_mut(), at(150), mock_info("addr1", &[]), execute_msg).unwrap_err();
+
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 2 };
+        execute(deps.as_mut(), at(150), mock_info("addr1", &[]), execute_msg).unwrap();
+    }
+
+    #[test]
+    fn proposer_cancels() {
+        let mut deps = mock_dependencies();
+
+        let members = ["addr1", "addr2"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        msg.proposal_deposit = Some(Uint128::from(10_u128));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let proposer = mock_info("addr1", &coins(10, DEFAULT_DENOM));
+        for id in 0..2 {
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: None,
+                amount: None,
+                proposal_type: None,
+            };
+            execute(deps.as_mut(), mock_env(), proposer.clone(), propose).unwrap();
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: id,
+                approve: true,
+            };
+            execute(deps.as_mut(), mock_env(), proposer.clone(), vote_msg).unwrap();
+        }
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 1,
+            approve: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr2", &[]), vote_msg).unwrap();
+
+        let cancel = |id| ExecuteMsg::Cancel { proposal_id: id };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr2", &[]),
+            cancel(0),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        // someone else has weighed in
+        execute(deps.as_mut(), mock_env(), proposer.clone(), cancel(1)).unwrap_err();
+
+        let res = execute(deps.as_mut(), mock_env(), proposer.clone(), cancel(0)).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "addr1".to_string(),
+                amount: coins(10, DEFAULT_DENOM),
+            })],
+            res.messages
+        );
+        assert_eq!(
+            ProposalStatus::Cancelled,
+            PROPOSALS.load(&deps.storage, 0).unwrap().status
+        );
+        assert_eq!(
+            Uint128::from(10_u128),
+            HELD_DEPOSITS.load(&deps.storage).unwrap()
+        );
+
+        execute(deps.as_mut(), mock_env(), proposer.clone(), cancel(0)).unwrap_err();
+        let vote_msg = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr2", &[]), vote_msg).unwrap_err();
+    }
+
+    #[test]
+    fn quorum_decay() {
+        let mut deps = mock_dependencies();
+
+        let members = (0..10)
+            .map(|i| Member {
+                address: Addr::unchecked(format!("addr{}", i)),
+                weight: Uint128::one(),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        msg.quorum_decay = Some(vec![QuorumDecay {
+            grace: 100,
+            floor: Decimal::percent(20),
+            categories: vec![ProposalCategory::Grant],
+        }]);
         let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        msg.quorum = Some(Decimal::percent(50));
         instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        // Propose
-        let info = mock_info("addr1", &[]);
-        let proposal_msg = ExecuteMsg::Propose {
-            title: "Some Title".to_string(),
-            description: "Some Description".to_string(),
-            amount: Some(Uint128::from(100_u128)),
-            recipient: Some(Addr::unchecked("recipient_address")),
+        let at = |seconds| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            env
+        };
+        let vote = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env, voter: &str, id| {
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: id,
+                approve: true,
+            };
+            execute(deps.as_mut(), env, mock_info(voter, &[]), vote_msg)
+        };
+        let status = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, seconds, id| {
+            let ProposalRecord::Full(proposal) =
+                query_get_proposal(deps.as_ref(), at(seconds), id).unwrap()
+            else {
+                panic!("proposal was archived");
+            };
+            proposal.status
+        };
+
+        let announce = ProposalType::Announce {
+            title: "Some Title".parse().unwrap(),
+            body: "Some Body".parse().unwrap(),
+        };
+        for (id, proposal_type, voters) in [
+            (0, None, 3),
+            (1, None, 3),
+            (2, None, 1),
+            (3, Some(announce), 3),
+        ] {
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: None,
+                amount: None,
+                proposal_type,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("addr0", &[]), propose).unwrap();
+            for i in 0..voters {
+                vote(&mut deps, mock_env(), &format!("addr{}", i), id).unwrap();
+            }
+        }
+
+        // 30% cast, the quorum falls from 50% to 20% over the grace and reaches it two thirds in
+        assert_eq!(ProposalStatus::Open, status(&deps, 166, 0));
+        assert_eq!(ProposalStatus::Passed, status(&deps, 167, 0));
+
+        // votes are still taken in the grace, and bring the end forward
+        vote(&mut deps, at(120), "addr3", 1).unwrap();
+        assert_eq!(ProposalStatus::Open, status(&deps, 133, 1));
+        assert_eq!(ProposalStatus::Passed, status(&deps, 134, 1));
+        let err = vote(&mut deps, at(134), "addr4", 1).unwrap_err();
+        assert!(matches!(err, ContractError::VotingClosed {}));
+
+        // short of even the floor, and a category the decay does not cover
+        assert_eq!(ProposalStatus::Open, status(&deps, 199, 2));
+        assert_eq!(ProposalStatus::Expired, status(&deps, 200, 2));
+        assert_eq!(ProposalStatus::Expired, status(&deps, 100, 3));
+    }
+
+    #[test]
+    fn self_voted_vesting_locked() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("addr1", &[]);
+        let mut msg = instantiate_msg(vec![Member {
+            address: info.sender.clone(),
+            weight: Uint128::one(),
+        }]);
+        msg.lock_self_voted_vesting = Some(true);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(1_000, DEFAULT_DENOM));
+
+        // the first pays the voter themself, the second someone who never voted
+        for (proposal_id, recipient) in [(0, None), (1, Some("recipient".to_string()))] {
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Vested".parse().unwrap(),
+                description: "Vested grant".parse().unwrap(),
+                amount: Some(Uint128::from(100_u128)),
+                recipient,
+                proposal_type: Some(ProposalType::VestedGrant {
+                    upfront: Uint128::zero(),
+                    duration: 100,
+                }),
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), propose).unwrap();
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id,
+                approve: true,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), vote_msg).unwrap();
+            let execute_msg = ExecuteMsg::Execute { proposal_id };
+            let res = execute(deps.as_mut(), mock_env(), info.clone(), execute_msg).unwrap();
+            assert_eq!(
+                proposal_id == 0,
+                res.attributes
+                    .iter()
+                    .any(|attr| attr.key == "recipient_voted" && attr.value == "addr1")
+            );
+        }
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let claim = |id| ExecuteMsg::ClaimVested { proposal_id: id };
+        let err = execute(deps.as_mut(), env.clone(), info, claim(0)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+        execute(deps.as_mut(), env, mock_info("recipient", &[]), claim(1)).unwrap();
+    }
+
+    #[test]
+    fn governance_analytics() {
+        let mut deps = mock_dependencies();
+
+        let members = ["addr1", "addr2"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg(members),
+        )
+        .unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(1_000, DEFAULT_DENOM));
+
+        let at = |seconds| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            env
+        };
+        // executed after 100 and 300 seconds, and one voted down
+        for (id, approve, executed_after) in [(0, true, 100), (1, false, 0), (2, true, 300)] {
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: Some("recipient".to_string()),
+                amount: Some(Uint128::from(50_u128)),
+                proposal_type: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+            for voter in ["addr1", "addr2"] {
+                let vote_msg = ExecuteMsg::Vote {
+                    proposal_id: id,
+                    approve,
+                };
+                execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+            }
+            let msg = if approve {
+                ExecuteMsg::Execute { proposal_id: id }
+            } else {
+                ExecuteMsg::Close { proposal_id: id }
+            };
+            execute(
+                deps.as_mut(),
+                at(executed_after),
+                mock_info("addr1", &[]),
+                msg,
+            )
+            .unwrap();
+        }
+
+        let analytics = query_analytics(deps.as_ref(), mock_env(), None).unwrap();
+        assert_eq!(3, analytics.proposals_created);
+        assert_eq!(2, analytics.proposals_passed);
+        assert_eq!(1, analytics.proposals_rejected);
+        assert_eq!(Uint128::from(100_u128), analytics.funds_granted);
+        assert_eq!(Some(Decimal::one()), analytics.average_participation);
+        assert_eq!(Some(200), analytics.median_time_to_execution);
+        assert!(analytics.start <= mock_env().block.time && mock_env().block.time < analytics.end);
+
+        let earlier =
+            query_analytics(deps.as_ref(), mock_env(), Some(analytics.period - 1)).unwrap();
+        assert_eq!(0, earlier.proposals_created);
+        assert_eq!(None, earlier.average_participation);
+        assert_eq!(analytics.start, earlier.end);
+    }
+
+    #[test]
+    fn treasury_commitments() {
+        let mut deps = mock_dependencies();
+
+        let members = ["addr1", "addr2"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            instantiate_msg(members),
+        )
+        .unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(100, DEFAULT_DENOM));
+
+        // two grants pass counting on the same balance
+        for id in 0..2 {
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: Some("recipient".to_string()),
+                amount: Some(Uint128::from(80_u128)),
+                proposal_type: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+            for voter in ["addr1", "addr2"] {
+                let vote_msg = ExecuteMsg::Vote {
+                    proposal_id: id,
+                    approve: true,
+                };
+                execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+            }
+        }
+
+        // the first to pass reserves the funds, the second finds too little left
+        let committed = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, id| {
+            let ProposalRecord::Full(proposal) =
+                query_get_proposal(deps.as_ref(), mock_env(), id).unwrap()
+            else {
+                panic!("proposal {} is archived", id);
+            };
+            proposal.funds_committed
+        };
+        assert!(committed(&deps, 0));
+        assert!(!committed(&deps, 1));
+        let stats = query_treasury_stats(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(Uint128::from(100_u128), stats.balance);
+        assert_eq!(Uint128::from(80_u128), stats.committed);
+        assert_eq!(Uint128::from(20_u128), stats.available);
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            ExecuteMsg::Execute { proposal_id: 1 },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            ExecuteMsg::Execute { proposal_id: 0 },
+        )
+        .unwrap();
+
+        assert!(!committed(&deps, 0));
+        let stats = query_treasury_stats(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(Uint128::zero(), stats.committed);
+        assert_eq!(Uint128::from(80_u128), stats.granted);
+    }
+
+    #[test]
+    fn quadratic_voting() {
+        let mut deps = mock_dependencies();
+
+        let mut members = vec![Member {
+            address: Addr::unchecked("whale"),
+            weight: Uint128::from(100_u128),
+        }];
+        members.extend((1..=4).map(|i| Member {
+            address: Addr::unchecked(format!("addr{}", i)),
+            weight: Uint128::from(16_u128),
+        }));
+        let mut msg = instantiate_msg(members);
+        msg.quorum = Some(Decimal::percent(60));
+        msg.voting_strategy = Some(VotingStrategy::Quadratic);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+        let vote = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, voter: &str, approve| {
+            let vote_msg = ExecuteMsg::Vote {
+                proposal_id: 0,
+                approve,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+        };
+        let proposal = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+            let ProposalRecord::Full(proposal) =
+                query_get_proposal(deps.as_ref(), mock_env(), 0).unwrap()
+            else {
+                panic!("proposal was archived");
+            };
+            proposal
+        };
+
+        // the whale alone is 61% of the weight, enough for quorum but counted as 10 against
+        vote(&mut deps, "whale", false);
+        let ballot = BALLOTS
+            .load(&deps.storage, (0, &Addr::unchecked("whale")))
+            .unwrap();
+        assert_eq!(Uint128::from(100_u128), ballot.weight);
+        assert_eq!(Some(Uint128::from(10_u128)), ballot.counted);
+        assert!(proposal(&deps).quorum_reached);
+
+        // 48 weight in favour outvotes 100 against, 4 each to 10
+        for voter in ["addr1", "addr2"] {
+            vote(&mut deps, voter, true);
+        }
+        let current = proposal(&deps);
+        assert_eq!(Uint128::from(8_u128), current.votes_for);
+        assert_eq!(ProposalStatus::Open, current.status);
+        vote(&mut deps, "addr3", true);
+        let current = proposal(&deps);
+        assert_eq!(Uint128::from(12_u128), current.votes_for);
+        assert_eq!(Uint128::from(10_u128), current.votes_against);
+        assert_eq!(ProposalStatus::Passed, current.status);
+
+        // a changed ballot moves its counted weight across
+        vote(&mut deps, "whale", true);
+        let tally = TALLIES.load(&deps.storage, 0).unwrap();
+        assert_eq!(Uint128::from(22_u128), tally.votes_for);
+        assert!(tally.votes_against.is_zero());
+        assert_eq!(Some(Uint128::from(148_u128)), tally.weight_cast);
+
+        let mut msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }]);
+        msg.voting_strategy = Some(VotingStrategy::Conviction);
+        let err =
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+    }
+
+    #[test]
+    fn conviction_keeps_its_voting_period() {
+        let mut deps = mock_dependencies();
+        let members = ["addr1", "addr2"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::from(10_u128),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        msg.voting_strategy = Some(VotingStrategy::Conviction);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
         };
-        execute(deps.as_mut(), mock_env(), info.clone(), proposal_msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+        assert_eq!(
+            Some(100),
+            TALLIES.load(&deps.storage, 0).unwrap().voting_period
+        );
 
+        // a longer period configured mid-vote doesn't dilute ballots on the open proposal
+        CONFIG
+            .update(&mut deps.storage, |mut config| -> StdResult<_> {
+                config.voting_period = Some(1000);
+                Ok(config)
+            })
+            .unwrap();
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(50);
         let vote_msg = ExecuteMsg::Vote {
             proposal_id: 0,
             approve: true,
         };
+        execute(deps.as_mut(), later, mock_info("addr2", &[]), vote_msg).unwrap();
+        let tally = TALLIES.load(&deps.storage, 0).unwrap();
+        assert_eq!(Uint128::from(5_u128), tally.votes_for);
+    }
 
-        let res = execute(deps.as_mut(), mock_env(), info.clone(), vote_msg.clone()).unwrap();
-        assert_eq!(0, res.messages.len());
+    #[test]
+    fn execute_batch() {
+        // three passed grants of 60 against a balance of 150, only two of them fit
+        let setup = || {
+            let mut deps = mock_dependencies();
+            let members = ["addr1", "addr2"]
+                .iter()
+                .map(|address| Member {
+                    address: Addr::unchecked(*address),
+                    weight: Uint128::one(),
+                })
+                .collect();
+            instantiate(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("creator", &[]),
+                instantiate_msg(members),
+            )
+            .unwrap();
+            deps.querier
+                .update_balance(mock_env().contract.address, coins(150, DEFAULT_DENOM));
+            for id in 0..3 {
+                let propose = ExecuteMsg::Propose {
+                    frontend_id: None,
+                    title: "Some Title".parse().unwrap(),
+                    description: "Some Description".parse().unwrap(),
+                    recipient: Some(format!("recipient{}", id)),
+                    amount: Some(Uint128::from(60_u128)),
+                    proposal_type: None,
+                };
+                execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+                for voter in ["addr1", "addr2"] {
+                    let vote_msg = ExecuteMsg::Vote {
+                        proposal_id: id,
+                        approve: true,
+                    };
+                    execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+                }
+            }
+            deps
+        };
+        let batch = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, proposal_ids| {
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("addr1", &[]),
+                ExecuteMsg::ExecuteBatch { proposal_ids },
+            )
+        };
 
-        // Voting again should fail
-        execute(deps.as_mut(), mock_env(), info, vote_msg).unwrap_err();
+        // paid in id order whatever order they were asked for in
+        let mut deps = setup();
+        let res = batch(&mut deps, vec![1, 0, 1]).unwrap();
+        let recipients: Vec<_> = res
+            .messages
+            .iter()
+            .filter_map(|sub| match &sub.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, .. }) => Some(to_address.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["recipient0", "recipient1"], recipients);
+        assert_eq!(
+            Some(Uint128::zero()),
+            COMMITTED.may_load(&deps.storage).unwrap()
+        );
+
+        // the balance only drops after the batch, the third is checked against what the
+        // first two sent
+        let mut deps = setup();
+        let err = batch(&mut deps, vec![2, 1, 0]).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        let err = batch(&mut deps, vec![]).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
     }
 
     #[test]
-    fn execute_proposal() {
-        let sender = Addr::unchecked("sender");
+    fn proposal_indexes() {
+        let mut deps = mock_dependencies();
+        let members = ["addr1", "addr2"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.archive_after = Some(100);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(150, DEFAULT_DENOM));
+        // 0 is executed, 1 passed but still stored as open, 2 open
+        for (id, proposer, recipient, voters) in [
+            (0, "addr1", "recipienta", 2),
+            (1, "addr2", "recipientb", 2),
+            (2, "addr1", "recipientb", 0),
+        ] {
+            let propose = ExecuteMsg::Propose {
+                frontend_id: None,
+                title: "Some Title".parse().unwrap(),
+                description: "Some Description".parse().unwrap(),
+                recipient: Some(recipient.to_string()),
+                amount: Some(Uint128::from(10_u128)),
+                proposal_type: None,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(proposer, &[]), propose).unwrap();
+            for voter in ["addr1", "addr2"].iter().take(voters) {
+                let vote_msg = ExecuteMsg::Vote {
+                    proposal_id: id,
+                    approve: true,
+                };
+                execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote_msg).unwrap();
+            }
+        }
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            ExecuteMsg::Execute { proposal_id: 0 },
+        )
+        .unwrap();
+
+        let ids = |msg: QueryMsg| {
+            let res: ListProposalsResponse =
+                from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            let ids: Vec<_> = res.proposals.iter().map(|record| record.id()).collect();
+            (ids, res.next_cursor)
+        };
+        let by_status = |status| QueryMsg::ListProposalsByStatus {
+            status,
+            start_after: None,
+            limit: None,
+            cursor: None,
+        };
+        assert_eq!(vec![0], ids(by_status(ProposalStatus::Executed)).0);
+        assert_eq!(vec![1], ids(by_status(ProposalStatus::Passed)).0);
+        assert_eq!(vec![2], ids(by_status(ProposalStatus::Open)).0);
+        assert!(ids(by_status(ProposalStatus::Rejected)).0.is_empty());
+
+        let (proposed, _) = ids(QueryMsg::ListProposalsByProposer {
+            proposer: Addr::unchecked("addr1"),
+            start_after: None,
+            limit: None,
+            cursor: None,
+        });
+        assert_eq!(vec![0, 2], proposed);
+
+        let (first, cursor) = ids(QueryMsg::ListProposalsByRecipient {
+            recipient: Addr::unchecked("recipientb"),
+            start_after: None,
+            limit: Some(1),
+            cursor: None,
+        });
+        assert_eq!(vec![1], first);
+        let (rest, cursor) = ids(QueryMsg::ListProposalsByRecipient {
+            recipient: Addr::unchecked("recipientb"),
+            start_after: None,
+            limit: Some(1),
+            cursor,
+        });
+        assert_eq!(vec![2], rest);
+        assert_eq!(None, cursor);
+
+        // 1 is examined but no longer open, the page comes back empty with a cursor past it
+        let page = |cursor| QueryMsg::ListProposalsByStatus {
+            status: ProposalStatus::Open,
+            start_after: None,
+            limit: Some(1),
+            cursor,
+        };
+        let (first, cursor) = ids(page(None));
+        assert!(first.is_empty());
+        let (rest, cursor) = ids(page(cursor));
+        assert_eq!(vec![2], rest);
+        assert_eq!(None, cursor);
+
+        let under = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, status: ProposalStatus| {
+            PROPOSALS
+                .idx
+                .status
+                .prefix(status.as_str())
+                .keys(&deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap()
+        };
+        assert_eq!(vec![1, 2], under(&deps, ProposalStatus::Open));
+        assert_eq!(vec![0], under(&deps, ProposalStatus::Executed));
+
+        // executing moves the status entry rather than adding a second one
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr1", &[]),
+            ExecuteMsg::Execute { proposal_id: 1 },
+        )
+        .unwrap();
+        assert_eq!(vec![2], under(&deps, ProposalStatus::Open));
+        assert_eq!(vec![0, 1], under(&deps, ProposalStatus::Executed));
+
+        // archived proposals leave every index
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let archive_msg = ExecuteMsg::ArchiveProposals {
+            start_after: None,
+            limit: None,
+        };
+        let res = execute(deps.as_mut(), env, mock_info("addr1", &[]), archive_msg).unwrap();
+        assert_eq!(res.attributes[1], ("archived", "2"));
+        assert_eq!(vec![2], under(&deps, ProposalStatus::Open));
+        assert!(under(&deps, ProposalStatus::Executed).is_empty());
+        let by_address = |index: &AddrIndex, address: &str| {
+            index
+                .prefix(Addr::unchecked(address))
+                .keys(&deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap()
+        };
+        assert_eq!(vec![2], by_address(&PROPOSALS.idx.proposer, "addr1"));
+        assert!(by_address(&PROPOSALS.idx.proposer, "addr2").is_empty());
+        assert!(by_address(&PROPOSALS.idx.recipient, "recipienta").is_empty());
+        assert_eq!(vec![2], by_address(&PROPOSALS.idx.recipient, "recipientb"));
+    }
+
+    #[test]
+    fn removed_member_claim_window() {
+        let mut deps = mock_dependencies();
+        let members = ["addr1", "addr2", "addr3"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.removal_grace_period = Some(100);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // a distribution still owing each member 30
+        let distribution = RewardDistribution {
+            pool: Uint128::from(90_u128),
+            eligible: 3,
+            pending: 3,
+            paid: Uint128::zero(),
+            shadow: false,
+            reserved: false,
+        };
+        REWARD_DISTRIBUTIONS
+            .save(deps.as_mut().storage, 7, &distribution)
+            .unwrap();
+        for address in ["addr1", "addr2", "addr3"] {
+            let address = Addr::unchecked(address);
+            save_payout(deps.as_mut().storage, 7, &address, Uint128::from(30_u128)).unwrap();
+        }
+        let claim_until = claim_window(&deps.storage, &mock_env()).unwrap();
+        let remove = [Addr::unchecked("addr2"), Addr::unchecked("addr3")];
+        apply_member_changes(deps.as_mut().storage, 1, claim_until, &[], &remove, &[]).unwrap();
+
+        let claim = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env, sender| {
+            execute(
+                deps.as_mut(),
+                env,
+                mock_info(sender, &[]),
+                ExecuteMsg::ClaimRewards {},
+            )
+        };
+        let prune = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, env, address: &str| {
+            let msg = ExecuteMsg::PruneRemovedMember {
+                address: address.to_string(),
+            };
+            execute(deps.as_mut(), env, mock_info("anyone", &[]), msg)
+        };
+
+        // each member's payouts come from their own index entries
+        let other = RewardDistribution {
+            pending: 1,
+            ..distribution.clone()
+        };
+        REWARD_DISTRIBUTIONS
+            .save(deps.as_mut().storage, 8, &other)
+            .unwrap();
+        let addr1 = Addr::unchecked("addr1");
+        save_payout(deps.as_mut().storage, 8, &addr1, Uint128::from(5_u128)).unwrap();
+        assert_eq!(
+            vec![(7, Uint128::from(30_u128)), (8, Uint128::from(5_u128))],
+            pending_payouts(&deps.storage, &addr1).unwrap()
+        );
+        assert_eq!(
+            vec![(7, Uint128::from(30_u128))],
+            pending_payouts(&deps.storage, &Addr::unchecked("addr2")).unwrap()
+        );
+
+        // inside the window a removed member claims like anyone else
+        let res = claim(&mut deps, mock_env(), "addr2").unwrap();
+        assert_eq!(
+            vec![SubMsg::new(grant_transfer(
+                &Addr::unchecked("addr2"),
+                Uint128::from(30_u128),
+                DEFAULT_DENOM
+            ))],
+            res.messages
+        );
+        let err = claim(&mut deps, mock_env(), "addr2").unwrap_err();
+        assert!(matches!(err, ContractError::NothingToClaim {}));
+        assert!(PAYOUTS_BY_MEMBER
+            .prefix(&Addr::unchecked("addr2"))
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_none());
+        let err = prune(&mut deps, mock_env(), "addr3").unwrap_err();
+        assert!(matches!(err, ContractError::ClaimWindowOpen { .. }));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let err = claim(&mut deps, env.clone(), "addr3").unwrap_err();
+        assert!(matches!(err, ContractError::ClaimWindowClosed { .. }));
+        let res = prune(&mut deps, env.clone(), "addr3").unwrap();
+        assert!(res.messages.is_empty());
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "forfeited" && attr.value == "30"));
+        let window: Option<Deadline> = from_json(
+            query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetClaimWindow {
+                    address: Addr::unchecked("addr3"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(None, window);
+
+        // members never had a window to close
+        claim(&mut deps, env.clone(), "addr1").unwrap();
+        let err = prune(&mut deps, env, "addr1").unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMemberEntry { .. }));
+
+        let distribution = REWARD_DISTRIBUTIONS.load(&deps.storage, 7).unwrap();
+        assert_eq!(0, distribution.pending);
+        assert_eq!(Uint128::from(60_u128), distribution.paid);
+    }
+
+    #[test]
+    fn cw3_compatibility() {
+        let sender = Addr::unchecked("addr1");
         let mut app = App::new(|router, _api, storage| {
             router
                 .bank
-                .init_balance(storage, &sender, coins(100_000_000_000, DENOM))
+                .init_balance(storage, &sender, coins(100, DEFAULT_DENOM))
                 .unwrap();
         });
-
         let contract_id = app.store_code(dao_contract());
-
-        let members = vec![Member {
-            address: sender.clone(),
-            weight: Uint128::from(10_u128),
-        }];
-
+        let members = vec![
+            Member {
+                address: sender.clone(),
+                weight: Uint128::from(2_u128),
+            },
+            Member {
+                address: Addr::unchecked("addr2"),
+                weight: Uint128::one(),
+            },
+        ];
         let contract_addr = app
             .instantiate_contract(
                 contract_id,
                 sender.clone(),
-                &InstantiateMsg { members },
-                &[],
+                &instantiate_msg(members),
+                &coins(100, DEFAULT_DENOM),
                 "grant-dao",
                 None,
             )
             .unwrap();
 
-        // Propose
-        let proposal_msg = ExecuteMsg::Propose {
-            title: "Some Title".to_string(),
-            description: "Some Description".to_string(),
-            amount: Some(Uint128::from(100_u128)),
-            recipient: Some(Addr::unchecked("recipient_address")),
+        // sent in cw3's own shapes, which this contract's messages fail to parse
+        let transfer: CosmosMsg = BankMsg::Send {
+            to_address: "recipient".to_string(),
+            amount: coins(10, DEFAULT_DENOM),
+        }
+        .into();
+        let propose = |msgs, latest| Cw3ExecuteMsg::Propose {
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            msgs,
+            latest,
+        };
+        app.execute_contract(
+            sender.clone(),
+            contract_addr.clone(),
+            &propose(vec![transfer.clone()], Some(Expiration::AtHeight(100))),
+            &[],
+        )
+        .unwrap_err();
+        app.execute_contract(
+            sender.clone(),
+            contract_addr.clone(),
+            &propose(vec![transfer.clone()], None),
+            &[],
+        )
+        .unwrap();
+        let vote = |vote| Cw3ExecuteMsg::Vote {
+            proposal_id: 0,
+            vote,
         };
+        app.execute_contract(
+            Addr::unchecked("addr2"),
+            contract_addr.clone(),
+            &vote(cw3::Vote::Abstain),
+            &[],
+        )
+        .unwrap_err();
+        app.execute_contract(
+            sender.clone(),
+            contract_addr.clone(),
+            &vote(cw3::Vote::Yes),
+            &[],
+        )
+        .unwrap();
 
-        app.execute_contract(sender.clone(), contract_addr.clone(), &proposal_msg, &[])
+        let proposal: cw3::ProposalResponse = app
+            .wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::Proposal { proposal_id: 0 })
             .unwrap();
+        assert_eq!(cw3::Status::Passed, proposal.status);
+        assert_eq!(vec![transfer], proposal.msgs);
+        assert_eq!(sender, proposal.proposer);
+        assert_eq!(
+            cw3::ThresholdResponse::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::zero(),
+                total_weight: 3,
+            },
+            proposal.threshold
+        );
 
-        // Vote
-        let vote_msg = ExecuteMsg::Vote {
+        let ballot: cw3::VoteResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::Vote {
+                    proposal_id: 0,
+                    voter: sender.to_string(),
+                },
+            )
+            .unwrap();
+        let ballot = ballot.vote.unwrap();
+        assert_eq!((cw3::Vote::Yes, 2), (ballot.vote, ballot.weight));
+        // cw3's list queries answer under their own names, in cw3's shapes
+        let votes: cw3::VoteListResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::Cw3ListVotes {
+                    proposal_id: 0,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(vec![ballot], votes.votes);
+        let listed: cw3::ProposalListResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::Cw3ListProposals {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(vec![proposal.clone()], listed.proposals);
+
+        let voter = |address: &str| -> cw3::VoterResponse {
+            app.wrap()
+                .query_wasm_smart(
+                    &contract_addr,
+                    &QueryMsg::Voter {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(Some(1), voter("addr2").weight);
+        assert_eq!(None, voter("stranger").weight);
+        let voters: cw3::VoterListResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::ListVoters {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(2, voters.voters.len());
+
+        // cw3's `execute` is this contract's own; a proposal without messages is a text one
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        app.execute_contract(sender.clone(), contract_addr.clone(), &execute_msg, &[])
+            .unwrap();
+        app.execute_contract(
+            sender.clone(),
+            contract_addr.clone(),
+            &propose(vec![], None),
+            &[],
+        )
+        .unwrap();
+        let page: cw3::ProposalListResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::ReverseProposals {
+                    start_before: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let statuses: Vec<_> = page
+            .proposals
+            .iter()
+            .map(|proposal| (proposal.id, proposal.status))
+            .collect();
+        assert_eq!(
+            vec![(1, cw3::Status::Open), (0, cw3::Status::Executed)],
+            statuses
+        );
+        assert!(page.proposals[0].msgs.is_empty());
+    }
+
+    #[test]
+    fn private_drafts() {
+        let mut deps = mock_dependencies();
+        let mut msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }]);
+        msg.admin = Some("admin".to_string());
+        msg.private_drafts = Some(true);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let import = ExecuteMsg::ImportProposals {
+            proposals: vec![ImportedProposal {
+                title: "Audit of the bridge".parse().unwrap(),
+                description: "Undisclosed vulnerability".parse().unwrap(),
+                recipient: Addr::unchecked("recipient"),
+                amount: Uint128::zero(),
+                proposal_type: None,
+                submitted_at: mock_env().block.time,
+            }],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), import).unwrap();
+
+        let listed = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+            query_list_proposals(
+                deps.as_ref(),
+                mock_env(),
+                Order::Ascending,
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+            .proposals
+            .len()
+        };
+        query_get_proposal(deps.as_ref(), mock_env(), 0).unwrap_err();
+        assert_eq!(0, listed(&deps));
+
+        // members read it through an execute, which carries a sender
+        let read = ExecuteMsg::ReadProposal { proposal_id: 0 };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &[]),
+            read.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let res = execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), read).unwrap();
+        let draft: ProposalResponse = from_json(res.data.unwrap()).unwrap();
+        assert_eq!("Audit of the bridge", draft.title);
+
+        // public from the moment voting opens
+        let open = ExecuteMsg::OpenDraft { proposal_id: 0 };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), open).unwrap();
+        query_get_proposal(deps.as_ref(), mock_env(), 0).unwrap();
+        assert_eq!(1, listed(&deps));
+    }
+
+    #[test]
+    fn security_payouts() {
+        let mut deps = mock_dependencies();
+        let mut msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }]);
+        msg.security_lane = Some(SecurityLane {
+            committee: ["sec1", "sec2", "sec3"]
+                .iter()
+                .map(|address| Addr::unchecked(*address))
+                .collect(),
+            required_approvals: 2,
+            payout_cap: Uint128::from(50_u128),
+            budget: Uint128::from(80_u128),
+            disclosure_delay: 100,
+        });
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(1_000, DEFAULT_DENOM));
+
+        let authorize = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, sender, amount: u128| {
+            let msg = ExecuteMsg::AuthorizeSecurityPayout {
+                recipient: "researcher".to_string(),
+                amount: Uint128::from(amount),
+                report_hash: "report".parse().unwrap(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+        };
+        let approve = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, sender, payout_id| {
+            let msg = ExecuteMsg::ApproveSecurityPayout { payout_id };
+            execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+        };
+
+        let err = authorize(&mut deps, "addr1", 10).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = authorize(&mut deps, "sec1", 60).unwrap_err();
+        assert!(matches!(err, ContractError::AmountAboveCap { .. }));
+
+        // sent on the second approval, not before
+        let res = authorize(&mut deps, "sec1", 50).unwrap();
+        assert!(res.messages.is_empty());
+        let err = approve(&mut deps, "sec1", 0).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+        let res = approve(&mut deps, "sec2", 0).unwrap();
+        assert_eq!(
+            vec![SubMsg::new(grant_transfer(
+                &Addr::unchecked("researcher"),
+                Uint128::from(50_u128),
+                DEFAULT_DENOM
+            ))],
+            res.messages
+        );
+        approve(&mut deps, "sec3", 0).unwrap_err();
+
+        // 30 of the budget is left
+        authorize(&mut deps, "sec1", 40).unwrap();
+        let err = approve(&mut deps, "sec3", 1).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
+
+        let list = |env| query_list_security_payouts(deps.as_ref(), env, None, None, None).unwrap();
+        let page = list(mock_env());
+        assert_eq!(Uint128::from(30_u128), page.remaining_budget);
+        assert_eq!(2, page.payouts.len());
+        assert!(page.payouts[0].paid);
+        assert_eq!(None, page.payouts[0].payout);
+        assert!(!page.payouts[1].paid);
+        assert_eq!(None, page.payouts[1].disclosure);
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let disclosed = list(env).payouts[0].payout.clone().unwrap();
+        assert_eq!(Uint128::from(50_u128), disclosed.amount);
+        assert_eq!(
+            vec![Addr::unchecked("sec1"), Addr::unchecked("sec2")],
+            disclosed.approvals
+        );
+    }
+    #[test]
+    fn vote_delegation() {
+        let mut deps = mock_dependencies();
+        let members = ["addr1", "addr2", "addr3", "addr4"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let delegate = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, sender, delegate: &str| {
+            let msg = ExecuteMsg::Delegate {
+                delegate: delegate.to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+        };
+        let vote = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, sender, approve| {
+            let msg = ExecuteMsg::Vote {
+                proposal_id: 0,
+                approve,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+        };
+        delegate(&mut deps, "addr2", "addr1").unwrap();
+        delegate(&mut deps, "addr3", "addr1").unwrap();
+
+        // no cycles and no chains
+        for (sender, to) in [("addr1", "addr1"), ("addr4", "addr2"), ("addr1", "addr4")] {
+            delegate(&mut deps, sender, to).unwrap_err();
+        }
+        let err = delegate(&mut deps, "stranger", "addr1").unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let member = query_get_member(deps.as_ref(), Addr::unchecked("addr1")).unwrap();
+        assert_eq!(
+            vec![Addr::unchecked("addr2"), Addr::unchecked("addr3")],
+            member.delegators
+        );
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr4", &[]), propose).unwrap();
+
+        // a delegator who votes first keeps their weight, the delegate carries the rest
+        vote(&mut deps, "addr3", false).unwrap();
+        vote(&mut deps, "addr1", true).unwrap();
+        let tally = TALLIES.load(&deps.storage, 0).unwrap();
+        assert_eq!(Uint128::from(2_u128), tally.votes_for);
+        assert_eq!(Uint128::one(), tally.votes_against);
+        let err = vote(&mut deps, "addr2", false).unwrap_err();
+        assert!(matches!(err, ContractError::MemberAlreadyVoted {}));
+
+        // changing the delegate's vote moves what it carries
+        vote(&mut deps, "addr1", false).unwrap();
+        let tally = TALLIES.load(&deps.storage, 0).unwrap();
+        assert_eq!(Uint128::zero(), tally.votes_for);
+        assert_eq!(Uint128::from(3_u128), tally.votes_against);
+
+        let undelegate = ExecuteMsg::Undelegate {};
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr2", &[]),
+            undelegate.clone(),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr2", &[]),
+            undelegate,
+        )
+        .unwrap_err();
+        let member = query_get_member(deps.as_ref(), Addr::unchecked("addr2")).unwrap();
+        assert_eq!(None, member.delegate);
+        // what was already cast for them stays cast
+        let err = vote(&mut deps, "addr2", true).unwrap_err();
+        assert!(matches!(err, ContractError::MemberAlreadyVoted {}));
+    }
+
+    #[test]
+    fn redelegation_is_not_counted_twice() {
+        let mut deps = mock_dependencies();
+        let members = ["addr1", "addr2", "addr3", "addr4"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::Signal {}),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr4", &[]), propose).unwrap();
+
+        let send = |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, sender, msg| {
+            execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+        };
+        let delegate = |to: &str| ExecuteMsg::Delegate {
+            delegate: to.to_string(),
+        };
+        let vote = |approve| ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve,
+        };
+        let votes = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+            let tally = TALLIES.load(&deps.storage, 0).unwrap();
+            (tally.votes_for.u128(), tally.votes_against.u128())
+        };
+
+        // addr1's weight goes with addr2's ballot, moving to addr3 does not cast it again
+        send(&mut deps, "addr1", delegate("addr2")).unwrap();
+        send(&mut deps, "addr2", vote(true)).unwrap();
+        send(&mut deps, "addr1", ExecuteMsg::Undelegate {}).unwrap();
+        send(&mut deps, "addr1", delegate("addr3")).unwrap();
+        send(&mut deps, "addr3", vote(true)).unwrap();
+        assert_eq!((3, 0), votes(&deps));
+
+        // a changed ballot goes by current delegations, addr2 gives addr1's weight back and
+        // addr3, addr1's delegate now, picks it up when it next votes
+        send(&mut deps, "addr2", vote(false)).unwrap();
+        assert_eq!((1, 1), votes(&deps));
+        send(&mut deps, "addr3", vote(false)).unwrap();
+        assert_eq!((0, 3), votes(&deps));
+        let err = send(&mut deps, "addr1", vote(true)).unwrap_err();
+        assert!(matches!(err, ContractError::MemberAlreadyVoted {}));
+        // addr2 changing back does not take it from addr3
+        send(&mut deps, "addr2", vote(true)).unwrap();
+        assert_eq!((1, 2), votes(&deps));
+    }
+    #[test]
+    fn signaling_proposals() {
+        let mut deps = mock_dependencies();
+        let msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = |amount| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Adopt the code of conduct".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount,
+            proposal_type: Some(ProposalType::Signal {}),
+        };
+        let info = mock_info("addr1", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            propose(Some(Uint128::one())),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+        execute(deps.as_mut(), mock_env(), info.clone(), propose(None)).unwrap();
+        let vote = ExecuteMsg::Vote {
             proposal_id: 0,
             approve: true,
         };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote).unwrap();
 
-        app.execute_contract(sender.clone(), contract_addr.clone(), &vote_msg, &[])
+        // the treasury is empty, which does not matter to a proposal that spends nothing
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        let res = execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+        assert!(res.messages.is_empty());
+        let signal = res
+            .events
+            .iter()
+            .find(|event| event.ty == "signal")
             .unwrap();
+        assert!(signal
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "votes_for" && attr.value == "1"));
+        let proposal = PROPOSALS.load(&deps.storage, 0).unwrap();
+        assert_eq!(ProposalStatus::Executed, proposal.status);
+    }
+    #[test]
+    fn swap_proposals() {
+        let members = vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }];
+        let swap = |min_receive: u128| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Diversify into stables".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::Swap {
+                offer: Uint128::from(400_u128),
+                ask_denom: "uusdc".to_string(),
+                min_receive: Uint128::from(min_receive),
+            }),
+        };
+        let info = mock_info("addr1", &[]);
+
+        // only chains with a router take swap proposals
+        let mut deps = mock_dependencies();
+        let msg = instantiate_msg(members.clone());
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), swap(390)).unwrap_err();
+
+        let mut deps = mock_dependencies();
+        let mut msg = instantiate_msg(members);
+        msg.dex_router = Some(Addr::unchecked("router"));
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(1_000, DEFAULT_DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), swap(0)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+        execute(deps.as_mut(), mock_env(), info.clone(), swap(390)).unwrap();
+        let vote = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote).unwrap();
+        // the offer is reserved like a grant while the proposal passes
+        assert_eq!(
+            Uint128::from(400_u128),
+            COMMITTED.load(&deps.storage).unwrap()
+        );
 
-        // Execute the proposal
-        // Should fail because no funds in contract
         let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
-        app.execute_contract(sender.clone(), contract_addr.clone(), &execute_msg, &[])
-            .unwrap_err();
+        let res = execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+        let expected = swap_msg(
+            &Addr::unchecked("router"),
+            Uint128::from(400_u128),
+            DEFAULT_DENOM,
+            "uusdc",
+            Uint128::from(390_u128),
+        )
+        .unwrap();
+        assert_eq!(vec![SubMsg::new(expected)], res.messages);
+    }
+    #[test]
+    fn grant_batches() {
+        let mut deps = mock_dependencies();
+        let msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(100, DEFAULT_DENOM));
 
-        // Send funds to contract so that proposal can be executed
-        app.send_tokens(sender.clone(), contract_addr.clone(), &coins(100, DENOM))
-            .unwrap();
+        let payouts: Vec<_> = [("grantee1", 60_u128), ("grantee2", 50)]
+            .iter()
+            .map(|(recipient, amount)| GrantPayout {
+                recipient: Addr::unchecked(*recipient),
+                amount: Uint128::from(*amount),
+            })
+            .collect();
+        let propose = |amount| ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Round one".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount,
+            proposal_type: Some(ProposalType::GrantBatch {
+                payouts: payouts.clone(),
+            }),
+        };
+        let info = mock_info("addr1", &[]);
+        // the amount, when given, has to add up
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            propose(Some(Uint128::from(100_u128))),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidInput(_)));
+        execute(deps.as_mut(), mock_env(), info.clone(), propose(None)).unwrap();
+        assert_eq!(
+            Uint128::from(110_u128),
+            PROPOSALS.load(&deps.storage, 0).unwrap().amount
+        );
+        let vote = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote).unwrap();
 
-        // Executing the proposal should succeed now
-        app.execute_contract(sender.clone(), contract_addr.clone(), &execute_msg, &[])
-            .unwrap();
+        // nothing goes out while the treasury cannot cover every payout
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        let err =
+            execute(deps.as_mut(), mock_env(), info.clone(), execute_msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
 
-        // Check balance of recipient
-        let balance = app
-            .wrap()
-            .query_balance(Addr::unchecked("recipient_address"), DENOM)
-            .unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(110, DEFAULT_DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+        let expected: Vec<_> = payouts
+            .iter()
+            .map(|payout| {
+                SubMsg::new(grant_transfer(
+                    &payout.recipient,
+                    payout.amount,
+                    DEFAULT_DENOM,
+                ))
+            })
+            .collect();
+        assert_eq!(expected, res.messages);
+        let by_recipient = QueryMsg::ListProposalsByRecipient {
+            recipient: Addr::unchecked("grantee2"),
+            start_after: None,
+            limit: None,
+            cursor: None,
+        };
+        let listed: ListProposalsResponse =
+            from_json(query(deps.as_ref(), mock_env(), by_recipient).unwrap()).unwrap();
+        assert_eq!(1, listed.proposals.len());
+    }
+    #[test]
+    fn export_ballots() {
+        let mut deps = mock_dependencies();
+        let members = ["addr1", "addr2", "addr3"]
+            .iter()
+            .map(|address| Member {
+                address: Addr::unchecked(*address),
+                weight: Uint128::one(),
+            })
+            .collect();
+        let mut msg = instantiate_msg(members);
+        msg.voting_period = Some(100);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Some Title".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::Signal {}),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("addr1", &[]), propose).unwrap();
+        for (voter, approve) in [("addr3", true), ("addr1", false)] {
+            let vote = ExecuteMsg::Vote {
+                proposal_id: 0,
+                approve,
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), vote).unwrap();
+        }
+
+        let export = |env, start_after: Option<&str>| {
+            query_export_ballots(
+                deps.as_ref(),
+                env,
+                0,
+                start_after.map(Addr::unchecked),
+                Some(1),
+            )
+        };
+        // nothing is exported while the outcome can still change
+        export(mock_env(), None).unwrap_err();
+
+        let mut closed = mock_env();
+        closed.block.height += 100;
+        closed.block.time = closed.block.time.plus_seconds(100);
+        let first = export(closed.clone(), None).unwrap();
+        assert_eq!(ProposalStatus::Rejected, first.status);
+        assert_eq!(Addr::unchecked("addr1"), first.ballots[0].voter);
+        assert!(!first.ballots[0].approve);
+        let rest = export(closed.clone(), Some("addr1")).unwrap();
+        assert_eq!(Addr::unchecked("addr3"), rest.ballots[0].voter);
+        assert_eq!(Uint128::one(), rest.ballots[0].counted);
+        assert!(export(closed, Some("addr3")).unwrap().ballots.is_empty());
+    }
+    #[test]
+    fn cw20_treasury() {
+        let mut deps = mock_dependencies();
+        let mut msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }]);
+        msg.cw20_tokens = Some(vec![Addr::unchecked("stabletoken")]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(100, DEFAULT_DENOM));
+
+        // the token contract itself calls Receive, on behalf of whoever sent the tokens
+        let receive = |amount: u128| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "donor".to_string(),
+                amount: Uint128::from(amount),
+                msg: Binary::default(),
+            })
+        };
+        let token = mock_info("stabletoken", &[]);
+        for amount in [300, 200] {
+            execute(deps.as_mut(), mock_env(), token.clone(), receive(amount)).unwrap();
+        }
+        execute(deps.as_mut(), mock_env(), token, receive(0)).unwrap_err();
+        // an unlisted contract cannot credit itself whatever it claims
+        let spoofed = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("spamtoken", &[]),
+            receive(1_000),
+        )
+        .unwrap_err();
+        assert!(matches!(spoofed, ContractError::Unauthorized {}));
+
+        let balances = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| {
+            query_list_treasury_balances(deps.as_ref(), mock_env(), None, None).unwrap()
+        };
+        let listed = balances(&deps);
+        assert_eq!(coins(100, DEFAULT_DENOM), listed.native);
+        assert_eq!(
+            vec![Cw20Balance {
+                token: Addr::unchecked("stabletoken"),
+                amount: Uint128::from(500_u128),
+            }],
+            listed.cw20
+        );
+
+        // a proposal transferring tokens out debits the ledger
+        let transfer = WasmMsg::Execute {
+            contract_addr: "stabletoken".to_string(),
+            msg: Binary::from(br#"{"transfer":{"recipient":"grantee","amount":"120"}}"#.to_vec()),
+            funds: vec![],
+        };
+        let propose = ExecuteMsg::Propose {
+            frontend_id: None,
+            title: "Pay in stables".parse().unwrap(),
+            description: "Some Description".parse().unwrap(),
+            recipient: None,
+            amount: None,
+            proposal_type: Some(ProposalType::Custom {
+                msgs: vec![transfer.into()],
+            }),
+        };
+        let info = mock_info("addr1", &[]);
+        execute(deps.as_mut(), mock_env(), info.clone(), propose).unwrap();
+        let vote = ExecuteMsg::Vote {
+            proposal_id: 0,
+            approve: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), vote).unwrap();
+        let execute_msg = ExecuteMsg::Execute { proposal_id: 0 };
+        execute(deps.as_mut(), mock_env(), info, execute_msg).unwrap();
+        assert_eq!(Uint128::from(380_u128), balances(&deps).cw20[0].amount);
+    }
+    #[test]
+    fn unvested_funds_stay_reserved() {
+        let mut deps = mock_dependencies();
+        let msg = instantiate_msg(vec![Member {
+            address: Addr::unchecked("addr1"),
+            weight: Uint128::one(),
+        }]);
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(100, DEFAULT_DENOM));
+
+        let info = mock_info("addr1", &[]);
+        let grant =
+            |deps: &mut cosmwasm_std::OwnedDeps<_, _, _>, id, recipient: &str, proposal_type| {
+                let propose = ExecuteMsg::Propose {
+                    frontend_id: None,
+                    title: "Some Title".parse().unwrap(),
+                    description: "Some Description".parse().unwrap(),
+                    recipient: Some(recipient.to_string()),
+                    amount: Some(Uint128::from(if id == 0 { 100_u128 } else { 80 })),
+                    proposal_type,
+                };
+                execute(deps.as_mut(), mock_env(), info.clone(), propose).unwrap();
+                let vote = ExecuteMsg::Vote {
+                    proposal_id: id,
+                    approve: true,
+                };
+                execute(deps.as_mut(), mock_env(), info.clone(), vote).unwrap();
+                let execute_msg = ExecuteMsg::Execute { proposal_id: id };
+                execute(deps.as_mut(), mock_env(), info.clone(), execute_msg)
+            };
+        let vested = Some(ProposalType::VestedGrant {
+            upfront: Uint128::from(20_u128),
+            duration: 100,
+        });
+        grant(&mut deps, 0, "grantee", vested).unwrap();
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(80, DEFAULT_DENOM));
+        let stats = query_treasury_stats(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(Uint128::from(80_u128), stats.committed);
+        assert_eq!(Uint128::zero(), stats.available);
+
+        // the 80 still owed to the grantee cannot fund another grant
+        let err = grant(&mut deps, 1, "other", None).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientFunds {}));
 
-        assert_eq!(balance, coin(100, DENOM));
+        // each claim draws the reservation down by what it pays
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(50);
+        let claim = ExecuteMsg::ClaimVested { proposal_id: 0 };
+        execute(deps.as_mut(), env, mock_info("grantee", &[]), claim).unwrap();
+        let stats = query_treasury_stats(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(Uint128::from(40_u128), stats.committed);
     }
 }
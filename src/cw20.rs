@@ -0,0 +1,66 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{from_json, Addr, Binary, CosmosMsg, Deps, StdResult, Uint128, WasmMsg};
+
+/// What a cw20 token contract sends the DAO when tokens are `Send` to it, `sender` being who
+/// sent them
+#[cw_serde]
+pub struct Cw20ReceiveMsg {
+    pub sender: String,
+    pub amount: Uint128,
+    pub msg: Binary,
+}
+
+/// The cw20 calls that move tokens out of the sender's own balance
+#[cw_serde]
+enum Cw20ExecuteMsg {
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+    },
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    Burn {
+        amount: Uint128,
+    },
+}
+
+/// Token contract and amount `msg` moves out of the DAO's cw20 holdings, if it is such a call
+pub fn cw20_outgoing(msg: &CosmosMsg) -> Option<(&str, Uint128)> {
+    let (contract_addr, msg) = match msg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        }) => (contract_addr, msg),
+        _ => return None,
+    };
+    let amount = match from_json(msg).ok()? {
+        Cw20ExecuteMsg::Transfer { amount, .. }
+        | Cw20ExecuteMsg::Send { amount, .. }
+        | Cw20ExecuteMsg::Burn { amount } => amount,
+    };
+    Some((contract_addr, amount))
+}
+
+/// The subset of the cw20 query interface the treasury needs
+#[cw_serde]
+enum Cw20QueryMsg {
+    Balance { address: String },
+}
+
+#[cw_serde]
+struct Cw20BalanceResponse {
+    balance: Uint128,
+}
+
+/// What `token` says `address` holds
+pub fn cw20_balance(deps: Deps, token: &Addr, address: &Addr) -> StdResult<Uint128> {
+    let response: Cw20BalanceResponse = deps.querier.query_wasm_smart(
+        token,
+        &Cw20QueryMsg::Balance {
+            address: address.to_string(),
+        },
+    )?;
+    Ok(response.balance)
+}
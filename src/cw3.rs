@@ -0,0 +1,126 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, CosmosMsg, Decimal, Uint128};
+use cw_utils::Expiration;
+
+use crate::bounded::{Description, Title};
+
+/// The cw3 message shapes that differ from this contract's own, accepted alongside them so
+/// wallets and multisig UIs that only speak cw3 work unchanged. cw3's `execute` and `close`
+/// already parse as `ExecuteMsg::Execute` and `ExecuteMsg::Close`
+#[cw_serde]
+pub enum Cw3ExecuteMsg {
+    /// Opens a `Custom` proposal dispatching `msgs`, or an `Announce` one without any.
+    /// `latest` must be unset, proposals always close after the configured `voting_period`
+    Propose {
+        title: Title,
+        description: Description,
+        msgs: Vec<CosmosMsg>,
+        latest: Option<Expiration>,
+    },
+    /// `veto` counts as `no`, abstaining is not supported
+    Vote { proposal_id: u64, vote: Vote },
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum Vote {
+    Yes,
+    No,
+    Abstain,
+    Veto,
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum Status {
+    Pending,
+    Open,
+    Rejected,
+    Passed,
+    Executed,
+}
+
+/// Always `ThresholdQuorum`, the share of weight cast in favour and the share of the whole
+/// electorate that has to vote
+#[cw_serde]
+pub enum ThresholdResponse {
+    AbsoluteCount {
+        weight: u64,
+        total_weight: u64,
+    },
+    AbsolutePercentage {
+        percentage: Decimal,
+        total_weight: u64,
+    },
+    ThresholdQuorum {
+        threshold: Decimal,
+        quorum: Decimal,
+        total_weight: u64,
+    },
+}
+
+#[cw_serde]
+pub enum Denom {
+    Native(String),
+    Cw20(Addr),
+}
+
+#[cw_serde]
+pub struct DepositInfo {
+    pub amount: Uint128,
+    pub denom: Denom,
+    pub refund_failed_proposals: bool,
+}
+
+/// `msgs` are what executing the proposal would dispatch, the transfer for a grant
+#[cw_serde]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub title: String,
+    pub description: String,
+    pub msgs: Vec<CosmosMsg>,
+    pub status: Status,
+    pub expires: Expiration,
+    pub threshold: ThresholdResponse,
+    pub proposer: Addr,
+    pub deposit: Option<DepositInfo>,
+}
+
+#[cw_serde]
+pub struct ProposalListResponse {
+    pub proposals: Vec<ProposalResponse>,
+}
+
+#[cw_serde]
+pub struct VoteInfo {
+    pub proposal_id: u64,
+    pub voter: String,
+    pub vote: Vote,
+    pub weight: u64,
+}
+
+#[cw_serde]
+pub struct VoteListResponse {
+    pub votes: Vec<VoteInfo>,
+}
+
+#[cw_serde]
+pub struct VoteResponse {
+    pub vote: Option<VoteInfo>,
+}
+
+#[cw_serde]
+pub struct VoterResponse {
+    pub weight: Option<u64>,
+}
+
+#[cw_serde]
+pub struct VoterDetail {
+    pub addr: String,
+    pub weight: u64,
+}
+
+#[cw_serde]
+pub struct VoterListResponse {
+    pub voters: Vec<VoterDetail>,
+}
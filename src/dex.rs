@@ -0,0 +1,61 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{to_json_binary, Addr, Coin, CosmosMsg, Decimal, StdResult, Uint128, WasmMsg};
+
+/// The subset of an Astroport-style router's interface a swap proposal calls
+#[cw_serde]
+pub enum RouterExecuteMsg {
+    ExecuteSwapOperations {
+        operations: Vec<SwapOperation>,
+        /// The router fails the whole swap when it would return less
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+        max_spread: Option<Decimal>,
+    },
+}
+
+#[cw_serde]
+pub enum SwapOperation {
+    AstroSwap {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+    },
+}
+
+#[cw_serde]
+pub enum AssetInfo {
+    NativeToken { denom: String },
+    Token { contract_addr: String },
+}
+
+/// Single-hop swap of `offer` `denom` into `ask_denom` through `router`, proceeds returned to
+/// the sender
+pub fn swap_msg(
+    router: &Addr,
+    offer: Uint128,
+    denom: &str,
+    ask_denom: &str,
+    min_receive: Uint128,
+) -> StdResult<CosmosMsg> {
+    let msg = RouterExecuteMsg::ExecuteSwapOperations {
+        operations: vec![SwapOperation::AstroSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            },
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: ask_denom.to_string(),
+            },
+        }],
+        minimum_receive: Some(min_receive),
+        to: None,
+        max_spread: None,
+    };
+    Ok(WasmMsg::Execute {
+        contract_addr: router.to_string(),
+        msg: to_json_binary(&msg)?,
+        funds: vec![Coin {
+            denom: denom.to_string(),
+            amount: offer,
+        }],
+    }
+    .into())
+}
@@ -1,26 +1,203 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
+use cw_utils::PaymentError;
 use thiserror::Error;
 
+/// Every message starts with the variant's `code()` in brackets so clients can map failures
+/// without matching on the wording
 #[derive(Error, Debug)]
 pub enum ContractError {
-    #[error("{0}")]
+    #[error("[1] {0}")]
     Std(#[from] StdError),
 
-    #[error("Unauthorized")]
+    #[error("[2] Unauthorized")]
     Unauthorized {},
 
-    #[error("Invalid input")]
+    #[error("[3] Invalid input")]
     InvalidInput(String),
 
-    #[error("Already Executed")]
-    AlreadyExecuted {},
+    #[error("[4] Already Executed at height {height}")]
+    AlreadyExecuted { height: u64, tx_index: Option<u32> },
 
-    #[error("Proposal does not exist")]
+    #[error("[5] Proposal does not exist")]
     ProposalDoesNotExist {},
 
-    #[error("Insufficient funds")]
+    #[error("[6] Insufficient funds")]
     InsufficientFunds {},
 
-    #[error("Member already voted")]
+    #[error("[7] Member already voted")]
     MemberAlreadyVoted {},
+
+    #[error("[8] Member is suspended")]
+    MemberSuspended {},
+
+    #[error("[9] Invalid member entry {address}: {reason}")]
+    InvalidMemberEntry { address: String, reason: String },
+
+    #[error("[10] Key rotation is not executable before {executable_at}")]
+    KeyRotationPending { executable_at: String },
+
+    #[error("[11] Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("[12] Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("[13] Notification failed: {reason}")]
+    NotificationFailed { reason: String },
+
+    #[error("[14] Proposal is decided and can no longer change")]
+    ProposalLocked {},
+
+    #[error("[15] Voting has closed")]
+    VotingClosed {},
+
+    #[error("[16] Voting is open until {ends_at}")]
+    VotingOpen { ends_at: String },
+
+    #[error("[17] Invariant violated: {invariant}")]
+    InvariantViolated { invariant: String },
+
+    #[error("[18] {0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("[19] Timelocked until {executable_at}")]
+    Timelocked { executable_at: String },
+
+    #[error("[20] Proposal has no description")]
+    EmptyDescription {},
+
+    #[error("[21] Amount {amount} exceeds the per-proposal cap of {cap}")]
+    AmountAboveCap { amount: Uint128, cap: Uint128 },
+
+    #[error("[22] Claim window closed at {closed_at}")]
+    ClaimWindowClosed { closed_at: String },
+
+    #[error("[23] Claim window is open until {closes_at}")]
+    ClaimWindowOpen { closes_at: String },
+}
+
+impl ContractError {
+    /// Stable numeric code, never reused once assigned
+    pub fn code(&self) -> u32 {
+        match self {
+            ContractError::Std(_) => 1,
+            ContractError::Unauthorized {} => 2,
+            ContractError::InvalidInput(_) => 3,
+            ContractError::AlreadyExecuted { .. } => 4,
+            ContractError::ProposalDoesNotExist {} => 5,
+            ContractError::InsufficientFunds {} => 6,
+            ContractError::MemberAlreadyVoted {} => 7,
+            ContractError::MemberSuspended {} => 8,
+            ContractError::InvalidMemberEntry { .. } => 9,
+            ContractError::KeyRotationPending { .. } => 10,
+            ContractError::NothingToClaim {} => 11,
+            ContractError::UnknownReplyId { .. } => 12,
+            ContractError::NotificationFailed { .. } => 13,
+            ContractError::ProposalLocked {} => 14,
+            ContractError::VotingClosed {} => 15,
+            ContractError::VotingOpen { .. } => 16,
+            ContractError::InvariantViolated { .. } => 17,
+            ContractError::Payment(_) => 18,
+            ContractError::Timelocked { .. } => 19,
+            ContractError::EmptyDescription {} => 20,
+            ContractError::AmountAboveCap { .. } => 21,
+            ContractError::ClaimWindowClosed { .. } => 22,
+            ContractError::ClaimWindowOpen { .. } => 23,
+        }
+    }
+
+    /// Stable string code, for clients that prefer names over numbers
+    pub fn code_name(&self) -> &'static str {
+        match self {
+            ContractError::Std(_) => "std",
+            ContractError::Unauthorized {} => "unauthorized",
+            ContractError::InvalidInput(_) => "invalid_input",
+            ContractError::AlreadyExecuted { .. } => "already_executed",
+            ContractError::ProposalDoesNotExist {} => "proposal_does_not_exist",
+            ContractError::InsufficientFunds {} => "insufficient_funds",
+            ContractError::MemberAlreadyVoted {} => "member_already_voted",
+            ContractError::MemberSuspended {} => "member_suspended",
+            ContractError::InvalidMemberEntry { .. } => "invalid_member_entry",
+            ContractError::KeyRotationPending { .. } => "key_rotation_pending",
+            ContractError::NothingToClaim {} => "nothing_to_claim",
+            ContractError::UnknownReplyId { .. } => "unknown_reply_id",
+            ContractError::NotificationFailed { .. } => "notification_failed",
+            ContractError::ProposalLocked {} => "proposal_locked",
+            ContractError::VotingClosed {} => "voting_closed",
+            ContractError::VotingOpen { .. } => "voting_open",
+            ContractError::InvariantViolated { .. } => "invariant_violated",
+            ContractError::Payment(_) => "payment",
+            ContractError::Timelocked { .. } => "timelocked",
+            ContractError::EmptyDescription {} => "empty_description",
+            ContractError::AmountAboveCap { .. } => "amount_above_cap",
+            ContractError::ClaimWindowClosed { .. } => "claim_window_closed",
+            ContractError::ClaimWindowOpen { .. } => "claim_window_open",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_carries_code() {
+        let errors = vec![
+            ContractError::Std(StdError::generic_err("boom")),
+            ContractError::Unauthorized {},
+            ContractError::InvalidInput("bad".to_string()),
+            ContractError::AlreadyExecuted {
+                height: 1,
+                tx_index: None,
+            },
+            ContractError::ProposalDoesNotExist {},
+            ContractError::InsufficientFunds {},
+            ContractError::MemberAlreadyVoted {},
+            ContractError::MemberSuspended {},
+            ContractError::InvalidMemberEntry {
+                address: "addr".to_string(),
+                reason: "bad".to_string(),
+            },
+            ContractError::KeyRotationPending {
+                executable_at: "0".to_string(),
+            },
+            ContractError::NothingToClaim {},
+            ContractError::UnknownReplyId { id: 9 },
+            ContractError::NotificationFailed {
+                reason: "boom".to_string(),
+            },
+            ContractError::ProposalLocked {},
+            ContractError::VotingClosed {},
+            ContractError::VotingOpen {
+                ends_at: "0".to_string(),
+            },
+            ContractError::InvariantViolated {
+                invariant: "total weight".to_string(),
+            },
+            ContractError::Payment(PaymentError::NoFunds {}),
+            ContractError::Timelocked {
+                executable_at: "0".to_string(),
+            },
+            ContractError::EmptyDescription {},
+            ContractError::AmountAboveCap {
+                amount: Uint128::new(2),
+                cap: Uint128::one(),
+            },
+            ContractError::ClaimWindowClosed {
+                closed_at: "0".to_string(),
+            },
+            ContractError::ClaimWindowOpen {
+                closes_at: "0".to_string(),
+            },
+        ];
+
+        let mut codes = vec![];
+        for err in errors {
+            assert!(err.to_string().starts_with(&format!("[{}] ", err.code())));
+            codes.push(err.code());
+        }
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(23, codes.len());
+    }
 }
@@ -1,4 +1,12 @@
+pub mod bounded;
+pub mod clock;
 pub mod contract;
+pub mod cw20;
+pub mod cw3;
+pub mod dex;
 pub mod error;
+pub mod membership;
 pub mod msg;
+pub mod querier;
 pub mod state;
+pub mod tally;
@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Deps, StdResult, Storage, Uint128};
+
+use crate::state::{MEMBERS, ROTATED_FROM, TOTAL_WEIGHT, VOTING_POWER};
+
+/// Where voting weight comes from, fixed for each proposal when it is opened
+#[cw_serde]
+#[derive(Default)]
+pub enum MembershipSource {
+    /// The contract's own member list and its weight snapshots
+    #[default]
+    Internal,
+    /// A cw4 group contract, queried at the height each proposal opened in
+    Cw4 { group: Addr },
+}
+
+/// Sent by a cw4 group to its hooks whenever its members change
+#[cw_serde]
+pub struct MemberChangedHookMsg {
+    pub diffs: Vec<MemberDiff>,
+}
+
+/// `None` on either side for a member joining or leaving the group
+#[cw_serde]
+pub struct MemberDiff {
+    pub key: String,
+    pub old: Option<u64>,
+    pub new: Option<u64>,
+}
+
+/// Weight lookups behind a `MembershipSource`, voting only ever goes through these
+pub(crate) trait MembershipBackend {
+    /// `address`'s weight as of the end of block `height`, `None` if it had none to vote with
+    fn weight_at(&self, deps: Deps, address: &Addr, height: u64) -> StdResult<Option<Uint128>>;
+
+    /// Weight of the whole electorate as of the current block
+    fn total_weight(&self, deps: Deps) -> StdResult<Uint128>;
+}
+
+struct Internal;
+
+struct Cw4 {
+    group: Addr,
+}
+
+impl MembershipBackend for Internal {
+    // members removed since the proposal opened lose their say in it
+    fn weight_at(&self, deps: Deps, address: &Addr, height: u64) -> StdResult<Option<Uint128>> {
+        if !MEMBERS.has(deps.storage, address) {
+            return Ok(None);
+        }
+        snapshot_weight(deps.storage, address, height)
+    }
+
+    fn total_weight(&self, deps: Deps) -> StdResult<Uint128> {
+        TOTAL_WEIGHT.load(deps.storage)
+    }
+}
+
+/// The subset of the cw4 group query interface voting needs
+#[cw_serde]
+enum Cw4QueryMsg {
+    Member {
+        addr: String,
+        at_height: Option<u64>,
+    },
+    TotalWeight {
+        at_height: Option<u64>,
+    },
+}
+
+#[cw_serde]
+struct Cw4MemberResponse {
+    weight: Option<u64>,
+}
+
+#[cw_serde]
+struct Cw4TotalWeightResponse {
+    weight: u64,
+}
+
+impl MembershipBackend for Cw4 {
+    // cw4 snapshots read as of the start of a block, the one after `height` starts where it
+    // ended
+    fn weight_at(&self, deps: Deps, address: &Addr, height: u64) -> StdResult<Option<Uint128>> {
+        let member: Cw4MemberResponse = deps.querier.query_wasm_smart(
+            &self.group,
+            &Cw4QueryMsg::Member {
+                addr: address.to_string(),
+                at_height: Some(height + 1),
+            },
+        )?;
+        Ok(member
+            .weight
+            .filter(|weight| *weight > 0)
+            .map(Uint128::from))
+    }
+
+    fn total_weight(&self, deps: Deps) -> StdResult<Uint128> {
+        let total: Cw4TotalWeightResponse = deps
+            .querier
+            .query_wasm_smart(&self.group, &Cw4QueryMsg::TotalWeight { at_height: None })?;
+        Ok(total.weight.into())
+    }
+}
+
+impl MembershipSource {
+    pub(crate) fn backend(&self) -> Box<dyn MembershipBackend> {
+        match self {
+            MembershipSource::Internal => Box::new(Internal),
+            MembershipSource::Cw4 { group } => Box::new(Cw4 {
+                group: group.clone(),
+            }),
+        }
+    }
+}
+
+/// `voter`'s weight as of the end of block `height`, following key rotations back to the
+/// address that held it then
+fn snapshot_weight(storage: &dyn Storage, voter: &Addr, height: u64) -> StdResult<Option<Uint128>> {
+    let mut address = voter.clone();
+    let mut seen = HashSet::new();
+    loop {
+        if let Some(weight) = VOTING_POWER.may_load_at_height(storage, &address, height + 1)? {
+            return Ok(Some(weight));
+        }
+        // a key can be rotated back to an address it once left
+        if !seen.insert(address.clone()) {
+            return Ok(None);
+        }
+        match ROTATED_FROM.may_load(storage, &address)? {
+            Some(previous) => address = previous,
+            None => return Ok(None),
+        }
+    }
+}
@@ -1,44 +1,836 @@
-use crate::state::{Member, Proposal};
+use crate::bounded::{
+    ContentHash, Description, DisplayName, FrontendId, GroupId, Reason, Title, Uri,
+};
+use crate::clock::{ClockMode, Deadline};
+use crate::cw20::Cw20ReceiveMsg;
+use crate::cw3::{self, Cw3ExecuteMsg};
+use crate::membership::{MemberChangedHookMsg, MembershipSource};
+use crate::state::{
+    Announcement, ArchivedProposal, Ballot, Bounty, Config, Deposit, Execution, Group, GroupMember,
+    GroupQuorum, GroupTally, KeyRotation, Member, OutboxEntry, PendingConfig, Profile,
+    ProposalStatus, ProposalType, QuorumDecay, RewardDistribution, SecurityLane, SecurityPayout,
+    StorageUsage, Suspension, TieBreak, Vesting,
+};
+use crate::tally::VotingStrategy;
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Order, Timestamp, Uint128};
+use cw_utils::Scheduled;
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub members: Vec<Member>,
+    /// Defaults to `udevcore`
+    pub denom: Option<String>,
+    pub notifier: Option<String>,
+    pub receipt_salt: Option<String>,
+    pub admin: Option<String>,
+    pub key_rotation_delay: Option<u64>,
+    pub clock: Option<ClockMode>,
+    pub expected_block_time: Option<u64>,
+    pub archive_after: Option<u64>,
+    pub referral_fee: Option<Decimal>,
+    pub parent_dao: Option<String>,
+    pub voting_period: Option<u64>,
+    pub quorum: Option<Decimal>,
+    pub threshold: Option<Decimal>,
+    pub expense_threshold: Option<Decimal>,
+    pub proposal_deposit: Option<Uint128>,
+    pub burn_forfeited_deposits: Option<bool>,
+    /// Defaults to `TieBreak::TieFails`
+    pub tie_break: Option<TieBreak>,
+    /// Tags some of `members` into groups
+    pub groups: Option<Vec<GroupMember>>,
+    pub group_quorums: Option<Vec<GroupQuorum>>,
+    /// Starts in shadow mode with this much simulated treasury
+    pub shadow_funds: Option<Uint128>,
+    /// Both need `voting_period`, as they count from the voting deadline
+    pub execution_delay: Option<u64>,
+    pub execution_expiration: Option<u64>,
+    pub private_ballots: Option<bool>,
+    /// Needs `execution_delay`, vetoes are only possible while a passed proposal waits it out
+    pub guardian: Option<String>,
+    /// Needs `voting_period` and `quorum`, the grace starts at the voting deadline
+    pub quorum_decay: Option<Vec<QuorumDecay>>,
+    pub max_proposal_amount: Option<Uint128>,
+    pub lock_self_voted_vesting: Option<bool>,
+    /// `Conviction` needs `voting_period`, ballots count for the share of it left
+    pub voting_strategy: Option<VotingStrategy>,
+    pub membership: Option<MembershipSource>,
+    pub removal_grace_period: Option<u64>,
+    /// Only hides drafts from queries, contract storage stays readable to anyone running a node
+    pub private_drafts: Option<bool>,
+    pub security_lane: Option<SecurityLane>,
+    pub dex_router: Option<Addr>,
+    pub cw20_tokens: Option<Vec<Addr>>,
 }
 
 #[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
+    /// The description may not be empty, and `recipient` defaults to the sender
     Propose {
-        title: String,
-        description: String,
-        recipient: Option<Addr>,
+        title: Title,
+        description: Description,
+        recipient: Option<String>,
         amount: Option<Uint128>,
+        /// Defaults to `ProposalType::Grant`
+        proposal_type: Option<ProposalType>,
+        /// Registered frontend submitting the proposal, recorded for attribution
+        frontend_id: Option<FrontendId>,
     },
+    /// Casts the sender's ballot, or flips it if they already voted and voting has not ended
     Vote {
         proposal_id: u64,
         approve: bool,
     },
+    /// Casts `member`'s ballot as `Vote` would, sent by the operator they registered
+    VoteFor {
+        member: Addr,
+        proposal_id: u64,
+        approve: bool,
+    },
+    /// Lets `operator` vote on the sender's behalf, but not propose or manage membership,
+    /// `None` revokes it
+    SetVoteOperator {
+        operator: Option<String>,
+    },
+    /// Lends the sender's voting weight to `delegate`, who casts it along with their own on
+    /// any proposal the sender has not voted on themselves. Delegates cannot delegate on
+    Delegate {
+        delegate: String,
+    },
+    Undelegate {},
     Execute {
         proposal_id: u64,
     },
+    /// Executes each proposal in id order, all or none, each checked against the funds the
+    /// ones before it spend
+    ExecuteBatch {
+        proposal_ids: Vec<u64>,
+    },
+    UpdateProfile {
+        display_name: Option<DisplayName>,
+        avatar_uri: Option<Uri>,
+        bio_hash: Option<ContentHash>,
+    },
+    /// Admin only, bars a member from proposing and voting until `duration` seconds or blocks
+    /// pass or governance confirms the suspension
+    SuspendMember {
+        member: String,
+        reason: Reason,
+        duration: u64,
+    },
+    /// Moves the sender's membership, profile and open ballots to `new_address`, or schedules
+    /// the move when a key rotation delay is configured
+    RotateKey {
+        new_address: String,
+    },
+    /// Sent by the new address to complete a scheduled rotation once the delay has passed
+    ConfirmKeyRotation {},
+    /// Sends up to `limit` outstanding payouts of an executed `DistributeRewards` proposal
+    PayRewards {
+        proposal_id: u64,
+        limit: Option<u32>,
+    },
+    /// Sends the sender every payout still pending to them across reward distributions.
+    /// Removed members can only claim until their claim window closes
+    ClaimRewards {},
+    /// Drops the pending payouts of a removed member whose claim window has closed, leaving
+    /// them in the treasury
+    PruneRemovedMember {
+        address: String,
+    },
+    /// Releases whatever has vested so far on a `VestedGrant` to its recipient
+    ClaimVested {
+        proposal_id: u64,
+    },
+    /// Sent by the parent DAO to open a grant here, which is only paid once it passes a local
+    /// vote like any other proposal
+    MirrorProposal {
+        parent_proposal_id: u64,
+        title: Title,
+        description: Description,
+        recipient: Addr,
+        amount: Uint128,
+    },
+    /// Sent by the proposer to settle a tied proposal under `TieBreak::ProposerDecides`
+    BreakTie {
+        proposal_id: u64,
+        approve: bool,
+    },
+    /// Sent by the proposer with funds before voting ends, paid to whoever executes or closes
+    /// the proposal within an hour of its deadline and refunded to the proposer otherwise
+    AttachBounty {
+        proposal_id: u64,
+    },
+    /// Refunds the sender's deposit on a passed or executed proposal
+    WithdrawDeposit {
+        proposal_id: u64,
+    },
+    /// Closes a rejected, expired or lapsed proposal for good, open to anyone
+    Close {
+        proposal_id: u64,
+    },
+    /// Withdraws an open proposal nobody but its proposer has voted on, sent by the proposer,
+    /// the deposit and any bounty are refunded
+    Cancel {
+        proposal_id: u64,
+    },
+    /// Blocks a passed proposal from ever executing, sent by the guardian before its timelock
+    /// runs out, the deposit is forfeited as for a rejected proposal
+    VetoProposal {
+        proposal_id: u64,
+    },
+    /// Archives executed proposals whose retention period has passed among the next `limit`
+    /// executed after `start_after`, open to anyone. A full chunk reports `last_proposal_id` to
+    /// continue from
+    ArchiveProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Stores an off-chain grants backlog as drafts, sent by the admin or by the contract
+    /// itself through a `Custom` proposal
+    ImportProposals {
+        proposals: Vec<ImportedProposal>,
+    },
+    /// Opens an imported draft for voting, gated like `ImportProposals`
+    OpenDraft {
+        proposal_id: u64,
+    },
+    /// Sent by a security committee member to start a payout, which counts as their approval
+    AuthorizeSecurityPayout {
+        recipient: String,
+        amount: Uint128,
+        report_hash: ContentHash,
+    },
+    /// Sent by another committee member, the payout is sent once it has enough approvals
+    ApproveSecurityPayout {
+        payout_id: u64,
+    },
+    /// Sent by a member or the admin, returns the proposal as a `ProposalResponse` in the
+    /// response data, private drafts included
+    ReadProposal {
+        proposal_id: u64,
+    },
+    /// Sent by the configured cw4 group, once the DAO is registered as one of its hooks, to
+    /// mirror its membership changes into the member list
+    MemberChangedHook(MemberChangedHookMsg),
+    /// Sent by a cw20 token contract in `Config::cw20_tokens` when tokens are sent to the DAO,
+    /// credited to its treasury ledger. `msg` is not read
+    Receive(Cw20ReceiveMsg),
+    /// A cw3 message, only tried when none of the above parses
+    #[serde(untagged)]
+    Cw3(Cw3ExecuteMsg),
+}
+
+#[cw_serde]
+pub struct ImportedProposal {
+    pub title: Title,
+    pub description: Description,
+    pub recipient: Addr,
+    pub amount: Uint128,
+    pub proposal_type: Option<ProposalType>,
+    /// Original submission time, kept on the proposal
+    pub submitted_at: Timestamp,
+}
+
+/// Each variant is one targeted migration, the chunked ones are repeated with the previous
+/// call's `last_proposal_id` attribute as `start_after` until it stops returning one
+#[cw_serde]
+pub enum MigrateMsg {
+    /// Only records the new contract version
+    Upgrade {},
+    /// Brings an instance of the original contract, which kept votes and voters on the
+    /// proposal itself and no config, to the current layout
+    UpgradeOriginalLayout {},
+    /// Locks the tallies of proposals executed before `Tally::locked` existed
+    BackfillStatuses {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+/// Sent by the chain itself, e.g. from upgrade handlers or monitoring
+#[cw_serde]
+pub enum SudoMsg {
+    /// Recomputes the total member weight and the tallies of the most recent proposals from
+    /// their sources, failing on the first mismatch
+    AssertInvariants {},
+}
+
+/// Message sent to the configured notifier contract
+#[cw_serde]
+pub enum NotifierMsg {
+    ProposalUpdated {
+        proposal_id: u64,
+        event: ProposalEvent,
+    },
+}
+
+#[cw_serde]
+pub enum ProposalEvent {
+    Opened,
+    Passed,
+    Executed,
+    /// Closed through `Close` after it was voted down
+    Rejected,
+    /// Closed through `Close` after voting ended without quorum
+    Expired,
+    /// Closed through `Close` after it passed but was never executed in time
+    Lapsed,
+    /// Struck down by the guardian through `VetoProposal`
+    Vetoed,
+    /// Withdrawn by its proposer through `Cancel`
+    Cancelled,
+}
+
+/// Direction a list query walks its keys in
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum ListOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl From<ListOrder> for Order {
+    fn from(order: ListOrder) -> Self {
+        match order {
+            ListOrder::Ascending => Order::Ascending,
+            ListOrder::Descending => Order::Descending,
+        }
+    }
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    #[returns(Proposal)]
+    #[returns(ProposalRecord)]
     GetProposal { proposal_id: u64 },
-    #[returns(Vec<Proposal>)]
+    /// Private drafts are left out of this and every other proposal listing
+    #[returns(ListProposalsResponse)]
     ListProposals {
         start_after: Option<u64>,
         limit: Option<u32>,
+        /// `next_cursor` from a previous page, takes precedence over `start_after`
+        cursor: Option<Binary>,
+        /// Oldest first by default, `descending` for the newest first
+        order: Option<ListOrder>,
     },
-    #[returns(Member)]
+    /// Proposals by their current status, including ones that reached it since the last vote.
+    /// A page may hold fewer than `limit` while `next_cursor` is still set
+    #[returns(ListProposalsResponse)]
+    ListProposalsByStatus {
+        status: ProposalStatus,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        /// `next_cursor` from a previous page, takes precedence over `start_after`
+        cursor: Option<Binary>,
+    },
+    #[returns(ListProposalsResponse)]
+    ListProposalsByProposer {
+        proposer: Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        /// `next_cursor` from a previous page, takes precedence over `start_after`
+        cursor: Option<Binary>,
+    },
+    #[returns(ListProposalsResponse)]
+    ListProposalsByRecipient {
+        recipient: Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        /// `next_cursor` from a previous page, takes precedence over `start_after`
+        cursor: Option<Binary>,
+    },
+    /// Fails under `private_ballots` unless the proposal's ballots were disclosed
+    #[returns(Ballot)]
+    GetVote { proposal_id: u64, voter: Addr },
+    /// Ballots on a proposal by voter address, private like `GetVote`
+    #[returns(ListVotesResponse)]
+    ListVotes {
+        proposal_id: u64,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        /// `next_cursor` from a previous page, takes precedence over `start_after`
+        cursor: Option<Binary>,
+    },
+    /// Every ballot on a proposal whose vote has closed, by voter address, for archiving and
+    /// third-party verification. Private like `GetVote`
+    #[returns(ExportBallotsResponse)]
+    ExportBallots {
+        proposal_id: u64,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    #[returns(MemberResponse)]
     GetMember { address: Addr },
-    #[returns(Vec<Member>)]
+    #[returns(ListMembersResponse)]
     ListMembers {
         start_after: Option<Addr>,
         limit: Option<u32>,
+        /// `next_cursor` from a previous page, takes precedence over `start_after`
+        cursor: Option<Binary>,
+        /// Address order by default, `descending` for the reverse
+        order: Option<ListOrder>,
+    },
+    /// Members from the lightest up, or the heaviest down with `descending`, ties in address
+    /// order
+    #[returns(ListMembersResponse)]
+    ListMembersByWeight {
+        descending: bool,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        /// `next_cursor` from a previous page, takes precedence over `start_after`
+        cursor: Option<Binary>,
+    },
+    #[returns(Option<SuspensionResponse>)]
+    GetSuspension { address: Addr },
+    /// When a removed member's claim window closes, `None` for anyone else
+    #[returns(Option<Deadline>)]
+    GetClaimWindow { address: Addr },
+    #[returns(Option<KeyRotationResponse>)]
+    GetKeyRotation { new_address: Addr },
+    #[returns(VestingResponse)]
+    GetVesting { proposal_id: u64 },
+    /// Everything about a grant in one response, for grantees and funders tracking it
+    #[returns(GrantStatusResponse)]
+    GetGrantStatus { proposal_id: u64 },
+    #[returns(Config)]
+    GetConfig {},
+    #[returns(Option<PendingConfigResponse>)]
+    GetPendingConfig {},
+    /// Per-member participation across executed proposals, for reward airdrops
+    #[returns(ParticipationSnapshotResponse)]
+    ParticipationSnapshot { selection: ProposalSelection },
+    #[returns(RewardDistribution)]
+    GetRewardDistribution { proposal_id: u64 },
+    #[returns(ListAnnouncementsResponse)]
+    ListAnnouncements {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        cursor: Option<Binary>,
+    },
+    /// Whether `address` could vote on the proposal right now, and why not if it could not
+    #[returns(CanVoteResponse)]
+    CanVote { proposal_id: u64, address: Addr },
+    /// Whether `address` could open a proposal right now, and why not if it could not
+    #[returns(CanProposeResponse)]
+    CanPropose { address: Addr },
+    #[returns(StorageStatsResponse)]
+    StorageStats {},
+    #[returns(TreasuryStatsResponse)]
+    TreasuryStats {},
+    /// Every native coin the contract holds, and its cw20 ledger by token contract
+    #[returns(TreasuryBalancesResponse)]
+    ListTreasuryBalances {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    /// Proposal activity over one 30 day period of block time, the current one when unset
+    #[returns(AnalyticsResponse)]
+    GetAnalytics { period: Option<u64> },
+    /// Raw storage entries behind a vote, for light clients verifying it against a state
+    /// proof, see `state::ballot_key` for how the keys are derived
+    #[returns(VoteProofResponse)]
+    VoteProof { proposal_id: u64, voter: Addr },
+    /// Every batch of messages executions have sent, oldest first, for reconciling transfers
+    /// against the proposals that approved them
+    #[returns(OutboxResponse)]
+    Outbox {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        cursor: Option<Binary>,
+    },
+    /// Payouts booked in shadow mode, by recipient
+    #[returns(ShadowLedgerResponse)]
+    ShadowLedger {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+        cursor: Option<Binary>,
+    },
+    #[returns(ListGroupsResponse)]
+    ListGroups {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        cursor: Option<Binary>,
+    },
+    #[returns(ListFrontendsResponse)]
+    ListFrontends {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        cursor: Option<Binary>,
+    },
+    /// Payouts through the security lane, with their details withheld until disclosed
+    #[returns(ListSecurityPayoutsResponse)]
+    ListSecurityPayouts {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        cursor: Option<Binary>,
+    },
+    /// cw3's threshold query. cw3's `list_proposals` and `list_votes` names are this contract's
+    /// own `ListProposals` and `ListVotes`, which answer in their own shape, cw3 clients list
+    /// through `Cw3ListProposals` and `Cw3ListVotes` instead
+    #[returns(cw3::ThresholdResponse)]
+    Threshold {},
+    /// cw3's proposal query. Archived proposals only keep a hash of their title, reported in
+    /// its place
+    #[returns(cw3::ProposalResponse)]
+    Proposal { proposal_id: u64 },
+    /// cw3's `list_proposals`
+    #[returns(cw3::ProposalListResponse)]
+    Cw3ListProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// cw3's proposals newest first
+    #[returns(cw3::ProposalListResponse)]
+    ReverseProposals {
+        start_before: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// cw3's vote query, hidden under `private_ballots` like `GetVote`
+    #[returns(cw3::VoteResponse)]
+    Vote { proposal_id: u64, voter: String },
+    /// cw3's `list_votes`, by voter address and private like `GetVote`
+    #[returns(cw3::VoteListResponse)]
+    Cw3ListVotes {
+        proposal_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Weight from the configured membership source, `None` for non-members
+    #[returns(cw3::VoterResponse)]
+    Voter { address: String },
+    /// This contract's own members in address order, weighed like `Voter`
+    #[returns(cw3::VoterListResponse)]
+    ListVoters {
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
 }
+
+/// `Proposal` joined with its current tally
+#[cw_serde]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub title: Title,
+    pub description: Description,
+    pub recipient: Addr,
+    pub amount: Uint128,
+    pub votes_for: Uint128,
+    pub votes_against: Uint128,
+    pub executed: bool,
+    pub proposal_type: ProposalType,
+    pub execution: Option<Execution>,
+    pub frontend_id: Option<FrontendId>,
+    pub parent_proposal_id: Option<u64>,
+    pub voting_end: Option<Scheduled>,
+    /// Seconds or blocks until `voting_end`, whichever it is scheduled in, 0 once it has passed
+    pub time_remaining: Option<u64>,
+    pub status: ProposalStatus,
+    /// Weight quorum and threshold are measured against, as of when voting opened for
+    /// proposals that snapshot it
+    pub total_weight: Uint128,
+    pub quorum_reached: bool,
+    /// Share of the weight cast in favour is above the threshold, quorum or not
+    pub threshold_reached: bool,
+    pub deposit: Option<Deposit>,
+    pub proposer: Option<Addr>,
+    pub groups: Vec<GroupTally>,
+    pub submitted_at: Option<Timestamp>,
+    pub bounty: Option<Bounty>,
+    pub created_at: Option<Timestamp>,
+    pub created_height: Option<u64>,
+    /// Whether funds are reserved for the proposal in the treasury
+    pub funds_committed: bool,
+}
+
+/// A proposal as stored, in full or archived
+#[cw_serde]
+#[allow(clippy::large_enum_variant)]
+pub enum ProposalRecord {
+    Full(ProposalResponse),
+    Archived(ArchivedProposal),
+}
+
+impl ProposalRecord {
+    pub fn id(&self) -> u64 {
+        match self {
+            ProposalRecord::Full(proposal) => proposal.id,
+            ProposalRecord::Archived(proposal) => proposal.id,
+        }
+    }
+
+    /// Only executed proposals are archived
+    pub fn status(&self) -> ProposalStatus {
+        match self {
+            ProposalRecord::Full(proposal) => proposal.status,
+            ProposalRecord::Archived(_) => ProposalStatus::Executed,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct ListProposalsResponse {
+    /// Full and archived proposals together, in id order or its reverse
+    pub proposals: Vec<ProposalRecord>,
+    /// Opaque token for the next page, `None` once the last page is reached
+    pub next_cursor: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct ListVotesResponse {
+    pub votes: Vec<Ballot>,
+    /// Opaque token for the next page, `None` once the last page is reached
+    pub next_cursor: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct ExportBallotsResponse {
+    pub proposal_id: u64,
+    pub status: ProposalStatus,
+    pub ballots: Vec<ExportedBallot>,
+}
+
+/// A ballot reduced to what settles the outcome, fields are only ever added
+#[cw_serde]
+pub struct ExportedBallot {
+    pub voter: Addr,
+    pub approve: bool,
+    /// Member weight behind the ballot, delegated weight included
+    pub weight: Uint128,
+    /// What the ballot counted for in the tally
+    pub counted: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct ListMembersResponse {
+    pub members: Vec<MemberResponse>,
+    /// Opaque token for the next page, `None` once the last page is reached
+    pub next_cursor: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct ListFrontendsResponse {
+    pub frontends: Vec<FrontendResponse>,
+    /// Opaque token for the next page, `None` once the last page is reached
+    pub next_cursor: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct OutboxResponse {
+    pub entries: Vec<OutboxEntry>,
+    /// Opaque token for the next page, `None` once the last page is reached
+    pub next_cursor: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct ShadowLedgerResponse {
+    /// Simulated treasury left, `None` once live
+    pub shadow_funds_left: Option<Uint128>,
+    pub payouts: Vec<ShadowPayout>,
+    /// Opaque token for the next page, `None` once the last page is reached
+    pub next_cursor: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct ShadowPayout {
+    pub recipient: Addr,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct ListGroupsResponse {
+    pub groups: Vec<Group>,
+    /// Opaque token for the next page, `None` once the last page is reached
+    pub next_cursor: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct FrontendResponse {
+    pub id: FrontendId,
+    pub operator: Addr,
+    /// Referral fees paid to `operator` so far
+    pub fees_paid: Uint128,
+}
+
+#[cw_serde]
+pub struct ListAnnouncementsResponse {
+    pub announcements: Vec<Announcement>,
+    /// Opaque token for the next page, `None` once the last page is reached
+    pub next_cursor: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct CanVoteResponse {
+    pub can_vote: bool,
+    /// `ContractError::code()` of the error a vote would fail with
+    pub error_code: Option<u32>,
+    pub reason: Option<String>,
+}
+
+#[cw_serde]
+pub struct CanProposeResponse {
+    pub can_propose: bool,
+    /// `ContractError::code()` of the error a proposal would fail with
+    pub error_code: Option<u32>,
+    pub reason: Option<String>,
+}
+
+#[cw_serde]
+pub struct StorageStatsResponse {
+    pub proposals: StorageUsage,
+    pub ballots: StorageUsage,
+    pub members: StorageUsage,
+    pub archive: StorageUsage,
+}
+
+#[cw_serde]
+pub struct TreasuryStatsResponse {
+    pub balance: Uint128,
+    /// Deposits and bounties owed back to proposers
+    pub held: Uint128,
+    /// Reserved for passing proposals
+    pub committed: Uint128,
+    /// What a newly passing proposal can still reserve, the shadow treasury's in shadow mode
+    pub available: Uint128,
+    pub granted: Uint128,
+}
+
+#[cw_serde]
+pub struct TreasuryBalancesResponse {
+    /// The same on every page
+    pub native: Vec<Coin>,
+    pub cw20: Vec<Cw20Balance>,
+}
+
+#[cw_serde]
+pub struct Cw20Balance {
+    pub token: Addr,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct AnalyticsResponse {
+    pub period: u64,
+    pub start: Timestamp,
+    pub end: Timestamp,
+    pub proposals_created: u32,
+    pub proposals_passed: u32,
+    pub proposals_rejected: u32,
+    pub funds_granted: Uint128,
+    /// Mean share of the electorate that voted on the proposals decided in the period
+    pub average_participation: Option<Decimal>,
+    /// In seconds, over the proposals executed in the period
+    pub median_time_to_execution: Option<u64>,
+}
+
+#[cw_serde]
+pub struct VoteProofResponse {
+    pub member: RawEntry,
+    pub total_weight: RawEntry,
+    /// Value withheld under `private_ballots` until the ballots are disclosed
+    pub ballot: RawEntry,
+}
+
+/// Storage entry as the chain holds it, `value` is `None` if the key is unset
+#[cw_serde]
+pub struct RawEntry {
+    pub key: Binary,
+    pub value: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct MemberResponse {
+    pub address: Addr,
+    pub weight: Uint128,
+    pub profile: Option<Profile>,
+    pub group: Option<GroupId>,
+    pub vote_operator: Option<Addr>,
+    pub delegate: Option<Addr>,
+    pub delegators: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct VestingResponse {
+    pub vesting: Vesting,
+    /// Portion of `vesting_amount` released as of the current block
+    pub vested: Uint128,
+    /// Vested but not yet claimed
+    pub claimable: Uint128,
+    /// When everything will have vested
+    pub fully_vested: Deadline,
+}
+
+#[cw_serde]
+pub struct GrantStatusResponse {
+    pub proposal: ProposalRecord,
+    /// Set for vested grants once executed
+    pub vesting: Option<VestingResponse>,
+    /// Sent to the recipient so far
+    pub paid: Uint128,
+    /// Still owed to the recipient, pending the vote for grants not yet executed
+    pub remaining: Uint128,
+}
+
+#[cw_serde]
+pub struct SecurityPayoutResponse {
+    pub id: u64,
+    pub paid: bool,
+    /// When the details are disclosed, `None` until the payout is sent
+    pub disclosure: Option<Deadline>,
+    /// `None` until disclosed
+    pub payout: Option<SecurityPayout>,
+}
+
+#[cw_serde]
+pub struct ListSecurityPayoutsResponse {
+    pub payouts: Vec<SecurityPayoutResponse>,
+    /// What the lane's budget has left
+    pub remaining_budget: Uint128,
+    /// Opaque token for the next page, `None` once the last page is reached
+    pub next_cursor: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct SuspensionResponse {
+    pub suspension: Suspension,
+    pub expires: Deadline,
+}
+
+#[cw_serde]
+pub struct KeyRotationResponse {
+    pub rotation: KeyRotation,
+    pub executable: Deadline,
+}
+
+#[cw_serde]
+pub struct PendingConfigResponse {
+    pub pending: PendingConfig,
+    pub effective: Deadline,
+}
+
+#[cw_serde]
+pub enum ProposalSelection {
+    /// Exactly these proposals, all of which must be executed
+    Ids { proposal_ids: Vec<u64> },
+    /// Every proposal executed in `[start, end)`
+    ExecutedBetween { start: Timestamp, end: Timestamp },
+}
+
+#[cw_serde]
+pub struct ParticipationSnapshotResponse {
+    pub proposal_ids: Vec<u64>,
+    /// Sorted by address
+    pub participants: Vec<Participation>,
+}
+
+#[cw_serde]
+pub struct Participation {
+    pub address: Addr,
+    pub proposals_voted: u32,
+    /// Sum of the weight cast across the selected proposals
+    pub weight: Uint128,
+}
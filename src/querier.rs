@@ -0,0 +1,51 @@
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult, Uint128};
+use serde::de::DeserializeOwned;
+
+use crate::msg::{MemberResponse, ProposalRecord, QueryMsg};
+use crate::state::{Config, ProposalStatus};
+
+/// Typed queries against a deployed grant DAO, for contracts that read its membership and
+/// proposals
+pub struct GrantDaoQuerier<'a> {
+    querier: QuerierWrapper<'a>,
+    contract: Addr,
+}
+
+impl<'a> GrantDaoQuerier<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, contract: Addr) -> Self {
+        GrantDaoQuerier { querier, contract }
+    }
+
+    pub fn contract(&self) -> &Addr {
+        &self.contract
+    }
+
+    /// Fails for addresses that are not members
+    pub fn member(&self, address: &Addr) -> StdResult<MemberResponse> {
+        self.query(&QueryMsg::GetMember {
+            address: address.clone(),
+        })
+    }
+
+    /// Fails for addresses that are not members, like `member`
+    pub fn member_weight(&self, address: &Addr) -> StdResult<Uint128> {
+        Ok(self.member(address)?.weight)
+    }
+
+    pub fn proposal(&self, proposal_id: u64) -> StdResult<ProposalRecord> {
+        self.query(&QueryMsg::GetProposal { proposal_id })
+    }
+
+    /// Only executed proposals are archived, so an archived one reads as executed
+    pub fn proposal_status(&self, proposal_id: u64) -> StdResult<ProposalStatus> {
+        Ok(self.proposal(proposal_id)?.status())
+    }
+
+    pub fn config(&self) -> StdResult<Config> {
+        self.query(&QueryMsg::GetConfig {})
+    }
+
+    fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier.query_wasm_smart(&self.contract, msg)
+    }
+}
@@ -1,20 +1,456 @@
-use std::collections::HashSet;
-
+use crate::bounded::{
+    ContentHash, Description, DisplayName, FrontendId, GroupId, Reason, Title, Uri,
+};
+use crate::clock::ClockMode;
+use crate::membership::MembershipSource;
+use crate::tally::VotingStrategy;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::Map;
+use cosmwasm_std::{
+    Addr, CosmosMsg, Decimal, Empty, HexBinary, StdResult, Storage, Timestamp, Uint128,
+};
+use cw_storage_plus::{
+    Index, IndexList, IndexedMap, Item, KeyDeserialize, Map, MultiIndex, Prefix, SnapshotMap,
+    Strategy,
+};
+use cw_utils::Scheduled;
 
 #[cw_serde]
 pub struct Proposal {
     pub id: u64,
-    pub title: String,
-    pub description: String,
+    pub title: Title,
+    pub description: Description,
     pub recipient: Addr,
     pub amount: Uint128,
+    pub executed: bool,
+    #[serde(default)]
+    pub proposal_type: ProposalType,
+    /// Where the proposal was executed, set once `executed` flips to true
+    pub execution: Option<Execution>,
+    /// Registered frontend the proposal was submitted through
+    #[serde(default)]
+    pub frontend_id: Option<FrontendId>,
+    /// Proposal in the parent DAO this one was mirrored from
+    #[serde(default)]
+    pub parent_proposal_id: Option<u64>,
+    /// Only ever stored once final, `Open` and `Passed` follow from the tally
+    #[serde(default)]
+    pub status: ProposalStatus,
+    /// Attached by the proposer when deposits are required
+    #[serde(default)]
+    pub deposit: Option<Deposit>,
+    /// Member who opened the proposal, `None` for proposals the contract opened itself
+    #[serde(default)]
+    pub proposer: Option<Addr>,
+    /// When an imported proposal was originally submitted off chain
+    #[serde(default)]
+    pub submitted_at: Option<Timestamp>,
+    /// Attached by the proposer for whoever finalizes the proposal in time
+    #[serde(default)]
+    pub bounty: Option<Bounty>,
+    /// Block the proposal was stored in, `None` for proposals stored before this was recorded
+    #[serde(default)]
+    pub created_at: Option<Timestamp>,
+    #[serde(default)]
+    pub created_height: Option<u64>,
+}
+
+#[cw_serde]
+pub struct Bounty {
+    pub amount: Uint128,
+    /// Set once paid out or refunded
+    pub settled: bool,
+}
+
+#[cw_serde]
+pub struct Deposit {
+    pub depositor: Addr,
+    pub amount: Uint128,
+    /// Set once refunded or forfeited
+    pub settled: bool,
+}
+
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum ProposalStatus {
+    /// Taking votes, the outcome is not settled yet
+    #[default]
+    Open,
+    /// Carried and waiting to be executed
+    Passed,
+    /// Reached quorum but could not carry the threshold
+    Rejected,
+    Executed,
+    /// Voting ended without reaching quorum
+    Expired,
+    /// Ended in a tie, waiting on the proposer's casting vote under `TieBreak::ProposerDecides`
+    Tied,
+    /// Imported from an off-chain backlog, takes no votes until `OpenDraft`
+    Draft,
+    /// Passed but not executed before `Config::execution_expiration` ran out
+    Lapsed,
+    /// Passed but struck down by the guardian before its timelock ran out
+    Vetoed,
+    /// Withdrawn by its proposer before anyone else voted
+    Cancelled,
+}
+
+/// How a proposal that ends with as much weight against it as for it is settled
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum TieBreak {
+    /// Rejected, as any proposal short of the threshold
+    #[default]
+    TieFails,
+    TiePasses,
+    /// The proposer casts the deciding vote through `BreakTie`, proposals the contract opened
+    /// itself fail instead
+    ProposerDecides,
+    /// Voting stays open for one more voting period and the first vote that breaks the tie
+    /// settles it, failing if it is still tied by then
+    ExtendVoting,
+}
+
+impl ProposalStatus {
+    /// Key in `PROPOSALS_BY_STATUS`, the JSON name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProposalStatus::Open => "open",
+            ProposalStatus::Passed => "passed",
+            ProposalStatus::Rejected => "rejected",
+            ProposalStatus::Executed => "executed",
+            ProposalStatus::Expired => "expired",
+            ProposalStatus::Tied => "tied",
+            ProposalStatus::Draft => "draft",
+            ProposalStatus::Lapsed => "lapsed",
+            ProposalStatus::Vetoed => "vetoed",
+            ProposalStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl Ballot {
+    /// What the ballot adds to its side of the tally
+    pub fn counted_weight(&self) -> Uint128 {
+        self.counted.unwrap_or(self.weight)
+    }
+}
+
+impl TieBreak {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TieBreak::TieFails => "tie_fails",
+            TieBreak::TiePasses => "tie_passes",
+            TieBreak::ProposerDecides => "proposer_decides",
+            TieBreak::ExtendVoting => "extend_voting",
+        }
+    }
+}
+
+#[cw_serde]
+pub struct Execution {
+    pub height: u64,
+    pub time: Timestamp,
+    pub tx_index: Option<u32>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum ProposalType {
+    /// Sends `amount` to `recipient` once passed
+    #[default]
+    Grant,
+    /// Sends every payout at once, `amount` is their total
+    GrantBatch { payouts: Vec<GrantPayout> },
+    /// Publishes an announcement on the board once passed
+    Announce { title: Title, body: Description },
+    /// Moves no funds, executing it only records the outcome and emits a `signal` event
+    Signal {},
+    /// Pays `upfront` of `amount` on execution and vests the rest linearly over `duration`
+    /// seconds or blocks, per the configured clock
+    VestedGrant { upfront: Uint128, duration: u64 },
+    /// Applies all membership changes atomically once passed
+    UpdateMembersBatch {
+        add: Vec<Member>,
+        remove: Vec<Addr>,
+        update: Vec<Member>,
+    },
+    /// Removes a suspended member once passed, opened automatically by `SuspendMember`
+    ConfirmSuspension { member: Addr },
+    /// Splits `pool` pro-rata by cast weight between members who voted on at least
+    /// `min_participation` of the proposals executed in `[start, end)`, paid out via `PayRewards`
+    DistributeRewards {
+        pool: Uint128,
+        start: Timestamp,
+        end: Timestamp,
+        min_participation: Decimal,
+    },
+    /// Replaces the config once passed, or schedules it if `effective_at` is in the future
+    UpdateConfig {
+        config: Box<Config>,
+        effective_at: Option<Scheduled>,
+    },
+    /// Pays `amount` to the vendor in `recipient` against the invoice hashed in
+    /// `invoice_hash`, under the expense threshold rather than the regular one
+    Expense { invoice_hash: ContentHash },
+    /// Casts this DAO's vote on `proposal_id` in the configured parent DAO once passed
+    ParentVote { proposal_id: u64, approve: bool },
+    /// Makes the ballots of a proposal queryable despite `private_ballots`, e.g. to settle
+    /// a dispute
+    DiscloseBallots { proposal_id: u64 },
+    /// Registers or replaces `register` and drops `remove` from the frontend registry
+    UpdateFrontends {
+        register: Vec<Frontend>,
+        remove: Vec<FrontendId>,
+    },
+    /// Tags `assign` into their groups, moving them out of any earlier one, and untags `unassign`
+    UpdateGroups {
+        assign: Vec<GroupMember>,
+        unassign: Vec<Addr>,
+    },
+    /// Leaves shadow mode, everything approved from then on is paid out for real
+    GoLive {},
+    /// Dispatches arbitrary messages from the contract once passed, such as contract calls or
+    /// admin changes
+    Custom { msgs: Vec<CosmosMsg> },
+    /// Swaps `offer` of the treasury denom into `ask_denom` through the configured
+    /// `dex_router` once passed, failing if it would return less than `min_receive`
+    Swap {
+        offer: Uint128,
+        ask_denom: String,
+        min_receive: Uint128,
+    },
+}
+
+/// `ProposalType` without its payload, for rules that apply to some kinds of proposal only
+#[cw_serde]
+#[derive(Copy)]
+pub enum ProposalCategory {
+    Grant,
+    GrantBatch,
+    Announce,
+    Signal,
+    VestedGrant,
+    UpdateMembersBatch,
+    ConfirmSuspension,
+    DistributeRewards,
+    UpdateConfig,
+    Expense,
+    ParentVote,
+    DiscloseBallots,
+    UpdateFrontends,
+    UpdateGroups,
+    GoLive,
+    Custom,
+    Swap,
+}
+
+impl ProposalType {
+    pub fn category(&self) -> ProposalCategory {
+        match self {
+            ProposalType::Grant => ProposalCategory::Grant,
+            ProposalType::GrantBatch { .. } => ProposalCategory::GrantBatch,
+            ProposalType::Announce { .. } => ProposalCategory::Announce,
+            ProposalType::Signal {} => ProposalCategory::Signal,
+            ProposalType::VestedGrant { .. } => ProposalCategory::VestedGrant,
+            ProposalType::UpdateMembersBatch { .. } => ProposalCategory::UpdateMembersBatch,
+            ProposalType::ConfirmSuspension { .. } => ProposalCategory::ConfirmSuspension,
+            ProposalType::DistributeRewards { .. } => ProposalCategory::DistributeRewards,
+            ProposalType::UpdateConfig { .. } => ProposalCategory::UpdateConfig,
+            ProposalType::Expense { .. } => ProposalCategory::Expense,
+            ProposalType::ParentVote { .. } => ProposalCategory::ParentVote,
+            ProposalType::DiscloseBallots { .. } => ProposalCategory::DiscloseBallots,
+            ProposalType::UpdateFrontends { .. } => ProposalCategory::UpdateFrontends,
+            ProposalType::UpdateGroups { .. } => ProposalCategory::UpdateGroups,
+            ProposalType::GoLive {} => ProposalCategory::GoLive,
+            ProposalType::Swap { .. } => ProposalCategory::Swap,
+            ProposalType::Custom { .. } => ProposalCategory::Custom,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct GroupMember {
+    pub address: Addr,
+    pub group: GroupId,
+}
+
+/// Members tagged into a group and their combined weight
+#[cw_serde]
+pub struct Group {
+    pub id: GroupId,
+    pub members: u32,
+    pub weight: Uint128,
+}
+
+/// Share of a group's weight that has to vote for a proposal to reach quorum, on top of the
+/// overall quorum
+#[cw_serde]
+pub struct GroupQuorum {
+    pub group: GroupId,
+    pub quorum: Decimal,
+    /// Proposals the requirement applies to, all of them when empty
+    pub categories: Vec<ProposalCategory>,
+}
+
+/// Keeps a proposal that reaches its deadline short of quorum open for `grace` more seconds or
+/// blocks, while the quorum it needs falls steadily to `floor`
+#[cw_serde]
+pub struct QuorumDecay {
+    pub grace: u64,
+    pub floor: Decimal,
+    /// Proposals the decay applies to, all of them when empty
+    pub categories: Vec<ProposalCategory>,
+}
+
+/// Votes cast by one group's members on a proposal its quorum applies to
+#[cw_serde]
+pub struct GroupTally {
+    pub group: GroupId,
+    /// Weight the group has to cast, fixed when the proposal is opened
+    pub required: Uint128,
     pub votes_for: Uint128,
     pub votes_against: Uint128,
-    pub voters: HashSet<Addr>,
-    pub executed: bool,
+}
+
+/// Third-party frontend approved to submit proposals on members' behalf
+#[cw_serde]
+pub struct Frontend {
+    pub id: FrontendId,
+    pub operator: Addr,
+}
+
+/// Vote totals live apart from `Proposal` so a vote only rewrites these two numbers
+#[cw_serde]
+#[derive(Default)]
+pub struct Tally {
+    pub votes_for: Uint128,
+    pub votes_against: Uint128,
+    /// Set once the proposal is decided, from then on its record never changes
+    #[serde(default)]
+    pub locked: bool,
+    /// Votes are refused from here on, and until then the proposal only executes once no
+    /// outstanding weight could overturn it
+    #[serde(default)]
+    pub voting_end: Option<Scheduled>,
+    /// Threshold, quorum and voting period as of when the proposal opened, the expense
+    /// threshold for expenses, so config changes leave proposals already open alone. Tallies
+    /// from before they were fixed follow the current config
+    #[serde(default)]
+    pub threshold: Option<Decimal>,
+    #[serde(default)]
+    pub quorum: Option<Decimal>,
+    #[serde(default)]
+    pub voting_period: Option<u64>,
+    /// The proposer's decision on a tie under `TieBreak::ProposerDecides`
+    #[serde(default)]
+    pub casting_vote: Option<bool>,
+    /// Carried over from the original layout, which kept no ballots to check it against
+    #[serde(default)]
+    pub migrated: bool,
+    /// One entry per group whose quorum applies to the proposal
+    #[serde(default)]
+    pub groups: Vec<GroupTally>,
+    /// Block the proposal opened in, ballots carry each member's `VOTING_POWER` as of its end.
+    /// `None` for proposals opened before snapshots were kept, which go by current weights
+    #[serde(default)]
+    pub snapshot_height: Option<u64>,
+    /// `TOTAL_WEIGHT` as of `snapshot_height`, what quorum and thresholds are measured against
+    #[serde(default)]
+    pub total_weight: Option<Uint128>,
+    /// The first `Config::quorum_decay` rule matching the proposal, fixed when it is opened
+    #[serde(default)]
+    pub quorum_decay: Option<QuorumDecay>,
+    /// What executing the proposal would spend, fixed when it is opened
+    #[serde(default)]
+    pub commitment: Uint128,
+    /// `Config::voting_strategy` when the proposal was opened
+    #[serde(default)]
+    pub strategy: VotingStrategy,
+    /// `Config::membership` when the proposal was opened, ballots are weighed by it
+    #[serde(default)]
+    pub membership: MembershipSource,
+    /// Member weight behind `votes_for` and `votes_against`, which quorum is measured in.
+    /// `None` while every ballot counts for its voter's full weight
+    #[serde(default)]
+    pub weight_cast: Option<Uint128>,
+    /// Whether `commitment` is reserved in `COMMITTED`, from the vote that made the proposal
+    /// pass until it is executed or voted back down
+    #[serde(default)]
+    pub committed: bool,
+}
+
+#[cw_serde]
+pub struct Ballot {
+    pub voter: Addr,
+    pub approve: bool,
+    pub weight: Uint128,
+    /// What the ballot counts for in the tally, when the voting strategy weighs it for less
+    /// than `weight`
+    #[serde(default)]
+    pub counted: Option<Uint128>,
+    /// Block height the vote was cast at
+    pub height: u64,
+    /// Block time the vote was cast at
+    pub time: Timestamp,
+    /// sha256 over the salt, voter, proposal, vote and height, see `ballot_receipt`
+    pub receipt: HexBinary,
+    /// Operator who cast the ballot on the voter's behalf
+    #[serde(default)]
+    pub operator: Option<Addr>,
+    /// Delegators whose weight the ballot carries, included in `weight`
+    #[serde(default)]
+    pub delegators: Vec<Addr>,
+    /// Weight each of `delegators` lent, in the same order
+    #[serde(default)]
+    pub lent: Vec<Uint128>,
+}
+
+/// Messages sent on behalf of one proposal in one transaction
+#[cw_serde]
+pub struct OutboxEntry {
+    pub id: u64,
+    pub proposal_id: u64,
+    /// sha256 over the JSON encoding of the messages, in the order they were sent
+    pub digest: HexBinary,
+    pub messages: u32,
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+#[cw_serde]
+pub struct Vesting {
+    pub proposal_id: u64,
+    pub recipient: Addr,
+    /// Paid out when the proposal was executed
+    pub upfront: Uint128,
+    /// Released linearly between `start` and `end`
+    pub vesting_amount: Uint128,
+    pub start: Scheduled,
+    pub end: Scheduled,
+    /// Portion of `vesting_amount` already claimed
+    pub claimed: Uint128,
+    /// Granted in shadow mode, so claims are only booked in the shadow ledger
+    #[serde(default)]
+    pub shadow: bool,
+    /// Whether the unclaimed part is held in `COMMITTED`, vestings from before it was are not
+    #[serde(default)]
+    pub reserved: bool,
+}
+
+#[cw_serde]
+pub struct RewardDistribution {
+    pub pool: Uint128,
+    pub eligible: u32,
+    /// Payouts not yet sent
+    pub pending: u32,
+    pub paid: Uint128,
+    /// Scheduled in shadow mode, so payouts are only booked in the shadow ledger
+    #[serde(default)]
+    pub shadow: bool,
+    /// Whether unpaid payouts are held in `COMMITTED`, distributions from before they were
+    /// are not
+    #[serde(default)]
+    pub reserved: bool,
 }
 
 #[cw_serde]
@@ -23,5 +459,499 @@ pub struct Member {
     pub weight: Uint128,
 }
 
-pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-pub const MEMBERS: Map<Addr, Member> = Map::new("members");
+#[cw_serde]
+pub struct GrantPayout {
+    pub recipient: Addr,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct Suspension {
+    pub reason: Reason,
+    pub suspended_at: Timestamp,
+    /// The member regains their rights after this unless governance confirms the suspension
+    pub expires_at: Scheduled,
+    /// `ConfirmSuspension` proposal opened alongside the suspension
+    pub confirmation_proposal_id: u64,
+}
+
+#[cw_serde]
+pub struct KeyRotation {
+    pub old_address: Addr,
+    pub executable_at: Scheduled,
+}
+
+#[cw_serde]
+pub struct Profile {
+    pub display_name: Option<DisplayName>,
+    pub avatar_uri: Option<Uri>,
+    pub bio_hash: Option<ContentHash>,
+}
+
+/// What an executed proposal is reduced to once its retention period has passed
+#[cw_serde]
+pub struct ArchivedProposal {
+    pub id: u64,
+    /// sha256 of the title
+    pub title_hash: HexBinary,
+    pub recipient: Addr,
+    pub amount: Uint128,
+    pub votes_for: Uint128,
+    pub votes_against: Uint128,
+    pub execution: Execution,
+}
+
+/// Entries of one kind of record and their approximate footprint, namespaced key plus value
+#[cw_serde]
+#[derive(Copy, Default)]
+pub struct StorageUsage {
+    pub count: u64,
+    pub bytes: u64,
+}
+
+/// Proposal activity in one `ANALYTICS_PERIOD` of block time, updated as proposals open and
+/// are decided
+#[cw_serde]
+#[derive(Default)]
+pub struct PeriodStats {
+    pub created: u32,
+    /// Counted when executed, the first point a pass is recorded
+    pub passed: u32,
+    /// Counted when closed
+    pub rejected: u32,
+    pub granted: Uint128,
+    /// Sum of the share of the electorate that voted, over passed and rejected proposals
+    pub participation: Decimal,
+    /// Seconds from creation to execution of each proposal passed in the period
+    pub execution_times: Vec<u64>,
+}
+
+#[cw_serde]
+pub struct Announcement {
+    pub proposal_id: u64,
+    pub title: Title,
+    pub body: Description,
+    pub published_at: Timestamp,
+}
+
+/// Denom of deployments from before it was configurable
+pub const DEFAULT_DENOM: &str = "udevcore";
+
+fn default_denom() -> String {
+    DEFAULT_DENOM.to_string()
+}
+
+#[cw_serde]
+pub struct Config {
+    /// Native denom the treasury holds and every payout is made in, fixed at instantiation
+    #[serde(default = "default_denom")]
+    pub denom: String,
+    /// Contract notified when proposals open, pass or execute
+    pub notifier: Option<Addr>,
+    /// Mixed into every vote receipt hash
+    pub receipt_salt: Option<String>,
+    /// May suspend members pending a governance confirmation vote
+    pub admin: Option<Addr>,
+    /// Seconds or blocks a key rotation waits before the new address can confirm it
+    pub key_rotation_delay: Option<u64>,
+    /// Unit of every delay and duration, time when unset
+    pub clock: Option<ClockMode>,
+    /// Average seconds per block, only used to report deadlines in both units
+    pub expected_block_time: Option<u64>,
+    /// Seconds or blocks after execution before a proposal may be archived, never when unset
+    pub archive_after: Option<u64>,
+    /// Share of each executed grant paid on top of it to the frontend it was submitted through
+    #[serde(default)]
+    pub referral_fee: Option<Decimal>,
+    /// DAO this one holds a membership in, voted in through `ParentVote` proposals
+    #[serde(default)]
+    pub parent_dao: Option<Addr>,
+    /// Seconds or blocks a proposal stays open for voting, no deadline when unset
+    #[serde(default)]
+    pub voting_period: Option<u64>,
+    /// Share of the total weight that has to vote for a proposal to pass, none when unset
+    #[serde(default)]
+    pub quorum: Option<Decimal>,
+    /// Share of the cast weight approvals have to exceed, half when unset
+    #[serde(default)]
+    pub threshold: Option<Decimal>,
+    /// Threshold for `Expense` proposals, the regular one when unset
+    #[serde(default)]
+    pub expense_threshold: Option<Decimal>,
+    /// Attached to every `Propose` in `denom`, refunded once the proposal passes and
+    /// forfeited if it is rejected or expires
+    #[serde(default)]
+    pub proposal_deposit: Option<Uint128>,
+    /// Burns forfeited deposits rather than keeping them in the treasury
+    #[serde(default)]
+    pub burn_forfeited_deposits: bool,
+    #[serde(default)]
+    pub tie_break: TieBreak,
+    #[serde(default)]
+    pub group_quorums: Vec<GroupQuorum>,
+    /// Runs the DAO in shadow mode on this much simulated treasury, payouts are booked in the
+    /// shadow ledger instead of sent until a `GoLive` proposal is executed
+    #[serde(default)]
+    pub shadow_funds: Option<Uint128>,
+    /// Seconds or blocks after voting ends before a passed proposal may execute, so members
+    /// can react to a malicious one
+    #[serde(default)]
+    pub execution_delay: Option<u64>,
+    /// Seconds or blocks a passed proposal stays executable once its delay is over, after
+    /// which it lapses, never when unset
+    #[serde(default)]
+    pub execution_expiration: Option<u64>,
+    /// Hides individual ballots from queries until governance discloses them, only tallies
+    /// stay public
+    #[serde(default)]
+    pub private_ballots: bool,
+    /// Grace windows for proposals that miss quorum, the first rule matching one applies
+    #[serde(default)]
+    pub quorum_decay: Vec<QuorumDecay>,
+    /// Most a single proposal may pay out, proposed or imported, no cap when unset
+    #[serde(default)]
+    pub max_proposal_amount: Option<Uint128>,
+    /// Withholds vested claims from recipients who voted on the grant paying them
+    #[serde(default)]
+    pub lock_self_voted_vesting: bool,
+    /// How ballots count towards the threshold of proposals opened from here on
+    #[serde(default)]
+    pub voting_strategy: VotingStrategy,
+    /// Where proposals opened from here on take voting weight from. Proposing and the member
+    /// list's own management stay with `MEMBERS` either way
+    #[serde(default)]
+    pub membership: MembershipSource,
+    /// Seconds or blocks a removed member has to claim their pending reward payouts before
+    /// anyone may prune them, never pruned when unset
+    #[serde(default)]
+    pub removal_grace_period: Option<u64>,
+    /// Keeps drafts out of queries until voting opens on them, members and the admin read them
+    /// through `ReadProposal`
+    #[serde(default)]
+    pub private_drafts: bool,
+    /// Lets a security committee pay bounties without a proposal, disclosed after a delay
+    #[serde(default)]
+    pub security_lane: Option<SecurityLane>,
+    /// Router `Swap` proposals trade through, they are rejected on chains without one
+    #[serde(default)]
+    pub dex_router: Option<Addr>,
+    /// cw20 contracts whose `Receive` is credited to the treasury ledger, any other is refused
+    #[serde(default)]
+    pub cw20_tokens: Vec<Addr>,
+    /// May veto passed proposals while they wait out `execution_delay`, replaced only through
+    /// an `UpdateConfig` proposal
+    pub guardian: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct SecurityLane {
+    pub committee: Vec<Addr>,
+    /// Distinct committee members who have to approve a payout before it is sent
+    pub required_approvals: u32,
+    /// Most a single payout may send
+    pub payout_cap: Uint128,
+    /// Most the lane may send in total, raised through `UpdateConfig`
+    pub budget: Uint128,
+    /// Seconds or blocks after a payout is sent before its details are disclosed
+    pub disclosure_delay: u64,
+}
+
+#[cw_serde]
+pub struct SecurityPayout {
+    pub recipient: Addr,
+    pub amount: Uint128,
+    /// Hash of the confidential report the payout rewards
+    pub report_hash: ContentHash,
+    pub approvals: Vec<Addr>,
+    /// Set once enough approvals came in and the payout was sent
+    pub paid_at: Option<Timestamp>,
+    pub disclose_at: Option<Scheduled>,
+}
+
+macro_rules! config_update {
+    ($($field:ident: $ty:ty,)*) => {
+        /// The fields a scheduled `UpdateConfig` changes, merged into the config as it is when
+        /// they take effect so that changes made in between are kept
+        #[cw_serde]
+        #[derive(Default)]
+        pub struct ConfigUpdate {
+            $(
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                pub $field: Option<$ty>,
+            )*
+        }
+
+        impl ConfigUpdate {
+            /// What going from `from` to `to` changes
+            pub fn between(from: &Config, to: &Config) -> Self {
+                // exhaustive, a field added to `Config` has to be added here too
+                let Config { $($field: _,)* } = to;
+                ConfigUpdate {
+                    $($field: (from.$field != to.$field).then(|| to.$field.clone()),)*
+                }
+            }
+
+            pub fn apply(self, config: &mut Config) {
+                $(
+                    if let Some(value) = self.$field {
+                        config.$field = value;
+                    }
+                )*
+            }
+        }
+    };
+}
+
+config_update! {
+    denom: String,
+    notifier: Option<Addr>,
+    receipt_salt: Option<String>,
+    admin: Option<Addr>,
+    key_rotation_delay: Option<u64>,
+    clock: Option<ClockMode>,
+    expected_block_time: Option<u64>,
+    archive_after: Option<u64>,
+    referral_fee: Option<Decimal>,
+    parent_dao: Option<Addr>,
+    voting_period: Option<u64>,
+    quorum: Option<Decimal>,
+    threshold: Option<Decimal>,
+    expense_threshold: Option<Decimal>,
+    proposal_deposit: Option<Uint128>,
+    burn_forfeited_deposits: bool,
+    tie_break: TieBreak,
+    group_quorums: Vec<GroupQuorum>,
+    shadow_funds: Option<Uint128>,
+    execution_delay: Option<u64>,
+    execution_expiration: Option<u64>,
+    private_ballots: bool,
+    quorum_decay: Vec<QuorumDecay>,
+    max_proposal_amount: Option<Uint128>,
+    lock_self_voted_vesting: bool,
+    voting_strategy: VotingStrategy,
+    membership: MembershipSource,
+    removal_grace_period: Option<u64>,
+    private_drafts: bool,
+    security_lane: Option<SecurityLane>,
+    dex_router: Option<Addr>,
+    cw20_tokens: Vec<Addr>,
+    guardian: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct PendingConfig {
+    pub changes: ConfigUpdate,
+    pub effective_at: Scheduled,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const PENDING_CONFIG: Item<PendingConfig> = Item::new("pending_config");
+pub const NEXT_PROPOSAL_ID: Item<u64> = Item::new("next_proposal_id");
+/// Proposals by id, indexed by stored status, proposer and recipient. Archived proposals leave
+/// the map and every index
+pub const PROPOSALS: IndexedMap<u64, Proposal, ProposalIndexes> = IndexedMap::new(
+    "proposals",
+    ProposalIndexes {
+        status: MultiIndex::new(stored_status, "proposals", "proposals__status"),
+        proposer: AddrIndex::new(proposer_of, "proposals__proposer"),
+        recipient: AddrIndex::new(recipients_of, "proposals__recipient"),
+    },
+);
+pub const ARCHIVE: Map<u64, ArchivedProposal> = Map::new("archive");
+/// Executed proposals by execution time in nanoseconds then id, archived ones included
+pub const EXECUTED_AT: Map<(u64, u64), Empty> = Map::new("executed_at");
+pub const TALLIES: Map<u64, Tally> = Map::new("tallies");
+pub const BALLOTS: Map<(u64, &Addr), Ballot> = Map::new("ballots");
+/// Proposals whose ballots governance has disclosed
+pub const DISCLOSED_BALLOTS: Map<u64, Empty> = Map::new("disclosed_ballots");
+pub const MEMBERS: Map<&Addr, Member> = Map::new("members");
+pub const TOTAL_WEIGHT: Item<Uint128> = Item::new("total_weight");
+/// Funds reserved for passing proposals, which no other proposal may spend
+pub const COMMITTED: Item<Uint128> = Item::new("committed");
+/// Sum of every executed proposal's amount
+pub const GRANTED: Item<Uint128> = Item::new("granted");
+/// Every member keyed by weight then address, kept in step with `MEMBERS`
+pub const MEMBERS_BY_WEIGHT: Map<(u128, &Addr), Empty> = Map::new("members_by_weight");
+/// Each member's weight with its history, so votes count what a member held when the
+/// proposal opened
+pub const VOTING_POWER: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "voting_power",
+    "voting_power__checkpoints",
+    "voting_power__changelog",
+    Strategy::EveryBlock,
+);
+/// Address each rotated key took over from, to find its voting power in older snapshots
+pub const ROTATED_FROM: Map<&Addr, Addr> = Map::new("rotated_from");
+/// Deposits and bounties not yet settled, held back from anything the treasury pays out
+pub const HELD_DEPOSITS: Item<Uint128> = Item::new("held_deposits");
+pub const PROFILES: Map<&Addr, Profile> = Map::new("profiles");
+/// Address each member lets vote on their behalf
+pub const VOTE_OPERATORS: Map<&Addr, Addr> = Map::new("vote_operators");
+/// cw20 holdings by token contract, credited by `Receive` and debited by proposals that
+/// transfer, send or burn them
+pub const CW20_TREASURY: Map<&Addr, Uint128> = Map::new("cw20_treasury");
+/// Member each delegator lends their voting weight to
+pub const DELEGATIONS: Map<&Addr, Addr> = Map::new("delegations");
+/// Delegators by delegate, the reverse of `DELEGATIONS`
+pub const DELEGATORS: Map<(&Addr, &Addr), Empty> = Map::new("delegators");
+/// Delegate whose ballot carries each delegator's weight, by proposal. Kept apart from the
+/// delegations so that undelegating does not free weight already cast
+pub const CARRIED_VOTES: Map<(u64, &Addr), Addr> = Map::new("carried_votes");
+pub const FRONTENDS: Map<&str, Frontend> = Map::new("frontends");
+pub const GROUPS: Map<&str, Group> = Map::new("groups");
+pub const MEMBER_GROUPS: Map<&Addr, GroupId> = Map::new("member_groups");
+/// Local proposal each mirrored parent proposal was opened as
+pub const MIRRORED_PROPOSALS: Map<u64, u64> = Map::new("mirrored_proposals");
+/// Simulated payouts per recipient, booked while in shadow mode
+pub const SHADOW_LEDGER: Map<&Addr, Uint128> = Map::new("shadow_ledger");
+/// Sum of `SHADOW_LEDGER`, drawn from `Config::shadow_funds`
+pub const SHADOW_SPENT: Item<Uint128> = Item::new("shadow_spent");
+/// Referral fees paid to each frontend so far
+pub const REFERRAL_FEES: Map<&str, Uint128> = Map::new("referral_fees");
+pub const ANNOUNCEMENTS: Map<u64, Announcement> = Map::new("announcements");
+pub const VESTINGS: Map<u64, Vesting> = Map::new("vestings");
+pub const SUSPENSIONS: Map<&Addr, Suspension> = Map::new("suspensions");
+pub const SECURITY_PAYOUTS: Map<u64, SecurityPayout> = Map::new("security_payouts");
+pub const NEXT_SECURITY_PAYOUT_ID: Item<u64> = Item::new("next_security_payout_id");
+/// Sent through the security lane so far, counted against its budget
+pub const SECURITY_SPENT: Item<Uint128> = Item::new("security_spent");
+/// When each removed member's claim window closes, cleared if they are added back
+pub const CLAIM_WINDOWS: Map<&Addr, Scheduled> = Map::new("claim_windows");
+/// Pending rotations keyed by the new address, which has to confirm them
+pub const KEY_ROTATIONS: Map<&Addr, KeyRotation> = Map::new("key_rotations");
+pub const REWARD_DISTRIBUTIONS: Map<u64, RewardDistribution> = Map::new("reward_distributions");
+pub const REWARD_PAYOUTS: Map<(u64, &Addr), Uint128> = Map::new("reward_payouts");
+/// The distributions each member still has a payout in, kept in step with `REWARD_PAYOUTS`
+pub const PAYOUTS_BY_MEMBER: Map<(&Addr, u64), Empty> = Map::new("payouts_by_member");
+/// Keyed by block time divided by the period length
+pub const ANALYTICS: Map<u64, PeriodStats> = Map::new("analytics");
+/// Append-only log of the messages executions sent, keyed by a running sequence number
+pub const OUTBOX: Map<u64, OutboxEntry> = Map::new("outbox");
+pub const NEXT_OUTBOX_ID: Item<u64> = Item::new("next_outbox_id");
+/// Keyed by record kind, kept up to date by every write to the tracked maps
+pub const STORAGE_USAGE: Map<&str, StorageUsage> = Map::new("storage_usage");
+
+/// Where the contract's records sit in its raw storage, for light clients checking them against
+/// state proofs. A map entry's key is its namespace, prefixed with the namespace length as two
+/// big-endian bytes, followed by the entry key. Every part of a composite key but the last is
+/// length-prefixed the same way, `u64` parts are 8 big-endian bytes and addresses their string
+/// bytes. An item's key is its bare namespace. Values are the records' JSON encoding.
+pub struct ProposalIndexes<'a> {
+    /// The status as last written, which lags for proposals that ended since
+    pub status: MultiIndex<'a, &'a str, Proposal, u64>,
+    pub proposer: AddrIndex<'a>,
+    pub recipient: AddrIndex<'a>,
+}
+
+impl IndexList<Proposal> for ProposalIndexes<'_> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Proposal>> + '_> {
+        let indexes: Vec<&dyn Index<Proposal>> =
+            vec![&self.status, &self.proposer, &self.recipient];
+        Box::new(indexes.into_iter())
+    }
+}
+
+fn stored_status(_pk: &[u8], proposal: &Proposal) -> &'static str {
+    proposal.status.as_str()
+}
+
+fn proposer_of(proposal: &Proposal) -> Vec<Addr> {
+    proposal.proposer.iter().cloned().collect()
+}
+
+// a batch is listed under each of its payout recipients as well
+fn recipients_of(proposal: &Proposal) -> Vec<Addr> {
+    let mut recipients = vec![proposal.recipient.clone()];
+    if let ProposalType::GrantBatch { payouts } = &proposal.proposal_type {
+        for payout in payouts {
+            if !recipients.contains(&payout.recipient) {
+                recipients.push(payout.recipient.clone());
+            }
+        }
+    }
+    recipients
+}
+
+/// Proposal ids by address for fields a proposal holds none or several of, which a
+/// `MultiIndex` with its one key per record cannot express
+pub struct AddrIndex<'a> {
+    addresses: fn(&Proposal) -> Vec<Addr>,
+    ids: Map<'a, (Addr, u64), Empty>,
+}
+
+impl<'a> AddrIndex<'a> {
+    pub const fn new(addresses: fn(&Proposal) -> Vec<Addr>, namespace: &'a str) -> Self {
+        AddrIndex {
+            addresses,
+            ids: Map::new(namespace),
+        }
+    }
+
+    pub fn prefix(&self, address: Addr) -> Prefix<u64, Empty, u64> {
+        self.ids.prefix(address)
+    }
+}
+
+impl Index<Proposal> for AddrIndex<'_> {
+    fn save(&self, store: &mut dyn Storage, pk: &[u8], data: &Proposal) -> StdResult<()> {
+        let id = u64::from_slice(pk)?;
+        for address in (self.addresses)(data) {
+            self.ids.save(store, (address, id), &Empty {})?;
+        }
+        Ok(())
+    }
+
+    fn remove(&self, store: &mut dyn Storage, pk: &[u8], old_data: &Proposal) -> StdResult<()> {
+        let id = u64::from_slice(pk)?;
+        for address in (self.addresses)(old_data) {
+            self.ids.remove(store, (address, id));
+        }
+        Ok(())
+    }
+}
+
+pub fn member_key(address: &Addr) -> Vec<u8> {
+    MEMBERS.key(address).to_vec()
+}
+
+pub fn total_weight_key() -> Vec<u8> {
+    TOTAL_WEIGHT.as_slice().to_vec()
+}
+
+pub fn ballot_key(proposal_id: u64, voter: &Addr) -> Vec<u8> {
+    BALLOTS.key((proposal_id, voter)).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_keys() {
+        let voter = Addr::unchecked("addr1");
+        assert_eq!(b"\x00\x07membersaddr1".to_vec(), member_key(&voter));
+        assert_eq!(b"total_weight".to_vec(), total_weight_key());
+
+        let mut expected = b"\x00\x07ballots\x00\x08".to_vec();
+        expected.extend(7_u64.to_be_bytes());
+        expected.extend(b"addr1");
+        assert_eq!(expected, ballot_key(7, &voter));
+    }
+
+    #[test]
+    fn later_fields_default() {
+        // as stored once tallies moved out, before types, statuses and deposits existed
+        let proposal: Proposal = cosmwasm_std::from_json(
+            br#"{"id":0,"title":"Round 1","description":"","recipient":"addr1","amount":"100","executed":false,"execution":null}"#,
+        )
+        .unwrap();
+        assert_eq!(ProposalType::Grant, proposal.proposal_type);
+        assert_eq!(ProposalStatus::Open, proposal.status);
+        assert_eq!(None, proposal.deposit);
+
+        let tally: Tally =
+            cosmwasm_std::from_json(br#"{"votes_for":"1","votes_against":"0"}"#).unwrap();
+        assert_eq!(None, tally.voting_end);
+        assert!(!tally.locked);
+    }
+}
@@ -0,0 +1,135 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Isqrt, Uint128};
+
+/// How ballots count towards a proposal's threshold, fixed when the proposal is opened. Quorum
+/// is always measured in member weight
+#[cw_serde]
+#[derive(Default)]
+pub enum VotingStrategy {
+    /// A ballot counts for the voter's full weight
+    #[default]
+    Linear,
+    /// For the square root of the voter's weight, so large holders count for less
+    Quadratic,
+    /// For the voter's weight up to `cap`
+    Capped { cap: Uint128 },
+    /// For the share of the voting period still left when the ballot is cast, support that
+    /// stood longer counts for more. Needs `voting_period`
+    Conviction,
+}
+
+/// How much of the voting period is left, in whichever unit it is scheduled in
+pub struct VotingWindow {
+    pub remaining: u64,
+    pub period: u64,
+}
+
+/// Voting math behind a `VotingStrategy`, the handlers only ever see the weight it returns
+pub(crate) trait TallyStrategy {
+    /// Never more than `weight`, so the weight still outstanding always bounds what could
+    /// overturn a tally
+    fn counted(&self, weight: Uint128, window: &VotingWindow) -> Uint128;
+}
+
+struct Linear;
+
+struct Quadratic;
+
+struct Capped {
+    cap: Uint128,
+}
+
+struct Conviction;
+
+impl TallyStrategy for Linear {
+    fn counted(&self, weight: Uint128, _window: &VotingWindow) -> Uint128 {
+        weight
+    }
+}
+
+impl TallyStrategy for Quadratic {
+    fn counted(&self, weight: Uint128, _window: &VotingWindow) -> Uint128 {
+        weight.isqrt()
+    }
+}
+
+impl TallyStrategy for Capped {
+    fn counted(&self, weight: Uint128, _window: &VotingWindow) -> Uint128 {
+        weight.min(self.cap)
+    }
+}
+
+impl TallyStrategy for Conviction {
+    fn counted(&self, weight: Uint128, window: &VotingWindow) -> Uint128 {
+        if window.period == 0 {
+            return weight;
+        }
+        // extensions past the deadline leave more than a period, but never more than a full
+        // ballot
+        weight.multiply_ratio(window.remaining.min(window.period), window.period)
+    }
+}
+
+impl VotingStrategy {
+    pub(crate) fn tally(&self) -> Box<dyn TallyStrategy> {
+        match self {
+            VotingStrategy::Linear => Box::new(Linear),
+            VotingStrategy::Quadratic => Box::new(Quadratic),
+            VotingStrategy::Capped { cap } => Box::new(Capped { cap: *cap }),
+            VotingStrategy::Conviction => Box::new(Conviction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL: VotingWindow = VotingWindow {
+        remaining: 100,
+        period: 100,
+    };
+
+    #[test]
+    fn linear_counts_full_weight() {
+        assert_eq!(Uint128::new(40), Linear.counted(Uint128::new(40), &FULL));
+    }
+
+    #[test]
+    fn quadratic_takes_square_root() {
+        assert_eq!(
+            Uint128::new(10),
+            Quadratic.counted(Uint128::new(100), &FULL)
+        );
+        // rounded down
+        assert_eq!(Uint128::new(9), Quadratic.counted(Uint128::new(99), &FULL));
+        assert_eq!(Uint128::one(), Quadratic.counted(Uint128::one(), &FULL));
+    }
+
+    #[test]
+    fn capped_at_cap() {
+        let capped = Capped {
+            cap: Uint128::new(25),
+        };
+        assert_eq!(Uint128::new(25), capped.counted(Uint128::new(40), &FULL));
+        assert_eq!(Uint128::new(10), capped.counted(Uint128::new(10), &FULL));
+    }
+
+    #[test]
+    fn conviction_scales_with_time_left() {
+        let weight = Uint128::new(40);
+        assert_eq!(weight, Conviction.counted(weight, &FULL));
+
+        let quarter = VotingWindow {
+            remaining: 25,
+            period: 100,
+        };
+        assert_eq!(Uint128::new(10), Conviction.counted(weight, &quarter));
+
+        let extended = VotingWindow {
+            remaining: 150,
+            period: 100,
+        };
+        assert_eq!(weight, Conviction.counted(weight, &extended));
+    }
+}